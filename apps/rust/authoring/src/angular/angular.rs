@@ -1,21 +1,29 @@
 #![allow(clippy::wildcard_imports, clippy::option_map_unit_fn)]
 
-use std::{cell::RefCell, rc::Rc, sync::Arc};
+use std::{rc::Rc, sync::Arc};
 
 use super::context::{AngularContext, AngularCtx};
 use super::transformers::DependencyInjection;
-use super::{InjectableCreator, TopLevelDecorator};
+use super::{
+    ComponentCreator, DirectiveCreator, InjectableCreator, NgModuleCreator, PipeCreator,
+    TopLevelDecorator,
+};
 use oxc_allocator::Allocator;
-use oxc_ast::{ast::*, AstBuilder, AstKind, VisitMut};
-use oxc_diagnostics::Error;
-use oxc_semantic::Semantic;
-use oxc_span::SourceType;
+use oxc_ast::{ast::*, visit::walk_mut, AstBuilder, VisitMut};
+use oxc_diagnostics::{Error, Severity};
+use oxc_semantic::{Semantic, SymbolFlags};
+use oxc_span::{SourceType, SPAN};
+use tracing::debug;
 
 #[derive()]
 pub struct Angular<'a> {
     ctx: AngularCtx<'a>,
-    dependency_injection: Option<DependencyInjection<'a>>,
+    dependency_injection: DependencyInjection<'a>,
     injectable_creator: Option<InjectableCreator<'a>>,
+    component_creator: Option<ComponentCreator<'a>>,
+    directive_creator: Option<DirectiveCreator<'a>>,
+    pipe_creator: Option<PipeCreator<'a>>,
+    ng_module_creator: Option<NgModuleCreator<'a>>,
 }
 
 impl<'a> Angular<'a> {
@@ -26,33 +34,58 @@ impl<'a> Angular<'a> {
         semantic: Semantic<'a>,
     ) -> Self {
         let ast = Rc::new(AstBuilder::new(allocator));
-        let ctx = AngularCtx::new(
-            Rc::clone(&ast),
-            Rc::new(RefCell::new(semantic)),
-        );
+        let ctx = AngularCtx::new(Rc::clone(&ast), semantic);
         Self {
             ctx: ctx.clone(),
             dependency_injection: DependencyInjection::new(Rc::clone(&ast), ctx.clone()),
             injectable_creator: InjectableCreator::new(Rc::clone(&ast), ctx.clone()),
+            component_creator: ComponentCreator::new(Rc::clone(&ast), ctx.clone()),
+            directive_creator: DirectiveCreator::new(Rc::clone(&ast), ctx.clone()),
+            pipe_creator: PipeCreator::new(Rc::clone(&ast), ctx.clone()),
+            ng_module_creator: NgModuleCreator::new(Rc::clone(&ast), ctx.clone()),
         }
     }
 
-    pub fn build(mut self, program: &mut Program<'a>) -> Result<(), Vec<Error>> {
+    /// Compiles the decorators of `program`, returning the warnings, or every diagnostic when
+    /// one of them is an error.
+    pub fn build(mut self, program: &mut Program<'a>) -> Result<Vec<Error>, Vec<Error>> {
         self.visit_program(program);
-        let errors: Vec<_> = self
+        let diagnostics: Vec<_> = self
             .ctx
             .errors()
             .into_iter()
-            .map(|e| e.with_source_code(Arc::new(self.ctx.semantic().source_text().to_string())))
+            .map(|e| e.with_source_code(Arc::new(self.ctx.source_text().to_string())))
             .collect();
 
-        if errors.is_empty() {
-            Ok(())
+        let is_error = |diagnostic: &Error| {
+            diagnostic.severity().unwrap_or(Severity::Error) == Severity::Error
+        };
+        if diagnostics.iter().any(is_error) {
+            Err(diagnostics)
         } else {
-            Err(errors)
+            Ok(diagnostics)
         }
     }
 
+    /// Whether `statement` declares the class `name`, exported or not.
+    fn declares_class(statement: &Statement, name: &str) -> bool {
+        let class = match statement {
+            Statement::ClassDeclaration(class) => Some(&**class),
+            Statement::ExportNamedDeclaration(export) => match &export.declaration {
+                Some(Declaration::ClassDeclaration(class)) => Some(&**class),
+                _ => None,
+            },
+            Statement::ExportDefaultDeclaration(export) => match &export.declaration {
+                ExportDefaultDeclarationKind::ClassDeclaration(class) => Some(&**class),
+                _ => None,
+            },
+            _ => None,
+        };
+        class
+            .and_then(|class| class.id.as_ref())
+            .is_some_and(|id| id.name == name)
+    }
+
     fn remove_import_specifiers_matching_imported_name(
         specifiers: &mut Option<oxc_allocator::Vec<'a, ImportDeclarationSpecifier<'a>>>,
         desired_imported_names: Vec<&str>,
@@ -60,7 +93,8 @@ impl<'a> Angular<'a> {
         if let Some(specs) = specifiers.as_mut() {
             specs.retain(|specifier| {
                 if let ImportDeclarationSpecifier::ImportSpecifier(import_spec) = specifier {
-                    let imported_name_str: &str = &import_spec.imported.name();
+                    let imported_name = import_spec.imported.name();
+                    let imported_name_str: &str = &imported_name;
                     !desired_imported_names.contains(&imported_name_str)
                 } else {
                     true
@@ -71,6 +105,46 @@ impl<'a> Angular<'a> {
 }
 
 impl<'a> VisitMut<'a> for Angular<'a> {
+    fn visit_program(&mut self, program: &mut Program<'a>) {
+        self.dependency_injection.transform_program(program);
+
+        walk_mut::walk_program(self, program);
+
+        // Template functions of the components, declared after the imports.
+        if let Some(declarations) = self
+            .component_creator
+            .as_mut()
+            .map(|t| t.take_declarations())
+        {
+            let position = program
+                .body
+                .iter()
+                .position(|statement| !matches!(statement, Statement::ImportDeclaration(_)))
+                .unwrap_or(program.body.len());
+            for (offset, declaration) in declarations.into_iter().enumerate() {
+                program.body.insert(position + offset, declaration);
+            }
+        }
+
+        // Module registrations, right after the class they register.
+        if let Some(side_effects) = self
+            .ng_module_creator
+            .as_mut()
+            .map(|t| t.take_side_effects())
+        {
+            for (class_name, statement) in side_effects.into_iter().rev() {
+                let position = program
+                    .body
+                    .iter()
+                    .position(|statement| Self::declares_class(statement, &class_name))
+                    .map_or(program.body.len(), |index| index + 1);
+                program.body.insert(position, statement);
+            }
+        }
+
+        super::imports::manage_imports(&self.ctx.0, &self.ctx, program);
+    }
+
     fn visit_class_body(&mut self, class_body: &mut ClassBody<'a>) {
         class_body.body.iter_mut().for_each(|class_element| {
             self.visit_class_element(class_element);
@@ -78,77 +152,186 @@ impl<'a> VisitMut<'a> for Angular<'a> {
     }
 
     fn visit_class(&mut self, class: &mut Class<'a>) {
-    //TODO: Strip this into context?
-    let mut top_level_decorators: Vec<(TopLevelDecorator, usize)> = Vec::new();
-    for (index, decorator) in class.decorators.iter().enumerate() {
-        if let Expression::CallExpression(boxed_expr) = &decorator.expression {
-            if let Expression::Identifier(identifier) = &boxed_expr.callee {
-                if let Some(top_level_decorator) =
-                    TopLevelDecorator::from_str(&identifier.name, decorator)
-                {
-                    top_level_decorators.push((top_level_decorator, index));
+        //TODO: Strip this into context?
+        let mut top_level_decorators: Vec<(TopLevelDecorator, usize)> = Vec::new();
+        let mut diagnostics = Vec::new();
+        for (index, decorator) in class.decorators.iter().enumerate() {
+            if let Expression::CallExpression(boxed_expr) = &decorator.expression {
+                if let Expression::Identifier(identifier) = &boxed_expr.callee {
+                    if let Some(top_level_decorator) =
+                        TopLevelDecorator::from_str(&identifier.name, decorator, &mut diagnostics)
+                    {
+                        top_level_decorators.push((top_level_decorator, index));
+                    }
                 }
             }
         }
-    }
+        for diagnostic in diagnostics {
+            self.ctx.error(diagnostic);
+        }
+
+        // Anonymous classes are named, so their definitions can refer to them.
+        if !top_level_decorators.is_empty() && class.id.is_none() {
+            let name = self.ctx.generate_uid("class");
+            let id = self.ctx.0.binding_identifier(SPAN, self.ctx.0.atom(&name));
+            if let Some(scope_id) = class.scope_id.get() {
+                id.symbol_id.set(Some(self.ctx.add_binding(
+                    scope_id,
+                    name,
+                    SymbolFlags::Class,
+                )));
+            }
+            class.id = Some(id);
+        }
+        if let Some(id) = class
+            .id
+            .as_ref()
+            .filter(|_| !top_level_decorators.is_empty())
+        {
+            debug!(
+                class = %id.name,
+                decorators = top_level_decorators.len(),
+                "compiling decorated class"
+            );
+        }
 
-    self.dependency_injection
-        .as_mut()
-        .map(|t: &mut DependencyInjection<'_>| t.transform_class(class, &top_level_decorators));
+        self.dependency_injection
+            .transform_class(class, &top_level_decorators);
 
-    self.injectable_creator
-        .as_mut()
-        .map(|t: &mut InjectableCreator<'_>| t.transform_class(class, &top_level_decorators));
-    }
+        self.injectable_creator
+            .as_mut()
+            .map(|t: &mut InjectableCreator<'_>| t.transform_class(class, &top_level_decorators));
 
+        self.component_creator
+            .as_mut()
+            .map(|t: &mut ComponentCreator<'_>| t.transform_class(class, &top_level_decorators));
 
-    fn visit_import_declaration(&mut self, decl: &mut ImportDeclaration<'a>) {
-        let kind = AstKind::ImportDeclaration(self.alloc(decl));
+        self.directive_creator
+            .as_mut()
+            .map(|t: &mut DirectiveCreator<'_>| t.transform_class(class, &top_level_decorators));
 
-        let is_angular = decl
-            .source
-            .value
-            .to_compact_string()
-            .starts_with("@angular");
-        self.enter_node(kind);
+        self.pipe_creator
+            .as_mut()
+            .map(|t: &mut PipeCreator<'_>| t.transform_class(class, &top_level_decorators));
+
+        self.ng_module_creator
+            .as_mut()
+            .map(|t: &mut NgModuleCreator<'_>| t.transform_class(class, &top_level_decorators));
+
+        // Removed once every definition is built, as the creators refer to them by index.
+        for (_, index) in top_level_decorators.iter().rev() {
+            class.decorators.remove(*index);
+        }
+    }
+
+    fn visit_import_declaration(&mut self, decl: &mut ImportDeclaration<'a>) {
+        let is_angular = decl.source.value.starts_with("@angular");
 
         if is_angular {
             let mut all_specifiers_to_remove: Vec<&str> = Vec::new();
             all_specifiers_to_remove.extend(DependencyInjection::specifier_to_remove());
             all_specifiers_to_remove.extend(InjectableCreator::specifier_to_remove());
+            all_specifiers_to_remove.extend(ComponentCreator::specifier_to_remove());
+            all_specifiers_to_remove.extend(DirectiveCreator::specifier_to_remove());
+            all_specifiers_to_remove.extend(PipeCreator::specifier_to_remove());
+            all_specifiers_to_remove.extend(NgModuleCreator::specifier_to_remove());
             Self::remove_import_specifiers_matching_imported_name(
                 &mut decl.specifiers,
-                all_specifiers_to_remove
+                all_specifiers_to_remove,
             );
         }
-        if let Some(specifiers) = &mut decl.specifiers {
-            for specifier in specifiers.iter_mut() {
-                self.visit_import_declaration_specifier(specifier);
+        walk_mut::walk_import_declaration(self, decl);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oxc_allocator::Allocator;
+    use oxc_ast::{ast::*, Visit};
+    use oxc_parser::Parser;
+    use oxc_semantic::{SemanticBuilder, SymbolId};
+    use oxc_span::SourceType;
+
+    use super::super::context::AngularContext;
+    use super::Angular;
+
+    /// The symbols of the bindings and references named `name`, in source order.
+    #[derive(Default)]
+    struct Named {
+        name: &'static str,
+        bindings: Vec<SymbolId>,
+        references: Vec<Option<oxc_semantic::ReferenceId>>,
+    }
+
+    impl<'a> Visit<'a> for Named {
+        fn visit_binding_identifier(&mut self, it: &BindingIdentifier<'a>) {
+            if it.name == self.name {
+                self.bindings.extend(it.symbol_id.get());
             }
         }
-        self.visit_string_literal(&mut decl.source);
-        self.leave_node(kind);
-    }
 
-    fn visit_statements(&mut self, stmts: &mut oxc_allocator::Vec<'a, Statement<'a>>) {
-        for stmt in stmts.iter_mut() {
-            self.visit_statement(stmt);
+        fn visit_identifier_reference(&mut self, it: &IdentifierReference<'a>) {
+            if it.name == self.name {
+                self.references.push(it.reference_id.get());
+            }
         }
-        // self.dependency_injection.as_mut().map(|t| t.transform_statements(stmts));
     }
 
-    fn visit_expression(&mut self, expr: &mut Expression<'a>) {
-        // self.dependency_injection.as_mut().map(|t| t.transform_expression(expr));
+    #[test]
+    fn generated_locals_get_their_own_bindings() {
+        let source = r#"
+            import { Injectable } from "@angular/core";
+            const t = 1;
+            @Injectable()
+            export class X {
+                constructor() { var v = t; }
+            }
+        "#;
+        let allocator = Allocator::default();
+        let source_type = SourceType::ts();
+        let mut program = Parser::new(&allocator, source, source_type).parse().program;
+        let semantic = SemanticBuilder::new(source).build(&program).semantic;
+        let angular = Angular::new(&allocator, source_type, semantic);
+        let ctx = angular.ctx.clone();
+        assert!(angular.build(&mut program).is_ok());
 
-        self.visit_expression_match(expr);
-    }
+        let mut named = Named {
+            name: "t",
+            ..Named::default()
+        };
+        named.visit_program(&program);
+        // The top-level `t` and the parameter of the factory.
+        let [top_level, parameter] = named.bindings[..] else {
+            panic!("expected two bindings of `t`, got {:?}", named.bindings);
+        };
+        assert_ne!(top_level, parameter);
 
-    fn visit_decorator(&mut self, decorator: &mut Decorator<'a>) {
-        let kind = AstKind::Decorator(self.alloc(decorator));
-        // self.dependency_injection.as_mut().map(|t| t.transform_decorator(decorator));
+        let symbols = ctx.symbols();
+        let resolved: Vec<_> = named
+            .references
+            .iter()
+            .map(|reference| {
+                let reference = reference.expect("references are created for generated code");
+                symbols.get_reference(reference).symbol_id()
+            })
+            .collect();
+        // `t || X` in the factory, defined before the constructor, then `var v = t`.
+        assert_eq!(resolved, [Some(parameter), Some(top_level)]);
 
-        self.enter_node(kind);
-        self.visit_expression(&mut decorator.expression);
-        self.leave_node(kind);
+        let mut class = Named {
+            name: "X",
+            ..Named::default()
+        };
+        class.visit_program(&program);
+        let [class_symbol] = class.bindings[..] else {
+            panic!("expected one binding of `X`");
+        };
+        for reference in class.references {
+            let reference = reference.expect("references are created for generated code");
+            assert_eq!(
+                symbols.get_reference(reference).symbol_id(),
+                Some(class_symbol)
+            );
+        }
     }
 }
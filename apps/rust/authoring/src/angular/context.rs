@@ -6,24 +6,72 @@ use std::{
 
 use oxc_ast::AstBuilder;
 use oxc_diagnostics::Error;
-use oxc_semantic::{ScopeId, ScopeTree, Semantic, SymbolId, SymbolTable};
-use oxc_span::{CompactString, SourceType};
+use oxc_semantic::{
+    NodeId, Reference, ReferenceFlags, ReferenceId, ScopeId, ScopeTree, Semantic, SymbolFlags,
+    SymbolId, SymbolTable,
+};
+use oxc_span::{CompactStr, SourceType, SPAN};
 
 #[derive(Clone)]
-pub struct AngularCtx<'a>(pub Rc<AstBuilder<'a>>, Rc<RefCell<Semantic<'a>>>, Rc<RefCell<Vec<Error>>>);
+pub struct AngularCtx<'a>(
+    pub Rc<AstBuilder<'a>>,
+    Rc<RefCell<SemanticModel<'a>>>,
+    Rc<RefCell<Vec<Error>>>,
+    Rc<RefCell<Vec<NamespaceImport>>>,
+);
+
+/// The semantic model of the source, kept up to date with the bindings and references the
+/// transform adds, so later passes can rely on it.
+pub struct SemanticModel<'a> {
+    source_text: &'a str,
+    source_type: SourceType,
+    symbols: SymbolTable,
+    scopes: ScopeTree,
+}
+
+/// `import * as alias from "module"`, added to the output for the generated code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceImport {
+    pub module: CompactStr,
+    pub alias: CompactStr,
+    /// The symbol bound by the import.
+    pub symbol_id: SymbolId,
+}
 
 pub trait AngularContext<'a> {
-    fn new(ast: Rc<AstBuilder<'a>>, semantic: Rc<RefCell<Semantic<'a>>>) -> Self;
+    fn new(ast: Rc<AstBuilder<'a>>, semantic: Semantic<'a>) -> Self;
 
-    fn semantic(&self) -> Ref<'_, Semantic<'a>>;
+    fn source_text(&self) -> &'a str;
 
     fn symbols(&self) -> Ref<'_, SymbolTable>;
 
+    fn symbols_mut(&self) -> RefMut<'_, SymbolTable>;
+
     fn scopes(&self) -> Ref<'_, ScopeTree>;
 
     fn scopes_mut(&self) -> RefMut<'_, ScopeTree>;
 
-    fn add_binding(&self, name: CompactString);
+    /// Declare `name` in `scope_id` with a new symbol.
+    fn add_binding(&self, scope_id: ScopeId, name: CompactStr, flags: SymbolFlags) -> SymbolId;
+
+    /// A reference from generated code to `name`, resolved to `symbol_id`, or else to a global.
+    fn create_reference(
+        &self,
+        name: &str,
+        symbol_id: Option<SymbolId>,
+        flags: ReferenceFlags,
+    ) -> ReferenceId;
+
+    /// Generate a name that is not bound at the top level, e.g. `_Foo` or `_Foo2`, and bind it
+    /// there so the next one differs.
+    fn generate_uid(&self, name: &str) -> CompactStr;
+
+    /// The alias of the namespace import of `module`, e.g. `i0` for `@angular/core`, allocated
+    /// on first use as the first `iN` not bound anywhere in the source.
+    fn namespace(&self, module: &str) -> CompactStr;
+
+    /// The namespace imports the output uses, in the order they were first used.
+    fn namespace_imports(&self) -> Vec<NamespaceImport>;
 
     fn source_type(&self) -> Ref<'_, SourceType>;
 
@@ -34,33 +82,133 @@ pub trait AngularContext<'a> {
 }
 
 impl<'a> AngularContext<'a> for AngularCtx<'a> {
-    fn new(ast: Rc<AstBuilder<'a>>, semantic: Rc<RefCell<Semantic<'a>>>) -> Self {
-        Self(ast, semantic, Rc::new(RefCell::new(vec![])))
+    fn new(ast: Rc<AstBuilder<'a>>, semantic: Semantic<'a>) -> Self {
+        let source_text = semantic.source_text();
+        let source_type = *semantic.source_type();
+        let (symbols, scopes) = semantic.into_symbol_table_and_scope_tree();
+        let model = SemanticModel {
+            source_text,
+            source_type,
+            symbols,
+            scopes,
+        };
+        Self(
+            ast,
+            Rc::new(RefCell::new(model)),
+            Rc::new(RefCell::new(vec![])),
+            Rc::new(RefCell::new(vec![])),
+        )
     }
 
-    fn semantic(&self) -> Ref<'_, Semantic<'a>> {
-        self.1.borrow()
+    fn source_text(&self) -> &'a str {
+        self.1.borrow().source_text
     }
 
     fn symbols(&self) -> Ref<'_, SymbolTable> {
-        Ref::map(self.1.borrow(), |semantic| semantic.symbols())
+        Ref::map(self.1.borrow(), |model| &model.symbols)
+    }
+
+    fn symbols_mut(&self) -> RefMut<'_, SymbolTable> {
+        RefMut::map(self.1.borrow_mut(), |model| &mut model.symbols)
     }
 
     fn scopes(&self) -> Ref<'_, ScopeTree> {
-        Ref::map(self.1.borrow(), |semantic| semantic.scopes())
+        Ref::map(self.1.borrow(), |model| &model.scopes)
     }
 
     fn scopes_mut(&self) -> RefMut<'_, ScopeTree> {
-        RefMut::map(self.1.borrow_mut(), |semantic| semantic.scopes_mut())
+        RefMut::map(self.1.borrow_mut(), |model| &mut model.scopes)
+    }
+
+    fn add_binding(&self, scope_id: ScopeId, name: CompactStr, flags: SymbolFlags) -> SymbolId {
+        // Generated code has no nodes in the semantic model to declare it.
+        let symbol_id =
+            self.symbols_mut()
+                .create_symbol(SPAN, name.clone(), flags, scope_id, NodeId::DUMMY);
+        self.scopes_mut().add_binding(scope_id, name, symbol_id);
+        symbol_id
+    }
+
+    fn create_reference(
+        &self,
+        name: &str,
+        symbol_id: Option<SymbolId>,
+        flags: ReferenceFlags,
+    ) -> ReferenceId {
+        let reference = match symbol_id {
+            Some(symbol_id) => Reference::new_with_symbol_id(NodeId::DUMMY, symbol_id, flags),
+            None => Reference::new(NodeId::DUMMY, flags),
+        };
+        let reference_id = self.symbols_mut().create_reference(reference);
+        match symbol_id {
+            Some(symbol_id) => self.symbols_mut().resolved_references[symbol_id].push(reference_id),
+            None => self
+                .scopes_mut()
+                .add_root_unresolved_reference(CompactStr::from(name), reference_id),
+        }
+        reference_id
+    }
+
+    fn generate_uid(&self, name: &str) -> CompactStr {
+        let uid = {
+            let scopes = self.scopes();
+            let root = scopes.root_scope_id();
+            let base = format!("_{}", name.trim_start_matches('_'));
+            let is_free = |uid: &str| {
+                !scopes.has_binding(root, uid)
+                    && !scopes.root_unresolved_references().contains_key(uid)
+            };
+            let mut uid = base.clone();
+            let mut i = 2;
+            while !is_free(&uid) {
+                uid = format!("{base}{i}");
+                i += 1;
+            }
+            CompactStr::from(uid)
+        };
+        let root = self.scopes().root_scope_id();
+        self.add_binding(root, uid.clone(), SymbolFlags::FunctionScopedVariable);
+        uid
+    }
+
+    fn namespace(&self, module: &str) -> CompactStr {
+        if let Some(import) = self
+            .3
+            .borrow()
+            .iter()
+            .find(|import| import.module == module)
+        {
+            return import.alias.clone();
+        }
+        let alias = {
+            let scopes = self.scopes();
+            // Generated code lands in nested scopes too, e.g. class bodies.
+            let is_free = |alias: &str| {
+                !scopes.iter_bindings().any(|(_, _, name)| name == alias)
+                    && !scopes.root_unresolved_references().contains_key(alias)
+            };
+            (0..)
+                .map(|i| format!("i{i}"))
+                .find(|alias| is_free(alias))
+                .map(CompactStr::from)
+                .unwrap()
+        };
+        let root = self.scopes().root_scope_id();
+        let symbol_id = self.add_binding(root, alias.clone(), SymbolFlags::Import);
+        self.3.borrow_mut().push(NamespaceImport {
+            module: CompactStr::from(module),
+            alias: alias.clone(),
+            symbol_id,
+        });
+        alias
     }
 
-    fn add_binding(&self, name: CompactString) {
-        // TODO: use the correct scope and symbol id
-        self.scopes_mut().add_binding(ScopeId::new(0), name, SymbolId::new(0));
+    fn namespace_imports(&self) -> Vec<NamespaceImport> {
+        self.3.borrow().clone()
     }
 
     fn source_type(&self) -> Ref<'_, SourceType> {
-        Ref::map(self.1.borrow(), |semantic| semantic.source_type())
+        Ref::map(self.1.borrow(), |model| &model.source_type)
     }
 
     fn errors(&self) -> Vec<Error> {
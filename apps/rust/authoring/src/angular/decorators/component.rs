@@ -0,0 +1,140 @@
+use oxc_ast::ast::{Decorator, ObjectPropertyKind};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_span::{GetSpan, Span};
+
+use super::directive::{
+    expect_string, member_name, options_literal, strings, DirectiveOptions, StringOption,
+};
+use crate::angular::diagnostics::{dynamic_option, expected_member, unsupported_option};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ChangeDetectionStrategy {
+    OnPush = 0,
+    #[default]
+    Default = 1,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ViewEncapsulation {
+    #[default]
+    Emulated = 0,
+    None = 2,
+    ShadowDom = 3,
+}
+
+/// The options of `@Component`. Expressions copied into the definition as written, such as
+/// `imports`, are kept as spans of the source.
+#[derive(Debug, Default)]
+pub struct ComponentOptions {
+    /// Options shared with `@Directive`, such as `selector` and `host`.
+    pub directive: DirectiveOptions,
+    pub template: Option<StringOption>,
+    pub template_url: Option<StringOption>,
+    pub styles: Vec<String>,
+    pub style_urls: Vec<StringOption>,
+    pub imports: Option<Span>,
+    pub view_providers: Option<Span>,
+    pub change_detection: ChangeDetectionStrategy,
+    pub encapsulation: ViewEncapsulation,
+}
+
+impl ComponentOptions {
+    /// The options of `@Component` besides those of [`DirectiveOptions`].
+    pub const OPTIONS: &'static [&'static str] = &[
+        "template",
+        "templateUrl",
+        "styles",
+        "styleUrl",
+        "styleUrls",
+        "imports",
+        "viewProviders",
+        "changeDetection",
+        "encapsulation",
+    ];
+
+    pub fn parse_decorator(
+        decorator: &Decorator,
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> Option<ComponentOptions> {
+        let object = options_literal("Component", decorator, diagnostics)?;
+        Some(ComponentOptions::from_properties(
+            &object.properties,
+            diagnostics,
+        ))
+    }
+
+    pub fn from_properties(
+        properties: &[ObjectPropertyKind],
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> Self {
+        let mut options = Self::default();
+        for property in properties {
+            let ObjectPropertyKind::ObjectProperty(property) = property else {
+                diagnostics.push(dynamic_option("Component", property.span()));
+                continue;
+            };
+            let Some(key) = property.key.static_name() else {
+                diagnostics.push(dynamic_option("Component", property.key.span()));
+                continue;
+            };
+            let value = &property.value;
+            if options.directive.set(&key, value, diagnostics) {
+                continue;
+            }
+            match key.as_ref() {
+                "template" => options.template = expect_string(&key, value, diagnostics),
+                "templateUrl" => options.template_url = expect_string(&key, value, diagnostics),
+                "styles" => options.styles = strings(value).into_iter().map(|s| s.value).collect(),
+                "styleUrl" | "styleUrls" => options.style_urls = strings(value),
+                "imports" => options.imports = Some(value.span()),
+                "viewProviders" => options.view_providers = Some(value.span()),
+                "changeDetection" => {
+                    options.change_detection = match member_name(value) {
+                        Some("OnPush") => ChangeDetectionStrategy::OnPush,
+                        Some("Default") => ChangeDetectionStrategy::Default,
+                        _ => {
+                            diagnostics.push(expected_member(
+                                &key,
+                                &[
+                                    "ChangeDetectionStrategy.OnPush",
+                                    "ChangeDetectionStrategy.Default",
+                                ],
+                                value.span(),
+                            ));
+                            ChangeDetectionStrategy::Default
+                        }
+                    }
+                }
+                "encapsulation" => {
+                    options.encapsulation = match member_name(value) {
+                        Some("Emulated") => ViewEncapsulation::Emulated,
+                        Some("None") => ViewEncapsulation::None,
+                        Some("ShadowDom") => ViewEncapsulation::ShadowDom,
+                        _ => {
+                            diagnostics.push(expected_member(
+                                &key,
+                                &[
+                                    "ViewEncapsulation.Emulated",
+                                    "ViewEncapsulation.None",
+                                    "ViewEncapsulation.ShadowDom",
+                                ],
+                                value.span(),
+                            ));
+                            ViewEncapsulation::Emulated
+                        }
+                    }
+                }
+                _ => {
+                    let known = [DirectiveOptions::OPTIONS, Self::OPTIONS].concat();
+                    diagnostics.push(unsupported_option(
+                        "Component",
+                        &key,
+                        &known,
+                        property.key.span(),
+                    ));
+                }
+            }
+        }
+        options
+    }
+}
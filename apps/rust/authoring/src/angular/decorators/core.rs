@@ -1,26 +1,54 @@
-use oxc_ast::ast::{
-    Argument, Decorator, Expression, FormalParameter
-};
+use oxc_ast::ast::{Argument, Decorator, Expression};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_span::{GetSpan, Span};
 
+use super::component::ComponentOptions;
+use super::directive::DirectiveOptions;
 use super::injectable::InjectableOptions;
+use super::ng_module::NgModuleOptions;
+use super::pipe::PipeOptions;
+use crate::angular::diagnostics::missing_decorator_argument;
 #[derive(Debug)]
 pub enum TopLevelDecorator {
-    Component,
-    Directive,
-    Pipe,
-    NgModule,
+    Component { options: ComponentOptions },
+    Directive { options: DirectiveOptions },
+    Pipe { options: PipeOptions },
+    NgModule { options: NgModuleOptions },
     Injectable { options: InjectableOptions },
 }
 
 impl TopLevelDecorator {
-    pub fn from_str(name: &str, decorator: &Decorator) -> Option<Self> {
+    /// The decorator `name`, with its options. Options that cannot be read are reported to
+    /// `diagnostics` and left out.
+    pub fn from_str(
+        name: &str,
+        decorator: &Decorator,
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> Option<Self> {
         match name {
-            "Component" => Some(Self::Component),
-            "Directive" => Some(Self::Directive),
-            "Pipe" => Some(Self::Pipe),
-            "NgModule" => Some(Self::NgModule),
+            "Component" => {
+                let options =
+                    ComponentOptions::parse_decorator(decorator, diagnostics).unwrap_or_default();
+                Some(Self::Component { options })
+            }
+            "Directive" => {
+                let options =
+                    DirectiveOptions::parse_decorator(decorator, diagnostics).unwrap_or_default();
+                Some(Self::Directive { options })
+            }
+            "Pipe" => {
+                let options =
+                    PipeOptions::parse_decorator(decorator, diagnostics).unwrap_or_default();
+                Some(Self::Pipe { options })
+            }
+            "NgModule" => {
+                let options =
+                    NgModuleOptions::parse_decorator(decorator, diagnostics).unwrap_or_default();
+                Some(Self::NgModule { options })
+            }
             "Injectable" => {
-                let options = InjectableOptions::parse_decorator(decorator).unwrap_or_default();
+                let options =
+                    InjectableOptions::parse_decorator(decorator, diagnostics).unwrap_or_default();
                 Some(Self::Injectable { options })
             }
             _ => None,
@@ -30,33 +58,55 @@ impl TopLevelDecorator {
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum ParamDecorator {
-    Optional(String),
-    ASelf(String),
-    SkipSelf(String),
-    Inject(String),
-    None,
+    Optional,
+    ASelf,
+    SkipSelf,
+    Host,
+    /// `@Inject(token)`, with the span of the token.
+    Inject(Span),
+    /// `@Attribute("name")`, with the span of the name.
+    Attribute(Span),
 }
 
 impl ParamDecorator {
-    pub fn from_str(name: &str, param: &FormalParameter, decorator: &Decorator) -> Option<Self> {
-        let _ = param;
-        // println!("{:#?}", param);
-        match name {
-            "Optional" => Some(ParamDecorator::Optional("".into())),
-            "Self" => Some(ParamDecorator::ASelf("".into())),
-            "SkipSelf" => Some(ParamDecorator::SkipSelf("".into())),
-            "Inject" => {
-                if let Expression::CallExpression(call_expr) = &decorator.expression {
-                    if let Some(Argument::Expression(Expression::Identifier(identifier_reference))) = call_expr.arguments.get(0) {
-                        Some(ParamDecorator::Inject(identifier_reference.name.to_string()))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
+    /// Names of the parameter decorators of `@angular/core`.
+    pub const NAMES: [&'static str; 6] = [
+        "Optional",
+        "Self",
+        "SkipSelf",
+        "Host",
+        "Inject",
+        "Attribute",
+    ];
 
-            },
+    /// The parameter decorator `name`, reporting `@Inject` and `@Attribute` without their
+    /// argument to `diagnostics`.
+    pub fn from_str(
+        name: &str,
+        decorator: &Decorator,
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> Option<Self> {
+        let mut argument = |expected: &str| {
+            let argument = match &decorator.expression {
+                Expression::CallExpression(call_expr) => call_expr
+                    .arguments
+                    .first()
+                    .and_then(Argument::as_expression)
+                    .map(GetSpan::span),
+                _ => None,
+            };
+            if argument.is_none() {
+                diagnostics.push(missing_decorator_argument(name, expected, decorator.span));
+            }
+            argument
+        };
+        match name {
+            "Optional" => Some(ParamDecorator::Optional),
+            "Self" => Some(ParamDecorator::ASelf),
+            "SkipSelf" => Some(ParamDecorator::SkipSelf),
+            "Host" => Some(ParamDecorator::Host),
+            "Inject" => argument("a token").map(ParamDecorator::Inject),
+            "Attribute" => argument("the name of an attribute").map(ParamDecorator::Attribute),
             _ => None,
         }
     }
@@ -75,6 +125,7 @@ pub enum PropertyDecorator {
     Attribute,
 }
 impl PropertyDecorator {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(name: &str) -> Option<Self> {
         match name {
             "Input" => Some(Self::Input),
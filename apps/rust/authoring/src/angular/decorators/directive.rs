@@ -0,0 +1,364 @@
+use oxc_ast::ast::{Argument, Decorator, Expression, ObjectExpression, ObjectPropertyKind};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_span::{GetSpan, Span};
+
+use crate::angular::diagnostics::{
+    dynamic_option, expected_boolean, expected_string, options_not_literal, unsupported_option,
+};
+use crate::html::{Attribute, AttributeKind};
+
+/// A string option, with the span of its contents in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StringOption {
+    pub value: String,
+    pub span: Span,
+}
+
+/// An input of a directive, from its `inputs` option or an `@Input` property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectiveInput {
+    /// The class property it sets.
+    pub name: String,
+    /// The name templates bind to.
+    pub public_name: String,
+    pub required: bool,
+    /// The `transform` function applied to bound values.
+    pub transform: Option<Span>,
+}
+
+/// An output of a directive, from its `outputs` option or an `@Output` property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectiveOutput {
+    pub name: String,
+    pub public_name: String,
+}
+
+/// A directive applied to the host element along with the one declaring it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostDirective {
+    /// The directive class.
+    pub directive: Span,
+    /// Inputs exposed on the host, as their name on the directive and their public name.
+    pub inputs: Vec<(String, String)>,
+    pub outputs: Vec<(String, String)>,
+}
+
+/// The options of `@Directive`, which `@Component` shares.
+#[derive(Debug, Default)]
+pub struct DirectiveOptions {
+    pub selector: Option<String>,
+    pub inputs: Vec<DirectiveInput>,
+    pub outputs: Vec<DirectiveOutput>,
+    /// Entries of `host`, as the attributes they would be in a template, e.g. `(click)`.
+    pub host: Vec<Attribute>,
+    /// Names templates can give to the instance, e.g. `#form="ngForm"`.
+    pub export_as: Vec<String>,
+    pub host_directives: Vec<HostDirective>,
+    pub providers: Option<Span>,
+    pub standalone: bool,
+}
+
+impl DirectiveOptions {
+    /// The options read by [`DirectiveOptions::set`].
+    pub const OPTIONS: &'static [&'static str] = &[
+        "selector",
+        "inputs",
+        "outputs",
+        "host",
+        "exportAs",
+        "hostDirectives",
+        "providers",
+        "standalone",
+    ];
+
+    pub fn parse_decorator(
+        decorator: &Decorator,
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> Option<DirectiveOptions> {
+        let object = options_literal("Directive", decorator, diagnostics)?;
+        Some(DirectiveOptions::from_properties(
+            &object.properties,
+            diagnostics,
+        ))
+    }
+
+    pub fn from_properties(
+        properties: &[ObjectPropertyKind],
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> Self {
+        let mut options = Self::default();
+        for property in properties {
+            let ObjectPropertyKind::ObjectProperty(property) = property else {
+                diagnostics.push(dynamic_option("Directive", property.span()));
+                continue;
+            };
+            let Some(key) = property.key.static_name() else {
+                diagnostics.push(dynamic_option("Directive", property.key.span()));
+                continue;
+            };
+            if !options.set(&key, &property.value, diagnostics) {
+                diagnostics.push(unsupported_option(
+                    "Directive",
+                    &key,
+                    Self::OPTIONS,
+                    property.key.span(),
+                ));
+            }
+        }
+        options
+    }
+
+    /// Reads option `key`, returning whether it is a directive option.
+    pub fn set(
+        &mut self,
+        key: &str,
+        value: &Expression,
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> bool {
+        match key {
+            "selector" => self.selector = expect_string(key, value, diagnostics).map(|s| s.value),
+            "inputs" => self.inputs = inputs(value),
+            "outputs" => {
+                self.outputs = mappings(value)
+                    .into_iter()
+                    .map(|(name, public_name)| DirectiveOutput { name, public_name })
+                    .collect()
+            }
+            "host" => self.host = host(value),
+            "exportAs" => {
+                self.export_as = expect_string(key, value, diagnostics)
+                    .map(|s| {
+                        s.value
+                            .split(',')
+                            .map(|name| name.trim().to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            }
+            "hostDirectives" => self.host_directives = host_directives(value),
+            "providers" => self.providers = Some(value.span()),
+            "standalone" => self.standalone = expect_boolean(key, value, diagnostics),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// The object literal a decorator is called with, reporting any other argument.
+pub(super) fn options_literal<'d, 'a>(
+    name: &str,
+    decorator: &'d Decorator<'a>,
+    diagnostics: &mut Vec<OxcDiagnostic>,
+) -> Option<&'d ObjectExpression<'a>> {
+    let Expression::CallExpression(call_expr) = &decorator.expression else {
+        return None;
+    };
+    match call_expr.arguments.first()? {
+        Argument::ObjectExpression(obj_expr) => Some(obj_expr),
+        argument => {
+            diagnostics.push(options_not_literal(name, argument.span()));
+            None
+        }
+    }
+}
+
+/// A string option, reporting any other value.
+pub(super) fn expect_string(
+    key: &str,
+    value: &Expression,
+    diagnostics: &mut Vec<OxcDiagnostic>,
+) -> Option<StringOption> {
+    let option = string(value);
+    if option.is_none() {
+        diagnostics.push(expected_string(key, value.span()));
+    }
+    option
+}
+
+/// A boolean option, reporting any other value as `false`.
+pub(super) fn expect_boolean(
+    key: &str,
+    value: &Expression,
+    diagnostics: &mut Vec<OxcDiagnostic>,
+) -> bool {
+    match value {
+        Expression::BooleanLiteral(literal) => literal.value,
+        value => {
+            diagnostics.push(expected_boolean(key, value.span()));
+            false
+        }
+    }
+}
+
+/// A string or a template literal without expressions.
+pub(super) fn string(expression: &Expression) -> Option<StringOption> {
+    let (value, span) = match expression {
+        Expression::StringLiteral(literal) => (literal.value.to_string(), literal.span),
+        Expression::TemplateLiteral(literal) if literal.expressions.is_empty() => {
+            let quasi = literal.quasis.first()?;
+            let value = quasi.value.cooked.as_ref().unwrap_or(&quasi.value.raw);
+            (value.to_string(), literal.span)
+        }
+        _ => return None,
+    };
+    // Without the quotes or backticks.
+    let span = Span::new(
+        span.start + 1,
+        span.end.saturating_sub(1).max(span.start + 1),
+    );
+    Some(StringOption { value, span })
+}
+
+/// A string, or an array of them.
+pub(super) fn strings(expression: &Expression) -> Vec<StringOption> {
+    match expression {
+        Expression::ArrayExpression(array) => array
+            .elements
+            .iter()
+            .filter_map(|element| string(element.as_expression()?))
+            .collect(),
+        _ => string(expression).into_iter().collect(),
+    }
+}
+
+/// `["name", "name: alias"]` as names with their public names.
+fn mappings(expression: &Expression) -> Vec<(String, String)> {
+    strings(expression)
+        .into_iter()
+        .map(|mapping| match mapping.value.split_once(':') {
+            Some((name, alias)) => (name.trim().to_string(), alias.trim().to_string()),
+            None => (
+                mapping.value.trim().to_string(),
+                mapping.value.trim().to_string(),
+            ),
+        })
+        .collect()
+}
+
+/// `["name", "name: alias", { name, alias, required, transform }]`.
+fn inputs(expression: &Expression) -> Vec<DirectiveInput> {
+    let Expression::ArrayExpression(array) = expression else {
+        return Vec::new();
+    };
+    array
+        .elements
+        .iter()
+        .filter_map(|element| match element.as_expression()? {
+            Expression::ObjectExpression(object) => {
+                let mut input = DirectiveInput {
+                    name: String::new(),
+                    public_name: String::new(),
+                    required: false,
+                    transform: None,
+                };
+                for property in &object.properties {
+                    let ObjectPropertyKind::ObjectProperty(property) = property else {
+                        continue;
+                    };
+                    let Some(key) = property.key.static_name() else {
+                        continue;
+                    };
+                    match key.as_ref() {
+                        "name" => input.name = string(&property.value)?.value,
+                        "alias" => input.public_name = string(&property.value)?.value,
+                        "required" => {
+                            input.required = matches!(
+                                &property.value,
+                                Expression::BooleanLiteral(literal) if literal.value
+                            )
+                        }
+                        "transform" => input.transform = Some(property.value.span()),
+                        _ => {}
+                    }
+                }
+                if input.public_name.is_empty() {
+                    input.public_name.clone_from(&input.name);
+                }
+                (!input.name.is_empty()).then_some(input)
+            }
+            expression => {
+                let (name, public_name) = mappings(expression).pop()?;
+                Some(DirectiveInput {
+                    name,
+                    public_name,
+                    required: false,
+                    transform: None,
+                })
+            }
+        })
+        .collect()
+}
+
+/// `Enum.Member` -> `Member`.
+pub(super) fn member_name<'e>(expression: &'e Expression) -> Option<&'e str> {
+    match expression {
+        Expression::StaticMemberExpression(member) => Some(member.property.name.as_str()),
+        _ => None,
+    }
+}
+
+fn host(expression: &Expression) -> Vec<Attribute> {
+    let Expression::ObjectExpression(object) = expression else {
+        return Vec::new();
+    };
+    object
+        .properties
+        .iter()
+        .filter_map(|property| {
+            let ObjectPropertyKind::ObjectProperty(property) = property else {
+                return None;
+            };
+            let name = property.key.static_name()?.to_string();
+            let value = string(&property.value)?;
+            Some(Attribute {
+                kind: AttributeKind::from_name(&name),
+                name,
+                value: value.value,
+                span: property.span,
+                value_span: value.span,
+            })
+        })
+        .collect()
+}
+
+/// `[Directive, { directive: Directive, inputs: ["a: alias"], outputs: ["b"] }]`.
+fn host_directives(expression: &Expression) -> Vec<HostDirective> {
+    let Expression::ArrayExpression(array) = expression else {
+        return Vec::new();
+    };
+    array
+        .elements
+        .iter()
+        .filter_map(|element| match element.as_expression()? {
+            Expression::ObjectExpression(object) => {
+                let mut host_directive = None;
+                let mut inputs = Vec::new();
+                let mut outputs = Vec::new();
+                for property in &object.properties {
+                    let ObjectPropertyKind::ObjectProperty(property) = property else {
+                        continue;
+                    };
+                    let Some(key) = property.key.static_name() else {
+                        continue;
+                    };
+                    match key.as_ref() {
+                        "directive" => host_directive = Some(property.value.span()),
+                        "inputs" => inputs = mappings(&property.value),
+                        "outputs" => outputs = mappings(&property.value),
+                        _ => {}
+                    }
+                }
+                Some(HostDirective {
+                    directive: host_directive?,
+                    inputs,
+                    outputs,
+                })
+            }
+            directive => Some(HostDirective {
+                directive: directive.span(),
+                inputs: Vec::new(),
+                outputs: Vec::new(),
+            }),
+        })
+        .collect()
+}
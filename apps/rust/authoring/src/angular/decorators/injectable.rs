@@ -1,7 +1,14 @@
-use std::{any::Any, fmt};
+use oxc_ast::ast::{Argument, ArrayExpressionElement, Decorator, Expression, ObjectPropertyKind};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_span::{GetSpan, Span};
 
-use oxc_ast::ast::{ Argument, Decorator, Expression, ObjectPropertyKind, PropertyKey};
-#[derive(Debug)]
+use super::directive::options_literal;
+use crate::angular::diagnostics::{dynamic_option, invalid_provided_in, unsupported_option};
+use crate::angular::runtime::InjectFlags;
+
+/// How an `@Injectable` is created, from the `use*` option of its decorator. Expressions are
+/// copied into the factory as written, so they are kept as spans of the source.
+#[derive(Debug, Default)]
 pub enum InjectableProvider {
     Value(ValueSansProvider),
     Existing(ExistingSansProvider),
@@ -9,47 +16,120 @@ pub enum InjectableProvider {
     Constructor(ConstructorSansProvider),
     Factory(FactorySansProvider),
     Class(ClassSansProvider),
+    #[default]
     None,
 }
-impl Default for InjectableProvider {
-    fn default() -> Self {
-        {
-            InjectableProvider::None
-        }
-    }
+#[derive(Debug)]
+pub struct ValueSansProvider {
+    pub use_value: Span,
+}
+#[derive(Debug)]
+pub struct ExistingSansProvider {
+    pub use_existing: Span,
 }
 #[derive(Debug)]
-struct ValueSansProvider {
-    use_value: Box<dyn Any>,
+pub struct StaticClassSansProvider {
+    pub use_class: Span,
+    pub deps: Vec<Dependency>,
 }
 #[derive(Debug)]
-struct ExistingSansProvider {
-    use_existing: Box<dyn Any>,
+pub struct ConstructorSansProvider {
+    pub deps: Option<Vec<Dependency>>,
 }
 #[derive(Debug)]
-struct StaticClassSansProvider {
-    use_class: Box<dyn Any>,
-    deps: Vec<Box<dyn Any>>,
+pub struct FactorySansProvider {
+    pub use_factory: Span,
+    pub deps: Option<Vec<Dependency>>,
 }
 #[derive(Debug)]
-struct ConstructorSansProvider {
-    deps: Option<Vec<Box<dyn Any>>>,
+pub struct ClassSansProvider {
+    pub use_class: Span,
 }
-struct FactorySansProvider {
-    use_factory: Box<dyn Fn(Vec<Box<dyn Any>>) -> Box<dyn Any>>,
-    deps: Option<Vec<Box<dyn Any>>>,
+
+/// A dependency of a factory: the token to inject and how, from an entry of `deps` such as
+/// `[new Optional(), Token]` or from a constructor parameter and its decorators.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    pub token: Span,
+    /// `InjectFlags`, combined.
+    pub flags: u8,
+    /// `@Attribute`, the token being the name of the attribute.
+    pub attribute: bool,
 }
-impl fmt::Debug for FactorySansProvider {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("FactorySansProvider")
-            .field("use_factory", &"Box<dyn Fn(Vec<Box<dyn Any>>) -> Box<dyn Any>>")
-            .field("deps", &self.deps)
-            .finish()
+
+impl Dependency {
+    fn parse(expression: &Expression) -> Option<Self> {
+        let Expression::ArrayExpression(array) = expression else {
+            return Some(Self {
+                token: expression.span(),
+                flags: InjectFlags::Default as u8,
+                attribute: false,
+            });
+        };
+        let mut token = None;
+        let mut flags = InjectFlags::Default as u8;
+        for element in &array.elements {
+            let Some(element) = element.as_expression() else {
+                continue;
+            };
+            // `Optional` and `new Optional()` alike; `new Inject(Token)` names the token.
+            let (name, arguments) = match element {
+                Expression::Identifier(identifier) => (identifier.name.as_str(), None),
+                Expression::NewExpression(new) => match &new.callee {
+                    Expression::Identifier(identifier) => {
+                        (identifier.name.as_str(), Some(&new.arguments))
+                    }
+                    _ => ("", None),
+                },
+                _ => ("", None),
+            };
+            match name {
+                "Optional" => flags |= InjectFlags::Optional as u8,
+                "Self" => flags |= InjectFlags::ASelf as u8,
+                "SkipSelf" => flags |= InjectFlags::SkipSelf as u8,
+                "Host" => flags |= InjectFlags::Host as u8,
+                "Inject" => {
+                    token = arguments
+                        .and_then(|arguments| arguments.first())
+                        .and_then(Argument::as_expression)
+                        .map(GetSpan::span);
+                }
+                _ => token = Some(element.span()),
+            }
+        }
+        Some(Self {
+            token: token?,
+            flags,
+            attribute: false,
+        })
     }
 }
-#[derive(Debug)]
-struct ClassSansProvider {
-    use_class: Box<dyn Any>,
+
+/// The flags of an `inject(Token, { optional: true, self: true })` call, from its options, or the
+/// span of the part that is not known statically.
+pub fn inject_flags(options: &Expression) -> Result<u8, Span> {
+    let Expression::ObjectExpression(object) = options else {
+        return Err(options.span());
+    };
+    let mut flags = InjectFlags::Default as u8;
+    for property in &object.properties {
+        let ObjectPropertyKind::ObjectProperty(property) = property else {
+            return Err(property.span());
+        };
+        let flag = match property.key.static_name().as_deref() {
+            Some("optional") => InjectFlags::Optional,
+            Some("self") => InjectFlags::ASelf,
+            Some("skipSelf") => InjectFlags::SkipSelf,
+            Some("host") => InjectFlags::Host,
+            _ => return Err(property.key.span()),
+        };
+        match &property.value {
+            Expression::BooleanLiteral(value) if value.value => flags |= flag as u8,
+            Expression::BooleanLiteral(_) => {}
+            value => return Err(value.span()),
+        }
+    }
+    Ok(flags)
 }
 
 #[derive(Debug, PartialEq)]
@@ -57,9 +137,12 @@ pub enum ProviderScope {
     Root,
     Platform,
     Any,
-    None
+    /// A module class or an `InjectionToken`.
+    Token(Span),
+    None,
 }
 impl ProviderScope {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(name: &str) -> Option<Self> {
         match name {
             "root" => Some(Self::Root),
@@ -69,16 +152,7 @@ impl ProviderScope {
         }
     }
 }
-impl ToString for ProviderScope {
-    fn to_string(&self) -> String {
-        match self {
-            ProviderScope::Root => String::from("root"),
-            ProviderScope::Platform => String::from("platform"),
-            ProviderScope::Any => String::from("any"),
-            ProviderScope::None => String::from("none"),
-        }
-    }
-}
+
 #[derive(Debug)]
 pub struct InjectableOptions {
     pub provided_in: ProviderScope,
@@ -88,51 +162,111 @@ impl Default for InjectableOptions {
     fn default() -> Self {
         InjectableOptions {
             provided_in: ProviderScope::None,
-            detail: InjectableProvider::None
+            detail: InjectableProvider::None,
         }
     }
 }
 impl InjectableOptions {
     pub const PROVIDED_IN_KEY: &'static str = "providedIn";
+    pub const OPTIONS: &'static [&'static str] = &[
+        Self::PROVIDED_IN_KEY,
+        "useValue",
+        "useExisting",
+        "useClass",
+        "useFactory",
+        "deps",
+    ];
 
-    pub fn parse_decorator(decorator: &Decorator) -> Option<InjectableOptions> {
-        if let Expression::CallExpression(call_expr) = &decorator.expression {
-            call_expr.arguments.iter().find_map(|arg| {
-                if let Argument::Expression(Expression::ObjectExpression(obj_expr)) = arg {
-                    InjectableOptions::from_properties(&obj_expr.properties)
-                } else {
-                    None
-                }
-            })
-        } else {
-            None
-        }
+    pub fn parse_decorator(
+        decorator: &Decorator,
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> Option<InjectableOptions> {
+        let object = options_literal("Injectable", decorator, diagnostics)?;
+        InjectableOptions::from_properties(&object.properties, diagnostics)
     }
 
-    pub fn from_properties(properties: &[ObjectPropertyKind]) -> Option<Self> {
-        properties
-            .iter()
-            .filter_map(|property_kind| {
-                if let ObjectPropertyKind::ObjectProperty(boxed_property) = property_kind {
-                    match boxed_property.key {
-                        PropertyKey::Identifier(ref identifier)
-                        if identifier.name == Self::PROVIDED_IN_KEY =>
-                        {
-                            match &boxed_property.value {
-                                Expression::StringLiteral(literal) => {
-                                    ProviderScope::from_str(&literal.value)
-                                        .map(|provided_in| Self { provided_in, detail: InjectableProvider::None })
-                                }
-                                _ => None,
-                            }
+    pub fn from_properties(
+        properties: &[ObjectPropertyKind],
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> Option<Self> {
+        let mut provided_in = ProviderScope::None;
+        let mut use_value = None;
+        let mut use_existing = None;
+        let mut use_class = None;
+        let mut use_factory = None;
+        let mut deps = None;
+        for property in properties {
+            let ObjectPropertyKind::ObjectProperty(property) = property else {
+                diagnostics.push(dynamic_option("Injectable", property.span()));
+                continue;
+            };
+            let Some(key) = property.key.static_name() else {
+                diagnostics.push(dynamic_option("Injectable", property.key.span()));
+                continue;
+            };
+            let value = &property.value;
+            match key.as_ref() {
+                Self::PROVIDED_IN_KEY => {
+                    provided_in = match value {
+                        Expression::StringLiteral(literal) => {
+                            ProviderScope::from_str(&literal.value).unwrap_or_else(|| {
+                                diagnostics.push(invalid_provided_in(literal.span));
+                                ProviderScope::None
+                            })
                         }
-                        _ => None,
+                        Expression::NullLiteral(_) => ProviderScope::None,
+                        value => ProviderScope::Token(value.span()),
                     }
-                } else {
-                    None
                 }
-            })
-            .next()
-    }
+                "useValue" => use_value = Some(value.span()),
+                "useExisting" => use_existing = Some(value.span()),
+                "useClass" => use_class = Some(value.span()),
+                "useFactory" => use_factory = Some(value.span()),
+                "deps" => {
+                    let Expression::ArrayExpression(array) = value else {
+                        continue;
+                    };
+                    deps = Some(
+                        array
+                            .elements
+                            .iter()
+                            .filter_map(ArrayExpressionElement::as_expression)
+                            .filter_map(Dependency::parse)
+                            .collect(),
+                    );
+                }
+                _ => diagnostics.push(unsupported_option(
+                    "Injectable",
+                    &key,
+                    Self::OPTIONS,
+                    property.key.span(),
+                )),
+            }
+        }
 
+        // In the order Angular checks them.
+        let detail = if let Some(use_class) = use_class {
+            match deps {
+                Some(deps) => InjectableProvider::StaticClass(StaticClassSansProvider {
+                    use_class,
+                    deps,
+                }),
+                None => InjectableProvider::Class(ClassSansProvider { use_class }),
+            }
+        } else if let Some(use_factory) = use_factory {
+            InjectableProvider::Factory(FactorySansProvider { use_factory, deps })
+        } else if let Some(use_value) = use_value {
+            InjectableProvider::Value(ValueSansProvider { use_value })
+        } else if let Some(use_existing) = use_existing {
+            InjectableProvider::Existing(ExistingSansProvider { use_existing })
+        } else if deps.is_some() {
+            InjectableProvider::Constructor(ConstructorSansProvider { deps })
+        } else {
+            InjectableProvider::None
+        };
+        Some(Self {
+            provided_in,
+            detail,
+        })
+    }
 }
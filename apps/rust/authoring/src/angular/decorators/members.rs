@@ -0,0 +1,340 @@
+use oxc_ast::ast::{
+    Argument, CallExpression, Class, ClassElement, Decorator, Expression, ObjectPropertyKind,
+    PropertyKey,
+};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_span::{GetSpan, Span};
+
+use super::core::PropertyDecorator;
+use super::directive::{
+    expect_boolean, expect_string, string, strings, DirectiveInput, DirectiveOutput,
+};
+use crate::angular::diagnostics::{
+    dynamic_member, dynamic_option, missing_decorator_argument, options_not_literal,
+    unsupported_option,
+};
+use crate::html::{Attribute, AttributeKind};
+
+/// A query declared with `@ViewChild`, `@ViewChildren`, `@ContentChild` or `@ContentChildren`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecoratorQuery {
+    /// The property the results are assigned to.
+    pub property: String,
+    /// `@ViewChild` and `@ViewChildren`, as opposed to the content queries.
+    pub view: bool,
+    /// Whether only the first match is kept, `@ViewChild` and `@ContentChild`.
+    pub first: bool,
+    pub predicate: QueryPredicate,
+    /// The `read` option, the token read from each match.
+    pub read: Option<Span>,
+    /// Whether matches are looked for in nested elements as well as direct children.
+    pub descendants: bool,
+    /// Whether the results are resolved before change detection runs.
+    pub is_static: bool,
+    pub emit_distinct_changes_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryPredicate {
+    /// `@ViewChild('a')` or `@ViewChildren('a, b')`, matching `#a` and `#b` references.
+    References(Vec<String>),
+    /// A type or token, e.g. `@ViewChild(Item)`.
+    Type(Span),
+}
+
+/// What the members of a directive class declare with decorators, merged into its definition
+/// along with the options of `@Directive` or `@Component`.
+#[derive(Debug, Default)]
+pub struct DirectiveMembers {
+    pub inputs: Vec<DirectiveInput>,
+    pub outputs: Vec<DirectiveOutput>,
+    /// `@HostBinding` and `@HostListener` members, as the `host` entries they stand for, e.g.
+    /// `[class.active]: "active"` or `(click): "onClick($event)"`.
+    pub host: Vec<Attribute>,
+    pub queries: Vec<DecoratorQuery>,
+}
+
+impl DirectiveMembers {
+    /// The decorated members of `class`, reporting arguments as
+    /// [`TopLevelDecorator::from_str`](super::TopLevelDecorator::from_str) does options.
+    pub fn parse(class: &Class, diagnostics: &mut Vec<OxcDiagnostic>) -> Self {
+        let mut members = Self::default();
+        for element in &class.body.body {
+            let Some((key, decorators)) = decorated_member(element) else {
+                continue;
+            };
+            for decorator in decorators {
+                let Some((name, call_expr)) = member_decorator(decorator) else {
+                    continue;
+                };
+                let Some(property) = key.static_name() else {
+                    diagnostics.push(dynamic_member(name, key.span()));
+                    continue;
+                };
+                members.add(name, &property, key.span(), call_expr, diagnostics);
+            }
+        }
+        members
+    }
+
+    fn add(
+        &mut self,
+        name: &str,
+        property: &str,
+        key_span: Span,
+        call_expr: &CallExpression,
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) {
+        let arguments: Vec<_> = call_expr
+            .arguments
+            .iter()
+            .filter_map(Argument::as_expression)
+            .collect();
+        match PropertyDecorator::from_str(name) {
+            Some(PropertyDecorator::Input) => {
+                self.inputs
+                    .push(input(property, arguments.first().copied(), diagnostics));
+            }
+            Some(PropertyDecorator::Output) => {
+                let public_name = arguments
+                    .first()
+                    .and_then(|alias| expect_string("alias", alias, diagnostics));
+                self.outputs.push(DirectiveOutput {
+                    name: property.to_string(),
+                    public_name: public_name.map_or(property.to_string(), |alias| alias.value),
+                });
+            }
+            Some(PropertyDecorator::HostBinding) => {
+                let target = arguments
+                    .first()
+                    .and_then(|target| expect_string("hostPropertyName", target, diagnostics));
+                let name = format!(
+                    "[{}]",
+                    target.map_or(property.to_string(), |target| target.value)
+                );
+                self.host.push(Attribute {
+                    kind: AttributeKind::from_name(&name),
+                    name,
+                    value: property.to_string(),
+                    span: call_expr.span,
+                    value_span: key_span,
+                });
+            }
+            Some(PropertyDecorator::HostListener) => {
+                let Some(event) = arguments.first() else {
+                    diagnostics.push(missing_decorator_argument(
+                        "HostListener",
+                        "the name of an event",
+                        call_expr.span,
+                    ));
+                    return;
+                };
+                let Some(event) = expect_string("eventName", event, diagnostics) else {
+                    return;
+                };
+                let args: Vec<_> = arguments
+                    .get(1)
+                    .map(|args| strings(args))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|arg| arg.value)
+                    .collect();
+                let name = format!("({})", event.value);
+                self.host.push(Attribute {
+                    kind: AttributeKind::from_name(&name),
+                    name,
+                    value: format!("{}({})", property, args.join(", ")),
+                    span: call_expr.span,
+                    value_span: key_span,
+                });
+            }
+            Some(
+                decorator @ (PropertyDecorator::ViewChild
+                | PropertyDecorator::ViewChildren
+                | PropertyDecorator::ContentChild
+                | PropertyDecorator::ContentChildren),
+            ) => {
+                if let Some(query) = query(
+                    name,
+                    decorator,
+                    property,
+                    call_expr,
+                    &arguments,
+                    diagnostics,
+                ) {
+                    self.queries.push(query);
+                }
+            }
+            Some(PropertyDecorator::Attribute) | None => {}
+        }
+    }
+}
+
+/// The decorator name and call of a member decorator compiled into the definition, e.g.
+/// `@Input()`. `@Attribute` only applies to constructor parameters.
+pub fn member_decorator<'d, 'a>(
+    decorator: &'d Decorator<'a>,
+) -> Option<(&'d str, &'d CallExpression<'a>)> {
+    let Expression::CallExpression(call_expr) = &decorator.expression else {
+        return None;
+    };
+    let Expression::Identifier(identifier) = &call_expr.callee else {
+        return None;
+    };
+    match PropertyDecorator::from_str(&identifier.name)? {
+        PropertyDecorator::Attribute => None,
+        _ => Some((identifier.name.as_str(), call_expr)),
+    }
+}
+
+/// The key and decorators of a property, accessor or method.
+fn decorated_member<'e, 'a>(
+    element: &'e ClassElement<'a>,
+) -> Option<(&'e PropertyKey<'a>, &'e [Decorator<'a>])> {
+    match element {
+        ClassElement::PropertyDefinition(property) => Some((&property.key, &property.decorators)),
+        ClassElement::AccessorProperty(accessor) => Some((&accessor.key, &accessor.decorators)),
+        ClassElement::MethodDefinition(method) => Some((&method.key, &method.decorators)),
+        _ => None,
+    }
+}
+
+/// `@Input()`, `@Input("alias")` or `@Input({ alias, required, transform })`.
+fn input(
+    property: &str,
+    argument: Option<&Expression>,
+    diagnostics: &mut Vec<OxcDiagnostic>,
+) -> DirectiveInput {
+    const OPTIONS: &[&str] = &["alias", "required", "transform"];
+    let mut input = DirectiveInput {
+        name: property.to_string(),
+        public_name: property.to_string(),
+        required: false,
+        transform: None,
+    };
+    match argument {
+        None => {}
+        Some(Expression::ObjectExpression(object)) => {
+            for property in &object.properties {
+                let ObjectPropertyKind::ObjectProperty(property) = property else {
+                    diagnostics.push(dynamic_option("Input", property.span()));
+                    continue;
+                };
+                let Some(key) = property.key.static_name() else {
+                    diagnostics.push(dynamic_option("Input", property.key.span()));
+                    continue;
+                };
+                match key.as_ref() {
+                    "alias" => {
+                        if let Some(alias) = expect_string(&key, &property.value, diagnostics) {
+                            input.public_name = alias.value;
+                        }
+                    }
+                    "required" => {
+                        input.required = expect_boolean(&key, &property.value, diagnostics)
+                    }
+                    "transform" => input.transform = Some(property.value.span()),
+                    _ => diagnostics.push(unsupported_option(
+                        "Input",
+                        &key,
+                        OPTIONS,
+                        property.key.span(),
+                    )),
+                }
+            }
+        }
+        Some(alias) => {
+            if let Some(alias) = expect_string("alias", alias, diagnostics) {
+                input.public_name = alias.value;
+            }
+        }
+    }
+    input
+}
+
+/// `@ViewChild(predicate, { read, static })` and the like, each reading the options Angular
+/// gives it.
+fn query(
+    name: &str,
+    decorator: PropertyDecorator,
+    property: &str,
+    call_expr: &CallExpression,
+    arguments: &[&Expression],
+    diagnostics: &mut Vec<OxcDiagnostic>,
+) -> Option<DecoratorQuery> {
+    let (view, first, options): (_, _, &[&str]) = match decorator {
+        PropertyDecorator::ViewChild => (true, true, &["read", "static"]),
+        PropertyDecorator::ViewChildren => (true, false, &["read", "emitDistinctChangesOnly"]),
+        PropertyDecorator::ContentChild => (false, true, &["descendants", "read", "static"]),
+        _ => (
+            false,
+            false,
+            &["descendants", "emitDistinctChangesOnly", "read"],
+        ),
+    };
+    let Some(predicate) = arguments.first() else {
+        diagnostics.push(missing_decorator_argument(
+            name,
+            "a predicate",
+            call_expr.span,
+        ));
+        return None;
+    };
+    let predicate = match string(predicate) {
+        Some(references) => QueryPredicate::References(
+            references
+                .value
+                .split(',')
+                .map(|reference| reference.trim().to_string())
+                .filter(|reference| !reference.is_empty())
+                .collect(),
+        ),
+        None => QueryPredicate::Type(predicate.span()),
+    };
+
+    let mut query = DecoratorQuery {
+        property: property.to_string(),
+        view,
+        first,
+        predicate,
+        read: None,
+        // Only `@ContentChildren` stops at direct children unless told otherwise.
+        descendants: view || first,
+        is_static: false,
+        emit_distinct_changes_only: true,
+    };
+    match arguments.get(1) {
+        None => {}
+        Some(Expression::ObjectExpression(object)) => {
+            for property in &object.properties {
+                let ObjectPropertyKind::ObjectProperty(property) = property else {
+                    diagnostics.push(dynamic_option(name, property.span()));
+                    continue;
+                };
+                let Some(key) = property.key.static_name() else {
+                    diagnostics.push(dynamic_option(name, property.key.span()));
+                    continue;
+                };
+                if !options.contains(&key.as_ref()) {
+                    diagnostics.push(unsupported_option(name, &key, options, property.key.span()));
+                    continue;
+                }
+                match key.as_ref() {
+                    "read" => query.read = Some(property.value.span()),
+                    "static" => {
+                        query.is_static = expect_boolean(&key, &property.value, diagnostics)
+                    }
+                    "descendants" => {
+                        query.descendants = expect_boolean(&key, &property.value, diagnostics)
+                    }
+                    _ => {
+                        query.emit_distinct_changes_only =
+                            expect_boolean(&key, &property.value, diagnostics)
+                    }
+                }
+            }
+        }
+        Some(options) => diagnostics.push(options_not_literal(name, options.span())),
+    }
+    Some(query)
+}
@@ -1,5 +1,15 @@
+mod component;
 mod core;
+mod directive;
 mod injectable;
+mod members;
+mod ng_module;
+mod pipe;
 
+pub use self::component::*;
 pub use self::core::*;
-pub use self::injectable::*;
\ No newline at end of file
+pub use self::directive::*;
+pub use self::injectable::*;
+pub use self::members::*;
+pub use self::ng_module::*;
+pub use self::pipe::*;
\ No newline at end of file
@@ -0,0 +1,77 @@
+use oxc_ast::ast::{Decorator, ObjectPropertyKind};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_span::{GetSpan, Span};
+
+use super::directive::{expect_string, options_literal, StringOption};
+use crate::angular::diagnostics::{dynamic_option, unsupported_option};
+
+/// The options of `@NgModule`. The arrays are copied into the definitions as written, so they are
+/// kept as spans of the source.
+#[derive(Debug, Default)]
+pub struct NgModuleOptions {
+    pub declarations: Option<Span>,
+    pub imports: Option<Span>,
+    pub exports: Option<Span>,
+    pub bootstrap: Option<Span>,
+    pub providers: Option<Span>,
+    pub schemas: Option<Span>,
+    /// Registers the module to be looked up by `getNgModuleById`.
+    pub id: Option<StringOption>,
+}
+
+impl NgModuleOptions {
+    pub const OPTIONS: &'static [&'static str] = &[
+        "declarations",
+        "imports",
+        "exports",
+        "bootstrap",
+        "providers",
+        "schemas",
+        "id",
+    ];
+
+    pub fn parse_decorator(
+        decorator: &Decorator,
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> Option<NgModuleOptions> {
+        let object = options_literal("NgModule", decorator, diagnostics)?;
+        Some(NgModuleOptions::from_properties(
+            &object.properties,
+            diagnostics,
+        ))
+    }
+
+    pub fn from_properties(
+        properties: &[ObjectPropertyKind],
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> Self {
+        let mut options = Self::default();
+        for property in properties {
+            let ObjectPropertyKind::ObjectProperty(property) = property else {
+                diagnostics.push(dynamic_option("NgModule", property.span()));
+                continue;
+            };
+            let Some(key) = property.key.static_name() else {
+                diagnostics.push(dynamic_option("NgModule", property.key.span()));
+                continue;
+            };
+            let value = &property.value;
+            match key.as_ref() {
+                "declarations" => options.declarations = Some(value.span()),
+                "imports" => options.imports = Some(value.span()),
+                "exports" => options.exports = Some(value.span()),
+                "bootstrap" => options.bootstrap = Some(value.span()),
+                "providers" => options.providers = Some(value.span()),
+                "schemas" => options.schemas = Some(value.span()),
+                "id" => options.id = expect_string(&key, value, diagnostics),
+                _ => diagnostics.push(unsupported_option(
+                    "NgModule",
+                    &key,
+                    Self::OPTIONS,
+                    property.key.span(),
+                )),
+            }
+        }
+        options
+    }
+}
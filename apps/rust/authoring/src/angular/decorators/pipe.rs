@@ -0,0 +1,71 @@
+use oxc_ast::ast::{Decorator, ObjectPropertyKind};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_span::GetSpan;
+
+use super::directive::{expect_boolean, expect_string, options_literal, StringOption};
+use crate::angular::diagnostics::{dynamic_option, unsupported_option};
+
+/// The options of `@Pipe`.
+#[derive(Debug)]
+pub struct PipeOptions {
+    /// The name templates use the pipe by.
+    pub name: Option<StringOption>,
+    /// Whether the pipe only runs again when its arguments change.
+    pub pure: bool,
+    pub standalone: bool,
+}
+
+impl Default for PipeOptions {
+    fn default() -> Self {
+        Self {
+            name: None,
+            pure: true,
+            standalone: false,
+        }
+    }
+}
+
+impl PipeOptions {
+    pub const OPTIONS: &'static [&'static str] = &["name", "pure", "standalone"];
+
+    pub fn parse_decorator(
+        decorator: &Decorator,
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> Option<PipeOptions> {
+        let object = options_literal("Pipe", decorator, diagnostics)?;
+        Some(PipeOptions::from_properties(
+            &object.properties,
+            diagnostics,
+        ))
+    }
+
+    pub fn from_properties(
+        properties: &[ObjectPropertyKind],
+        diagnostics: &mut Vec<OxcDiagnostic>,
+    ) -> Self {
+        let mut options = Self::default();
+        for property in properties {
+            let ObjectPropertyKind::ObjectProperty(property) = property else {
+                diagnostics.push(dynamic_option("Pipe", property.span()));
+                continue;
+            };
+            let Some(key) = property.key.static_name() else {
+                diagnostics.push(dynamic_option("Pipe", property.key.span()));
+                continue;
+            };
+            let value = &property.value;
+            match key.as_ref() {
+                "name" => options.name = expect_string(&key, value, diagnostics),
+                "pure" => options.pure = expect_boolean(&key, value, diagnostics),
+                "standalone" => options.standalone = expect_boolean(&key, value, diagnostics),
+                _ => diagnostics.push(unsupported_option(
+                    "Pipe",
+                    &key,
+                    Self::OPTIONS,
+                    property.key.span(),
+                )),
+            }
+        }
+        options
+    }
+}
@@ -0,0 +1,175 @@
+//! Every diagnostic the Angular transform reports, each with a code in the `angular` scope, e.g.
+//! `angular(unsupported-option)`, so tools can refer to and filter them.
+
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_span::Span;
+
+const SCOPE: &str = "angular";
+
+fn error(code: &'static str, message: impl Into<String>) -> OxcDiagnostic {
+    OxcDiagnostic::error(message.into()).with_error_code(SCOPE, code)
+}
+
+fn warn(code: &'static str, message: impl Into<String>) -> OxcDiagnostic {
+    OxcDiagnostic::warn(message.into()).with_error_code(SCOPE, code)
+}
+
+/// `@Component(options)` where `options` is not an object literal.
+pub fn options_not_literal(decorator: &str, span: Span) -> OxcDiagnostic {
+    error(
+        "options-not-literal",
+        format!("The options of `@{}` must be an object literal", decorator),
+    )
+    .with_help("Decorators are compiled ahead of time, so their options are read from the source")
+    .with_label(span.label("Not an object literal"))
+}
+
+/// A spread or computed key among the options of a decorator.
+pub fn dynamic_option(decorator: &str, span: Span) -> OxcDiagnostic {
+    error(
+        "dynamic-option",
+        format!("The options of `@{}` must be written out", decorator),
+    )
+    .with_help("Spread and computed keys are only known at runtime; name each option")
+    .with_label(span.label("Cannot be read statically"))
+}
+
+/// `@Input()` on a member with a computed or private name, e.g. `[key]` or `#value`.
+pub fn dynamic_member(decorator: &str, span: Span) -> OxcDiagnostic {
+    error(
+        "dynamic-member",
+        format!("`@{}` must decorate a member with a static name", decorator),
+    )
+    .with_help("The definition refers to the member by name, so the name must be written out")
+    .with_label(span.label("Cannot be read statically"))
+}
+
+/// An option this transform does not read, misspelled like `selecter` or unsupported like
+/// `animations`.
+pub fn unsupported_option(decorator: &str, key: &str, known: &[&str], span: Span) -> OxcDiagnostic {
+    warn(
+        "unsupported-option",
+        format!("Option `{}` of `@{}` is not supported", key, decorator),
+    )
+    .with_help(format!("Supported options are {}", quoted_list(known)))
+    .with_label(span.label("Ignored"))
+}
+
+/// A string option set to anything else, e.g. `selector: SELECTOR`.
+pub fn expected_string(key: &str, span: Span) -> OxcDiagnostic {
+    error(
+        "expected-string",
+        format!("`{}` must be a string literal", key),
+    )
+    .with_help("Template literals are allowed as long as they have no expressions")
+    .with_label(span.label("Not a string literal"))
+}
+
+/// A boolean option set to anything else, e.g. `standalone: isStandalone`.
+pub fn expected_boolean(key: &str, span: Span) -> OxcDiagnostic {
+    error(
+        "expected-boolean",
+        format!("`{}` must be `true` or `false`", key),
+    )
+    .with_label(span.label("Not a boolean literal"))
+}
+
+/// An enum option set to anything but one of its members, e.g. `changeDetection: 0`.
+pub fn expected_member(key: &str, members: &[&str], span: Span) -> OxcDiagnostic {
+    error(
+        "expected-member",
+        format!("`{}` must be one of {}", key, quoted_list(members)),
+    )
+    .with_help("Refer to the member of the enum, e.g. `ChangeDetectionStrategy.OnPush`")
+    .with_label(span.label("Not a known member"))
+}
+
+/// `providedIn: "everywhere"`.
+pub fn invalid_provided_in(span: Span) -> OxcDiagnostic {
+    error(
+        "invalid-provided-in",
+        "`providedIn` must be \"root\", \"platform\", \"any\", `null` or a module",
+    )
+    .with_label(span.label("Unknown injector"))
+}
+
+/// `@Inject()` or `@Attribute()` without its argument.
+pub fn missing_decorator_argument(decorator: &str, expected: &str, span: Span) -> OxcDiagnostic {
+    error(
+        "missing-decorator-argument",
+        format!("`@{}` requires {}", decorator, expected),
+    )
+    .with_label(span)
+}
+
+/// A constructor parameter neither typed with a class nor decorated with `@Inject`.
+pub fn missing_injection_token(span: Span) -> OxcDiagnostic {
+    error(
+        "missing-injection-token",
+        "No suitable injection token for this parameter",
+    )
+    .with_help("Type it with a class, or name its token with `@Inject`")
+    .with_label(span)
+}
+
+/// `inject()` where nothing is being injected.
+pub fn inject_outside_context(help: &'static str, span: Span) -> OxcDiagnostic {
+    error(
+        "inject-outside-context",
+        "`inject()` must be called from an injection context",
+    )
+    .with_help(help)
+    .with_label(span)
+}
+
+/// `inject()` without a token.
+pub fn inject_missing_token(span: Span) -> OxcDiagnostic {
+    error("inject-missing-token", "`inject()` requires a token").with_label(span)
+}
+
+/// `inject(Token, options)` where `options` is not written out.
+pub fn inject_options_not_literal(span: Span) -> OxcDiagnostic {
+    error(
+        "inject-options-not-literal",
+        "Expected `optional`, `self`, `skipSelf` or `host` set to a boolean",
+    )
+    .with_help("The options of `inject()` are compiled into flags, so they must be written out")
+    .with_label(span)
+}
+
+/// `@Pipe({})`.
+pub fn pipe_missing_name(span: Span) -> OxcDiagnostic {
+    error("pipe-missing-name", "`@Pipe` requires a `name`")
+        .with_help("Name the pipe as templates use it, e.g. `name: \"currency\"`")
+        .with_label(span)
+}
+
+/// `templateUrl`, which would need the file system.
+pub fn template_url_unsupported(span: Span) -> OxcDiagnostic {
+    error(
+        "template-url-unsupported",
+        "`templateUrl` cannot be loaded by this transform",
+    )
+    .with_help("Inline the template with `template`")
+    .with_label(span)
+}
+
+/// `styleUrl` and `styleUrls`, which would need the file system.
+pub fn style_url_unsupported(span: Span) -> OxcDiagnostic {
+    error(
+        "style-url-unsupported",
+        "`styleUrls` cannot be loaded by this transform",
+    )
+    .with_help("Inline the styles with `styles`")
+    .with_label(span)
+}
+
+/// `` `a`, `b` or `c` ``.
+fn quoted_list(names: &[&str]) -> String {
+    let names: Vec<_> = names.iter().map(|name| format!("`{}`", name)).collect();
+    match names.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,272 @@
+use std::cell::Cell;
+
+use oxc_ast::{ast::*, visit::walk_mut, AstBuilder, VisitMut};
+use oxc_semantic::{NodeId, ReferenceFlags, ScopeFlags, ScopeId, SymbolFlags, SymbolId};
+use oxc_span::SPAN;
+use tracing::{debug, trace};
+
+use super::context::{AngularContext, AngularCtx};
+
+/// Namespaces as the generated code writes them, e.g. `i0.ɵɵdefineComponent`, with their module.
+const PLACEHOLDERS: [(&str, &str); 2] = [("i0", "@angular/core"), ("i1", "@angular/common")];
+
+/// Adds the namespace imports of the runtime and points the generated references at them.
+/// The references of the generated code are resolved in the semantic model.
+/// Specifiers of `@angular` imports the output no longer refers to are dropped, e.g. those of
+/// removed decorators. So are the declarations left without any.
+pub(super) fn manage_imports<'a>(
+    ast: &AstBuilder<'a>,
+    context: &AngularCtx<'a>,
+    program: &mut Program<'a>,
+) {
+    let mut references = GeneratedReferences {
+        ast,
+        context,
+        symbols: Vec::new(),
+        scopes: Vec::new(),
+        binding: SymbolFlags::BlockScopedVariable,
+    };
+    references.visit_program(program);
+
+    for statement in program.body.iter_mut() {
+        if let Statement::ImportDeclaration(decl) = statement {
+            if let (true, Some(specifiers)) = (
+                decl.source.value.starts_with("@angular"),
+                &mut decl.specifiers,
+            ) {
+                specifiers.retain(|specifier| {
+                    let referenced = specifier
+                        .local()
+                        .symbol_id
+                        .get()
+                        .is_none_or(|symbol| references.symbols.contains(&symbol));
+                    if !referenced {
+                        trace!(specifier = %specifier.local().name, "dropped unused import");
+                    }
+                    referenced
+                });
+            }
+        }
+    }
+    program.body.retain(|statement| {
+        !matches!(
+            statement,
+            Statement::ImportDeclaration(decl) if decl.source.value.starts_with("@angular")
+                && decl.specifiers.as_ref().is_some_and(|s| s.is_empty())
+        )
+    });
+
+    // Built rather than parsed so that nothing in the source maps claims to be their origin.
+    for (index, import) in context.namespace_imports().into_iter().enumerate() {
+        debug!(alias = %import.alias, module = %import.module, "added namespace import");
+        let local = ast.binding_identifier(SPAN, ast.atom(&import.alias));
+        local.symbol_id.set(Some(import.symbol_id));
+        let specifiers =
+            ast.vec1(ast.import_declaration_specifier_import_namespace_specifier(SPAN, local));
+        let declaration = ast.module_declaration_import_declaration(
+            SPAN,
+            Some(specifiers),
+            ast.string_literal(SPAN, ast.atom(&import.module)),
+            None::<WithClause>,
+            ImportOrExportKind::Value,
+        );
+        program
+            .body
+            .insert(index, ast.statement_module_declaration(declaration));
+    }
+}
+
+/// Resolves the generated code in the semantic model. It was parsed on its own, so its scopes,
+/// bindings and references are created here, as the semantic analysis of the source did for the
+/// rest.
+struct GeneratedReferences<'c, 'a> {
+    ast: &'c AstBuilder<'a>,
+    context: &'c AngularCtx<'a>,
+    /// Symbols the output refers to.
+    symbols: Vec<SymbolId>,
+    /// The scopes around the node being visited, innermost last.
+    scopes: Vec<ScopeId>,
+    /// What the binding identifiers being visited declare, e.g. a `let` or a parameter.
+    binding: SymbolFlags,
+}
+
+impl<'c, 'a> GeneratedReferences<'c, 'a> {
+    fn current_scope(&self) -> ScopeId {
+        *self
+            .scopes
+            .last()
+            .expect("visited within the program scope")
+    }
+
+    /// Binds a generated declaration in `scope_id`, taking over the symbol of a name reserved
+    /// there with `generate_uid`.
+    fn declare(&mut self, it: &BindingIdentifier<'a>, scope_id: ScopeId, flags: SymbolFlags) {
+        if it.symbol_id.get().is_some() {
+            return;
+        }
+        let reserved = self.context.scopes().get_binding(scope_id, &it.name);
+        let symbol_id = reserved.unwrap_or_else(|| {
+            self.context
+                .add_binding(scope_id, it.name.to_compact_str(), flags)
+        });
+        it.symbol_id.set(Some(symbol_id));
+    }
+
+    fn reference(&mut self, it: &mut IdentifierReference<'a>, flags: ReferenceFlags) {
+        // References in the source were resolved by the semantic analysis, generated ones were not.
+        if let Some(reference) = it.reference_id.get() {
+            let symbol = self.context.symbols().get_reference(reference).symbol_id();
+            self.symbols.extend(symbol);
+            return;
+        }
+        let symbol_id = match PLACEHOLDERS
+            .iter()
+            .find(|(placeholder, _)| it.name == *placeholder)
+        {
+            Some((_, module)) => {
+                let alias = self.context.namespace(module);
+                it.name = self.ast.atom(&alias);
+                self.context
+                    .namespace_imports()
+                    .into_iter()
+                    .find(|import| import.alias == alias)
+                    .map(|import| import.symbol_id)
+            }
+            // The bindings of the generated code itself, e.g. factory parameters, or names copied
+            // from the source, e.g. the class or its `imports`.
+            None => self
+                .context
+                .scopes()
+                .find_binding(self.current_scope(), &it.name),
+        };
+        let reference_id = self.context.create_reference(&it.name, symbol_id, flags);
+        it.reference_id.set(Some(reference_id));
+        self.symbols.extend(symbol_id);
+    }
+}
+
+impl<'c, 'a> VisitMut<'a> for GeneratedReferences<'c, 'a> {
+    fn enter_scope(&mut self, flags: ScopeFlags, scope_id: &Cell<Option<ScopeId>>) {
+        let scope = scope_id.get().unwrap_or_else(|| {
+            let parent = self.scopes.last().copied();
+            let scope = self
+                .context
+                .scopes_mut()
+                .add_scope(parent, NodeId::DUMMY, flags);
+            scope_id.set(Some(scope));
+            scope
+        });
+        self.scopes.push(scope);
+    }
+
+    fn leave_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn visit_function(&mut self, it: &mut Function<'a>, flags: ScopeFlags) {
+        // A declaration binds its name around it, an expression only within itself.
+        if let Some(id) = it.id.as_ref().filter(|_| it.is_declaration()) {
+            self.declare(id, self.current_scope(), SymbolFlags::Function);
+        }
+        self.binding = SymbolFlags::Function;
+        walk_mut::walk_function(self, it, flags);
+    }
+
+    fn visit_formal_parameters(&mut self, it: &mut FormalParameters<'a>) {
+        self.binding = SymbolFlags::FunctionScopedVariable;
+        walk_mut::walk_formal_parameters(self, it);
+    }
+
+    fn visit_variable_declaration(&mut self, it: &mut VariableDeclaration<'a>) {
+        self.binding = match it.kind {
+            VariableDeclarationKind::Var => SymbolFlags::FunctionScopedVariable,
+            VariableDeclarationKind::Const => {
+                SymbolFlags::BlockScopedVariable | SymbolFlags::ConstVariable
+            }
+            _ => SymbolFlags::BlockScopedVariable,
+        };
+        walk_mut::walk_variable_declaration(self, it);
+    }
+
+    fn visit_binding_identifier(&mut self, it: &mut BindingIdentifier<'a>) {
+        // `var` and parameters belong to the function, the rest to the block they are in.
+        let scope_id = if self.binding == SymbolFlags::FunctionScopedVariable {
+            let scopes = self.context.scopes();
+            let function = scopes
+                .ancestors(self.current_scope())
+                .find(|scope_id| scopes.get_flags(*scope_id).is_var());
+            function.unwrap_or_else(|| scopes.root_scope_id())
+        } else {
+            self.current_scope()
+        };
+        self.declare(it, scope_id, self.binding);
+    }
+
+    fn visit_assignment_expression(&mut self, it: &mut AssignmentExpression<'a>) {
+        let AssignmentTarget::AssignmentTargetIdentifier(identifier) = &mut it.left else {
+            walk_mut::walk_assignment_expression(self, it);
+            return;
+        };
+        let flags = if it.operator == AssignmentOperator::Assign {
+            ReferenceFlags::Write
+        } else {
+            ReferenceFlags::read_write()
+        };
+        self.reference(identifier, flags);
+        self.visit_expression(&mut it.right);
+    }
+
+    fn visit_identifier_reference(&mut self, it: &mut IdentifierReference<'a>) {
+        self.reference(it, ReferenceFlags::Read);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{transform, TransformOptions};
+
+    fn compile(source: &str) -> String {
+        let result = transform(source, "test.ts", &TransformOptions::default());
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        result.code
+    }
+
+    #[test]
+    fn unused_angular_specifiers_are_removed() {
+        let code = compile(
+            "import { Injectable, EventEmitter } from '@angular/core';\n\
+             import { Http } from './http';\n\
+             @Injectable()\n\
+             export class Service { changes = new EventEmitter(); }\n",
+        );
+        assert!(code.contains("import { EventEmitter } from \"@angular/core\";"));
+        assert!(code.contains("import { Http } from \"./http\";"));
+    }
+
+    #[test]
+    fn empty_declarations_are_dropped() {
+        let code = compile(
+            "import '@angular/localize/init';\n\
+             import { Injectable } from '@angular/core';\n\
+             @Injectable()\n\
+             export class Service {}\n",
+        );
+        assert!(code.starts_with(
+            "import * as i0 from \"@angular/core\";\nimport \"@angular/localize/init\";\n"
+        ));
+        assert!(!code.contains("import {"));
+    }
+
+    #[test]
+    fn namespace_alias_skips_taken_names() {
+        let code = compile(
+            "import { Injectable } from '@angular/core';\n\
+             const i0 = 0;\n\
+             @Injectable()\n\
+             export class Service { value = i0; }\n",
+        );
+        assert!(code.contains("import * as i1 from \"@angular/core\";"));
+        assert!(code.contains("i1.ɵɵdefineInjectable"));
+        assert!(code.contains("value = i0;"));
+    }
+}
@@ -0,0 +1,290 @@
+//! `inject()` calls, found and checked here for both the Angular transform and the component
+//! compiler, which each rewrite them in their own output.
+
+use oxc_ast::{
+    ast::{
+        Argument, ArrowFunctionExpression, CallExpression, Class, ClassElement, Expression,
+        Function, ImportDeclarationSpecifier, MethodDefinitionKind, Program, Statement,
+    },
+    visit::walk,
+    Visit,
+};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_semantic::{ScopeFlags, SymbolId, SymbolTable};
+use oxc_span::{GetSpan, Span};
+
+use super::diagnostics::{
+    inject_missing_token, inject_options_not_literal, inject_outside_context,
+};
+use super::inject_flags;
+use super::runtime::{Identifier, InjectFlags};
+
+/// Where an `inject()` call runs.
+#[derive(Clone, Copy)]
+pub enum InjectionContext {
+    /// Run by a factory, which injects with `inject` and `flags`, e.g. a field initializer.
+    Factory { inject: Identifier, flags: u8 },
+    /// Module code, run once on import; the functions it declares may be factories.
+    Module,
+    /// A function, which may be called from a factory, e.g. a `useFactory`.
+    Function,
+    /// Never in an injection context, with why.
+    Outside(&'static str),
+}
+
+/// A call to the `inject` of `@angular/core`, e.g. `inject(Token, { optional: true })`, and the
+/// instruction it compiles to, e.g. `i0.ɵɵinject(Token, 8)`.
+pub struct InjectCall {
+    pub span: Span,
+    pub callee: Span,
+    pub token: Span,
+    /// The options, if any.
+    pub options: Option<Span>,
+    pub inject: Identifier,
+    pub flags: u8,
+}
+
+/// Finds the `inject()` calls of `program`, whose code starts out in `injection`, reporting those
+/// that do not run in an injection context or whose token or options cannot be compiled.
+pub fn inject_calls(
+    symbols: &SymbolTable,
+    program: &Program,
+    injection: InjectionContext,
+) -> (Vec<InjectCall>, Vec<OxcDiagnostic>) {
+    let mut calls = InjectCalls {
+        symbols,
+        inject: inject_bindings(program),
+        injection,
+        calls: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    if !calls.inject.is_empty() {
+        calls.visit_program(program);
+    }
+    (calls.calls, calls.diagnostics)
+}
+
+/// Local bindings of the `inject` imported from `@angular/core`.
+fn inject_bindings(program: &Program) -> Vec<SymbolId> {
+    program
+        .body
+        .iter()
+        .filter_map(|statement| match statement {
+            Statement::ImportDeclaration(decl) if decl.source.value == "@angular/core" => {
+                decl.specifiers.as_ref()
+            }
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|specifier| match specifier {
+            ImportDeclarationSpecifier::ImportSpecifier(specifier)
+                if specifier.imported.name() == "inject" =>
+            {
+                specifier.local.symbol_id.get()
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The factory of a class with an Angular decorator: directives and pipes inject from the
+/// element they are on.
+fn factory_context(class: &Class) -> Option<InjectionContext> {
+    let names: Vec<_> = class
+        .decorators
+        .iter()
+        .filter_map(|decorator| match &decorator.expression {
+            Expression::CallExpression(call) => match &call.callee {
+                Expression::Identifier(identifier) => Some(identifier.name.as_str()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let (inject, flags) = if names
+        .iter()
+        .any(|name| matches!(*name, "Component" | "Directive"))
+    {
+        (Identifier::DirectiveInject, InjectFlags::Default as u8)
+    } else if names.contains(&"Pipe") {
+        (Identifier::DirectiveInject, InjectFlags::ForPipe as u8)
+    } else if names
+        .iter()
+        .any(|name| matches!(*name, "Injectable" | "NgModule"))
+    {
+        (Identifier::Inject, InjectFlags::Default as u8)
+    } else {
+        return None;
+    };
+    Some(InjectionContext::Factory { inject, flags })
+}
+
+struct InjectCalls<'s> {
+    symbols: &'s SymbolTable,
+    inject: Vec<SymbolId>,
+    injection: InjectionContext,
+    calls: Vec<InjectCall>,
+    diagnostics: Vec<OxcDiagnostic>,
+}
+
+impl<'s> InjectCalls<'s> {
+    fn is_inject(&self, call: &CallExpression) -> bool {
+        let Expression::Identifier(identifier) = &call.callee else {
+            return false;
+        };
+        identifier
+            .reference_id
+            .get()
+            .and_then(|id| self.symbols.get_reference(id).symbol_id())
+            .is_some_and(|symbol| self.inject.contains(&symbol))
+    }
+
+    fn check(&mut self, call: &CallExpression) {
+        let (inject, mut flags) = match self.injection {
+            InjectionContext::Factory { inject, flags } => (inject, flags),
+            InjectionContext::Module => {
+                self.diagnostics.push(inject_outside_context(
+                    "Module code runs before anything is injected; call it in a field initializer or constructor",
+                    call.span,
+                ));
+                return;
+            }
+            InjectionContext::Function => (Identifier::Inject, InjectFlags::Default as u8),
+            InjectionContext::Outside(help) => {
+                self.diagnostics
+                    .push(inject_outside_context(help, call.span));
+                return;
+            }
+        };
+        let Some(token) = call.arguments.first().and_then(Argument::as_expression) else {
+            self.diagnostics.push(inject_missing_token(call.span));
+            return;
+        };
+        let options = call.arguments.get(1);
+        if let Some(options) = options {
+            match options
+                .as_expression()
+                .map_or(Err(options.span()), inject_flags)
+            {
+                Ok(options) => flags |= options,
+                Err(span) => {
+                    self.diagnostics.push(inject_options_not_literal(span));
+                    return;
+                }
+            }
+        }
+        self.calls.push(InjectCall {
+            span: call.span,
+            callee: call.callee.span(),
+            token: token.span(),
+            options: options.map(GetSpan::span),
+            inject,
+            flags,
+        });
+    }
+
+    /// Runs `walk` in `injection`, then restores the current one.
+    fn within(&mut self, injection: InjectionContext, walk: impl FnOnce(&mut Self)) {
+        let outer = std::mem::replace(&mut self.injection, injection);
+        walk(self);
+        self.injection = outer;
+    }
+
+    /// Functions nested in code a factory runs are callbacks, run later.
+    fn function_context(&self, r#async: bool) -> InjectionContext {
+        match self.injection {
+            _ if r#async => InjectionContext::Outside(
+                "Async functions resume after the injection context is gone; inject before the first `await`",
+            ),
+            InjectionContext::Factory { .. } => InjectionContext::Outside(
+                "Callbacks run after the injection context is gone; inject beforehand and capture the result",
+            ),
+            InjectionContext::Module | InjectionContext::Function => InjectionContext::Function,
+            outside @ InjectionContext::Outside(_) => outside,
+        }
+    }
+}
+
+impl<'s, 'a> Visit<'a> for InjectCalls<'s> {
+    fn visit_call_expression(&mut self, call: &CallExpression<'a>) {
+        if self.is_inject(call) {
+            self.check(call);
+        }
+        walk::walk_call_expression(self, call);
+    }
+
+    fn visit_function(&mut self, function: &Function<'a>, flags: ScopeFlags) {
+        let injection = self.function_context(function.r#async);
+        self.within(injection, |calls| {
+            walk::walk_function(calls, function, flags)
+        });
+    }
+
+    fn visit_arrow_function_expression(&mut self, arrow: &ArrowFunctionExpression<'a>) {
+        let injection = self.function_context(arrow.r#async);
+        self.within(injection, |calls| {
+            walk::walk_arrow_function_expression(calls, arrow)
+        });
+    }
+
+    fn visit_class(&mut self, class: &Class<'a>) {
+        self.visit_decorators(&class.decorators);
+        if let Some(super_class) = &class.super_class {
+            self.visit_expression(super_class);
+        }
+
+        // Only classes compiled with a factory here are constructed in an injection context.
+        let factory = match self.injection {
+            InjectionContext::Module | InjectionContext::Function => factory_context(class),
+            InjectionContext::Factory { .. } | InjectionContext::Outside(_) => None,
+        };
+        let Some(constructed) = factory else {
+            self.within(
+                InjectionContext::Outside(
+                    "Only classes with an Angular decorator are constructed by a factory; add `@Injectable()` or pass the value in",
+                ),
+                |calls| walk::walk_class_body(calls, &class.body),
+            );
+            return;
+        };
+
+        for element in &class.body.body {
+            match element {
+                ClassElement::PropertyDefinition(property) if !property.r#static => {
+                    self.visit_property_key(&property.key);
+                    if let Some(value) = &property.value {
+                        self.within(constructed, |calls| calls.visit_expression(value));
+                    }
+                }
+                ClassElement::MethodDefinition(method)
+                    if method.kind == MethodDefinitionKind::Constructor =>
+                {
+                    let function = &method.value;
+                    self.within(constructed, |calls| {
+                        calls.visit_formal_parameters(&function.params);
+                        if let Some(body) = &function.body {
+                            calls.visit_function_body(body);
+                        }
+                    });
+                }
+                // Methods run once the class is created, e.g. as lifecycle hooks.
+                ClassElement::MethodDefinition(method) => {
+                    self.visit_property_key(&method.key);
+                    let function = &method.value;
+                    self.within(
+                        InjectionContext::Outside(
+                            "Methods run after the class is constructed; inject in a field initializer or the constructor",
+                        ),
+                        |calls| {
+                            calls.visit_formal_parameters(&function.params);
+                            if let Some(body) = &function.body {
+                                calls.visit_function_body(body);
+                            }
+                        },
+                    );
+                }
+                element => self.visit_class_element(element),
+            }
+        }
+    }
+}
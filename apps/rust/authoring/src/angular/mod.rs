@@ -1,8 +1,15 @@
+#[allow(clippy::module_inception)]
 mod angular;
-mod runtime;
+pub mod runtime;
+pub mod diagnostics;
+pub mod inject;
 mod context;
+mod imports;
 mod decorators;
 mod transformers;
+mod transform;
 pub use self::decorators::*;
 pub use self::angular::Angular;
-pub use self::transformers::*;
\ No newline at end of file
+pub use self::context::{AngularContext, AngularCtx, NamespaceImport};
+pub use self::transformers::*;
+pub use self::transform::{transform, TransformOptions, TransformResult};
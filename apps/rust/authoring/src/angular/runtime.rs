@@ -6,10 +6,79 @@ use lazy_static::lazy_static;
 #[derive(Copy, Clone, Hash, Eq, PartialEq)]
 pub enum Identifier {
     Advance,
+    Attribute,
+    ClassMap,
+    ClassProp,
+    Conditional,
+    ContentQuery,
+    ContentQuerySignal,
+    CopyDefinitionFeature,
+    Defer,
+    DeferEnableTimerScheduling,
+    DeferOnHover,
+    DeferOnIdle,
+    DeferOnImmediate,
+    DeferOnInteraction,
+    DeferOnTimer,
+    DeferOnViewport,
+    DeferPrefetchOnHover,
+    DeferPrefetchOnIdle,
+    DeferPrefetchOnImmediate,
+    DeferPrefetchOnInteraction,
+    DeferPrefetchOnTimer,
+    DeferPrefetchOnViewport,
+    DeferPrefetchWhen,
+    DeferWhen,
     DefineComponent,
+    DefineDirective,
+    DefineInjectable,
+    DefineInjector,
+    DefineNgModule,
+    DefinePipe,
+    DirectiveInject,
     Element,
     ElementEnd,
     ElementStart,
+    GetCurrentView,
+    GetInheritedFactory,
+    HostDirectivesFeature,
+    HostProperty,
+    InheritDefinitionFeature,
+    Inject,
+    InjectAttribute,
+    InputTransformsFeature,
+    InvalidFactoryDep,
+    Listener,
+    LoadQuery,
+    NextContext,
+    NgOnChangesFeature,
+    Pipe,
+    PipeBind1,
+    PipeBind2,
+    PipeBind3,
+    PipeBind4,
+    PipeBindV,
+    Property,
+    ProvidersFeature,
+    QueryAdvance,
+    QueryRefresh,
+    Reference,
+    RegisterNgModuleType,
+    Repeater,
+    RepeaterCreate,
+    RepeaterTrackByIdentity,
+    RepeaterTrackByIndex,
+    ResetView,
+    ResolveBody,
+    ResolveDocument,
+    ResolveWindow,
+    RestoreView,
+    SetNgModuleScope,
+    StandaloneFeature,
+    StyleMap,
+    StyleProp,
+    SyntheticHostListener,
+    SyntheticHostProperty,
     Template,
     Text,
     TextInterpolate,
@@ -22,14 +91,21 @@ pub enum Identifier {
     TextInterpolate7,
     TextInterpolate8,
     TextInterpolateV,
+    TwoWayBindingSet,
+    TwoWayListener,
+    TwoWayProperty,
+    ViewQuery,
+    ViewQuerySignal,
 }
 
 impl Identifier {
-    pub fn imported<OpaqueT>(self) -> (String, String) {
-        return (
-            "@angular/core".to_string(),
-            RUNTIME.get(&self).unwrap().to_string(),
-        );
+    pub fn imported(self) -> (String, String) {
+        ("@angular/core".to_string(), self.name().to_string())
+    }
+
+    /// The exported name, e.g. `ɵɵelementStart`.
+    pub fn name(self) -> &'static str {
+        RUNTIME.get(&self).unwrap()
     }
 }
 
@@ -37,10 +113,79 @@ lazy_static! {
     static ref RUNTIME: HashMap<Identifier, &'static str> = {
         HashMap::from([
             (Identifier::Advance, "ɵɵadvance"),
+            (Identifier::Attribute, "ɵɵattribute"),
+            (Identifier::ClassMap, "ɵɵclassMap"),
+            (Identifier::ClassProp, "ɵɵclassProp"),
+            (Identifier::Conditional, "ɵɵconditional"),
+            (Identifier::ContentQuery, "ɵɵcontentQuery"),
+            (Identifier::ContentQuerySignal, "ɵɵcontentQuerySignal"),
+            (Identifier::CopyDefinitionFeature, "ɵɵCopyDefinitionFeature"),
+            (Identifier::Defer, "ɵɵdefer"),
+            (Identifier::DeferEnableTimerScheduling, "ɵɵdeferEnableTimerScheduling"),
+            (Identifier::DeferOnHover, "ɵɵdeferOnHover"),
+            (Identifier::DeferOnIdle, "ɵɵdeferOnIdle"),
+            (Identifier::DeferOnImmediate, "ɵɵdeferOnImmediate"),
+            (Identifier::DeferOnInteraction, "ɵɵdeferOnInteraction"),
+            (Identifier::DeferOnTimer, "ɵɵdeferOnTimer"),
+            (Identifier::DeferOnViewport, "ɵɵdeferOnViewport"),
+            (Identifier::DeferPrefetchOnHover, "ɵɵdeferPrefetchOnHover"),
+            (Identifier::DeferPrefetchOnIdle, "ɵɵdeferPrefetchOnIdle"),
+            (Identifier::DeferPrefetchOnImmediate, "ɵɵdeferPrefetchOnImmediate"),
+            (Identifier::DeferPrefetchOnInteraction, "ɵɵdeferPrefetchOnInteraction"),
+            (Identifier::DeferPrefetchOnTimer, "ɵɵdeferPrefetchOnTimer"),
+            (Identifier::DeferPrefetchOnViewport, "ɵɵdeferPrefetchOnViewport"),
+            (Identifier::DeferPrefetchWhen, "ɵɵdeferPrefetchWhen"),
+            (Identifier::DeferWhen, "ɵɵdeferWhen"),
             (Identifier::DefineComponent, "ɵɵdefineComponent"),
+            (Identifier::DefineDirective, "ɵɵdefineDirective"),
+            (Identifier::DefineInjectable, "ɵɵdefineInjectable"),
+            (Identifier::DefineInjector, "ɵɵdefineInjector"),
+            (Identifier::DefineNgModule, "ɵɵdefineNgModule"),
+            (Identifier::DefinePipe, "ɵɵdefinePipe"),
+            (Identifier::DirectiveInject, "ɵɵdirectiveInject"),
             (Identifier::Element, "ɵɵelement"),
             (Identifier::ElementEnd, "ɵɵelementEnd"),
             (Identifier::ElementStart, "ɵɵelementStart"),
+            (Identifier::GetCurrentView, "ɵɵgetCurrentView"),
+            (Identifier::GetInheritedFactory, "ɵɵgetInheritedFactory"),
+            (Identifier::HostDirectivesFeature, "ɵɵHostDirectivesFeature"),
+            (Identifier::HostProperty, "ɵɵhostProperty"),
+            (Identifier::InheritDefinitionFeature, "ɵɵInheritDefinitionFeature"),
+            (Identifier::Inject, "ɵɵinject"),
+            (Identifier::InjectAttribute, "ɵɵinjectAttribute"),
+            (Identifier::InputTransformsFeature, "ɵɵInputTransformsFeature"),
+            (Identifier::InvalidFactoryDep, "ɵɵinvalidFactoryDep"),
+            (Identifier::Listener, "ɵɵlistener"),
+            (Identifier::LoadQuery, "ɵɵloadQuery"),
+            (Identifier::NextContext, "ɵɵnextContext"),
+            (Identifier::NgOnChangesFeature, "ɵɵNgOnChangesFeature"),
+            (Identifier::Pipe, "ɵɵpipe"),
+            (Identifier::PipeBind1, "ɵɵpipeBind1"),
+            (Identifier::PipeBind2, "ɵɵpipeBind2"),
+            (Identifier::PipeBind3, "ɵɵpipeBind3"),
+            (Identifier::PipeBind4, "ɵɵpipeBind4"),
+            (Identifier::PipeBindV, "ɵɵpipeBindV"),
+            (Identifier::Property, "ɵɵproperty"),
+            (Identifier::ProvidersFeature, "ɵɵProvidersFeature"),
+            (Identifier::QueryAdvance, "ɵɵqueryAdvance"),
+            (Identifier::QueryRefresh, "ɵɵqueryRefresh"),
+            (Identifier::Reference, "ɵɵreference"),
+            (Identifier::RegisterNgModuleType, "ɵɵregisterNgModuleType"),
+            (Identifier::Repeater, "ɵɵrepeater"),
+            (Identifier::RepeaterCreate, "ɵɵrepeaterCreate"),
+            (Identifier::RepeaterTrackByIdentity, "ɵɵrepeaterTrackByIdentity"),
+            (Identifier::RepeaterTrackByIndex, "ɵɵrepeaterTrackByIndex"),
+            (Identifier::ResetView, "ɵɵresetView"),
+            (Identifier::ResolveBody, "ɵɵresolveBody"),
+            (Identifier::ResolveDocument, "ɵɵresolveDocument"),
+            (Identifier::ResolveWindow, "ɵɵresolveWindow"),
+            (Identifier::RestoreView, "ɵɵrestoreView"),
+            (Identifier::SetNgModuleScope, "ɵɵsetNgModuleScope"),
+            (Identifier::StandaloneFeature, "ɵɵStandaloneFeature"),
+            (Identifier::StyleMap, "ɵɵstyleMap"),
+            (Identifier::StyleProp, "ɵɵstyleProp"),
+            (Identifier::SyntheticHostListener, "ɵɵsyntheticHostListener"),
+            (Identifier::SyntheticHostProperty, "ɵɵsyntheticHostProperty"),
             (Identifier::Template, "ɵɵtemplate"),
             (Identifier::Text, "ɵɵtext"),
             (Identifier::TextInterpolate, "ɵɵtextInterpolate"),
@@ -53,6 +198,58 @@ lazy_static! {
             (Identifier::TextInterpolate7, "ɵɵtextInterpolate7"),
             (Identifier::TextInterpolate8, "ɵɵtextInterpolate8"),
             (Identifier::TextInterpolateV, "ɵɵtextInterpolateV"),
+            (Identifier::TwoWayBindingSet, "ɵɵtwoWayBindingSet"),
+            (Identifier::TwoWayListener, "ɵɵtwoWayListener"),
+            (Identifier::TwoWayProperty, "ɵɵtwoWayProperty"),
+            (Identifier::ViewQuery, "ɵɵviewQuery"),
+            (Identifier::ViewQuerySignal, "ɵɵviewQuerySignal"),
         ])
     };
 }
+
+/// Markers separating the sections of a static attribute array in `consts`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AttributeMarker {
+    Classes = 1,
+    Styles = 2,
+    Bindings = 3,
+}
+
+/// How a dependency is looked up, passed to `ɵɵinject` and `ɵɵdirectiveInject`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InjectFlags {
+    Default = 0,
+    Host = 1,
+    /// `@Self`.
+    ASelf = 2,
+    SkipSelf = 4,
+    Optional = 8,
+    /// Injecting into a pipe, which looks up the element the pipe is used on.
+    ForPipe = 16,
+}
+
+/// Flags of an entry in the `inputs` map of a directive definition.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InputFlags {
+    None = 0,
+    SignalBased = 1,
+    HasDecoratorInputTransform = 2,
+}
+
+/// Flags of a query, passed to the query instructions.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum QueryFlags {
+    None = 0,
+    Descendants = 1,
+    IsStatic = 2,
+    EmitDistinctChangesOnly = 4,
+}
+
+/// Flags in a parsed CSS selector, e.g. `[["", 8, "active"]]` for `.active`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SelectorFlags {
+    Not = 1,
+    Attribute = 2,
+    Element = 4,
+    Class = 8,
+}
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use oxc_allocator::Allocator;
+use oxc_codegen::CodeGenerator;
+use oxc_diagnostics::Error;
+use oxc_parser::Parser;
+use oxc_semantic::SemanticBuilder;
+use oxc_span::SourceType;
+
+use super::Angular;
+use crate::logging::{self, LogEvent, LogFilter};
+
+#[derive(Debug, Default, Clone)]
+pub struct TransformOptions {
+    /// Whether to generate a source map of the output.
+    pub source_map: bool,
+    /// Collects the log events the filter selects into the result, rather than leaving them to
+    /// the host's subscriber.
+    pub log: Option<LogFilter>,
+}
+
+pub struct TransformResult {
+    /// The module with its decorators compiled, empty when it could not be parsed.
+    pub code: String,
+    /// The source map of `code`, as JSON, if requested.
+    pub map: Option<String>,
+    pub diagnostics: Vec<Error>,
+    /// The log events collected, if requested.
+    pub events: Vec<LogEvent>,
+}
+
+/// Compiles the Angular decorators of a TypeScript module, e.g. for a bundler plugin.
+///
+/// Generated definitions map back to the decorator they replace, so stepping into `ɵfac` or
+/// `ɵcmp` in a debugger lands on `@Injectable` or `@Component`.
+pub fn transform(source: &str, filename: &str, options: &TransformOptions) -> TransformResult {
+    match &options.log {
+        Some(filter) => {
+            let (result, events) = logging::collect(filter, || compile(source, filename, options));
+            TransformResult { events, ..result }
+        }
+        None => compile(source, filename, options),
+    }
+}
+
+fn compile(source: &str, filename: &str, options: &TransformOptions) -> TransformResult {
+    let allocator = Allocator::default();
+    let source_type = SourceType::from_path(filename).unwrap_or_else(|_| SourceType::ts());
+    let source_code = Arc::new(source.to_string());
+
+    let ret = Parser::new(&allocator, source, source_type).parse();
+    if !ret.errors.is_empty() {
+        return TransformResult {
+            code: String::new(),
+            map: None,
+            diagnostics: ret
+                .errors
+                .into_iter()
+                .map(|error| error.with_source_code(Arc::clone(&source_code)))
+                .collect(),
+            events: Vec::new(),
+        };
+    }
+    let mut program = ret.program;
+
+    let semantic = SemanticBuilder::new(source).build(&program).semantic;
+    let (Ok(diagnostics) | Err(diagnostics)) =
+        Angular::new(&allocator, source_type, semantic).build(&mut program);
+
+    let mut codegen = CodeGenerator::new();
+    if options.source_map {
+        codegen = codegen.enable_source_map(filename, source);
+    }
+    let ret = codegen.build(&program);
+    TransformResult {
+        code: ret.source_text,
+        map: ret.source_map.map(|map| map.to_json_string()),
+        diagnostics,
+        events: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oxc_sourcemap::SourceMap;
+
+    use super::{transform, TransformOptions};
+
+    #[test]
+    fn definitions_map_back_to_their_decorator() {
+        let source = "import { Injectable } from '@angular/core';\n\
+                      \n\
+                      @Injectable()\n\
+                      export class Service {}\n";
+        let options = TransformOptions {
+            source_map: true,
+            ..TransformOptions::default()
+        };
+        let result = transform(source, "service.ts", &options);
+        assert!(result.diagnostics.is_empty());
+
+        let map = SourceMap::from_json_string(&result.map.expect("a source map was requested"))
+            .expect("the source map is valid");
+        let table = map.generate_lookup_table();
+        for definition in ["static ɵfac", "static ɵprov"] {
+            let (line, text) = result
+                .code
+                .lines()
+                .enumerate()
+                .find(|(_, text)| text.contains(definition))
+                .expect("the definition is generated");
+            let column = text.find(definition).unwrap() + definition.len();
+            let token = map
+                .lookup_token(&table, line as u32, column as u32)
+                .expect("the definition is mapped");
+            // `@Injectable()`, on the third line.
+            assert_eq!(token.get_src_line(), 2, "{}", definition);
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use crate::angular::diagnostics::{style_url_unsupported, template_url_unsupported};
+use crate::angular::runtime::Identifier;
+use crate::angular::{context::AngularContext, ComponentOptions, DirectiveMembers};
+use crate::angular::{ChangeDetectionStrategy, TopLevelDecorator, ViewEncapsulation};
+use crate::compiler::{compile_inline_template, minify_css, shim_css};
+
+use std::rc::Rc;
+
+use oxc_ast::{ast::*, AstBuilder};
+
+use crate::angular::context::AngularCtx;
+
+use super::definition::{
+    definition_property, directive_fields, statements, string_literal, take_members,
+};
+
+pub struct ComponentCreator<'a> {
+    ast: Rc<AstBuilder<'a>>,
+    context: AngularCtx<'a>,
+    /// Embedded view templates and other functions the definitions refer to, declared at the
+    /// top level of the module, with the span of the decorator they come from.
+    declarations: Vec<(String, Span)>,
+}
+
+impl<'a> ComponentCreator<'a> {
+    pub fn new(ast: Rc<AstBuilder<'a>>, context: AngularCtx<'a>) -> Option<Self> {
+        Some(Self {
+            ast,
+            context,
+            declarations: Vec::new(),
+        })
+    }
+
+    pub fn specifier_to_remove() -> Vec<&'a str> {
+        vec!["Component"]
+    }
+
+    pub fn transform_class(
+        &mut self,
+        class: &mut Class<'a>,
+        top_level_decorators: &[(TopLevelDecorator, usize)],
+    ) {
+        let class_name = if !top_level_decorators.is_empty() {
+            class
+                .id
+                .clone()
+                .map(|id| id.name.to_compact_str())
+                .or_else(|| Some(self.context.generate_uid("class")))
+        } else {
+            None
+        };
+        for (decorator, index) in top_level_decorators {
+            if let TopLevelDecorator::Component { options } = decorator {
+                let span = class.decorators[*index].span;
+                let members = take_members(&mut self.context, class);
+                if let Some(property_definition) = self.ng_component_builder(
+                    class,
+                    class_name.as_deref().unwrap_or_default(),
+                    options,
+                    &members,
+                    span,
+                ) {
+                    class.body.body.insert(1, property_definition);
+                }
+            }
+        }
+    }
+
+    /// The declarations collected from every component so far, parsed as statements.
+    pub fn take_declarations(&mut self) -> Vec<Statement<'a>> {
+        std::mem::take(&mut self.declarations)
+            .into_iter()
+            .flat_map(|(code, span)| statements(&self.ast, &code, span))
+            .collect()
+    }
+
+    /// `static ɵcmp = i0.ɵɵdefineComponent({ ... })`, with the template compiled from its
+    /// inline source.
+    fn ng_component_builder(
+        &mut self,
+        class: &Class<'a>,
+        class_name: &str,
+        options: &ComponentOptions,
+        members: &DirectiveMembers,
+        span: Span,
+    ) -> Option<ClassElement<'a>> {
+        let source_text = self.context.source_text();
+        let source = |span: Span| span.source_text(source_text).to_string();
+
+        if let Some(template_url) = &options.template_url {
+            self.context
+                .error(template_url_unsupported(template_url.span));
+        }
+        for style_url in &options.style_urls {
+            self.context.error(style_url_unsupported(style_url.span));
+        }
+
+        let (template, template_styles) = match &options.template {
+            Some(template) => {
+                compile_inline_template(class_name, &template.value, template.span.start)
+            }
+            None => compile_inline_template(class_name, "", 0),
+        };
+        for diagnostic in template.diagnostics {
+            self.context.error(diagnostic);
+        }
+
+        let mut fields = directive_fields(
+            &mut self.context,
+            class,
+            class_name,
+            &options.directive,
+            Some(options),
+            members,
+        );
+
+        fields.push(format!("decls: {}", template.decls));
+        fields.push(format!("vars: {}", template.vars));
+        if !template.consts.is_empty() {
+            fields.push(format!("consts: [{}]", template.consts.join(", ")));
+        }
+        fields.push(format!("template: {}", template.function));
+        if let Some(imports) = options.imports.filter(|_| options.directive.standalone) {
+            fields.push(format!("dependencies: {}", source(imports)));
+        }
+
+        let styles: Vec<_> = options
+            .styles
+            .iter()
+            .map(|style| minify_css(style))
+            .chain(template_styles)
+            .collect();
+        // Without styles there is nothing to scope.
+        let encapsulation = match options.encapsulation {
+            ViewEncapsulation::Emulated if styles.is_empty() => ViewEncapsulation::None,
+            encapsulation => encapsulation,
+        };
+        let styles: Vec<_> = styles
+            .iter()
+            .map(|style| match encapsulation {
+                ViewEncapsulation::Emulated => string_literal(&shim_css(style)),
+                _ => string_literal(style),
+            })
+            .collect();
+        if !styles.is_empty() {
+            fields.push(format!("styles: [{}]", styles.join(", ")));
+        }
+        if encapsulation != ViewEncapsulation::Emulated {
+            fields.push(format!("encapsulation: {}", encapsulation as u8));
+        }
+        if options.change_detection == ChangeDetectionStrategy::OnPush {
+            fields.push(format!(
+                "changeDetection: {}",
+                options.change_detection as u8
+            ));
+        }
+        if !template.declarations.is_empty() {
+            self.declarations
+                .push((template.declarations.join("\n\n"), span));
+        }
+
+        definition_property(
+            &self.ast,
+            &mut self.context,
+            "ɵcmp",
+            Identifier::DefineComponent,
+            &fields,
+            span,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::compile;
+
+    #[test]
+    fn inline_template() {
+        let (code, codes) = compile(
+            "import { Component } from '@angular/core';\n\
+             @Component({ selector: 'app-root', standalone: true, template: '<h1>{{ title }}</h1>' })\n\
+             export class AppComponent { title = 'a'; }\n",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(code.contains("static ɵcmp = i0.ɵɵdefineComponent({"));
+        assert!(code.contains("selectors: [[\"app-root\"]]"));
+        assert!(code.contains("features: [i0.ɵɵStandaloneFeature]"));
+        assert!(code.contains("decls: 2,"));
+        assert!(code.contains("i0.ɵɵtextInterpolate(ctx.title);"));
+        assert!(!code.contains("@Component"));
+    }
+
+    #[test]
+    fn styles_scoped_under_emulated_encapsulation() {
+        let (code, _) = compile(
+            "import { Component } from '@angular/core';\n\
+             @Component({ selector: 'a', template: '<h1></h1>', styles: [':host { display: block } h1 { color: red }'] })\n\
+             export class A {}\n",
+        );
+        assert!(code.contains(
+            "styles: [\"[_nghost-%COMP%]{display:block}h1[_ngcontent-%COMP%]{color:red}\"]"
+        ));
+        assert!(!code.contains("encapsulation"));
+    }
+
+    #[test]
+    fn styles_left_global_without_encapsulation() {
+        let (code, _) = compile(
+            "import { Component, ViewEncapsulation } from '@angular/core';\n\
+             @Component({ selector: 'a', template: '', styles: ['h1 { color: red }'], encapsulation: ViewEncapsulation.None })\n\
+             export class A {}\n",
+        );
+        assert!(code.contains("styles: [\"h1{color:red}\"]"));
+        assert!(code.contains("encapsulation: 2"));
+    }
+
+    #[test]
+    fn template_url_is_reported() {
+        let (_, codes) = compile(
+            "import { Component } from '@angular/core';\n\
+             @Component({ selector: 'a', templateUrl: './a.html' })\n\
+             export class A {}\n",
+        );
+        assert_eq!(codes, ["angular(template-url-unsupported)"]);
+    }
+
+    #[test]
+    fn alongside_injectable() {
+        let (code, codes) = compile(
+            "import { Injectable, Component } from '@angular/core';\n\
+             @Injectable() @Component({ selector: 'x', template: '' }) export class X {}\n",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(code.contains("static ɵcmp = i0.ɵɵdefineComponent({"));
+        assert!(code.contains("static ɵprov = i0.ɵɵdefineInjectable({"));
+        assert!(!code.contains("@Injectable") && !code.contains("@Component"));
+    }
+}
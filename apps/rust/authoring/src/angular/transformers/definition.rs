@@ -0,0 +1,493 @@
+//! Fields shared by the definitions of directives and components, which Angular emits in the same
+//! order for both: `ɵɵdefineComponent` continues where `ɵɵdefineDirective` stops.
+
+use std::rc::Rc;
+
+use oxc_ast::{ast::*, visit::walk_mut, AstBuilder, VisitMut};
+use oxc_parser::Parser;
+use oxc_semantic::ScopeFlags;
+use oxc_span::{GetSpanMut, SourceType, Span};
+
+use crate::angular::context::{AngularContext, AngularCtx};
+use crate::angular::runtime::{Identifier, InjectFlags, InputFlags, QueryFlags};
+use crate::angular::{
+    member_decorator, ComponentOptions, DecoratorQuery, Dependency, DirectiveMembers,
+    DirectiveOptions, QueryPredicate,
+};
+use crate::compiler::{compile_host, parse_selector};
+
+/// A reference to a runtime symbol, e.g. `i0.ɵɵdefineDirective`.
+pub(super) fn core(identifier: Identifier) -> String {
+    format!("i0.{}", identifier.name())
+}
+
+/// `type` to `outputs`, from the options and the decorated members, then `exportAs`, `standalone`
+/// and `features`. `component` holds the options of `@Component`, for a component definition.
+pub(super) fn directive_fields(
+    context: &mut AngularCtx,
+    class: &Class,
+    class_name: &str,
+    options: &DirectiveOptions,
+    component: Option<&ComponentOptions>,
+    members: &DirectiveMembers,
+) -> Vec<String> {
+    let source_text = context.source_text();
+    let source = |span: Span| span.source_text(source_text).to_string();
+
+    let mut fields = vec![format!("type: {}", class_name)];
+    // Components can be created without matching anything, e.g. by the router.
+    let selector = component.map(|_| "ng-component");
+    if let Some(selector) = options.selector.as_deref().or(selector) {
+        fields.push(format!("selectors: {}", parse_selector(selector)));
+    }
+    if let Some(function) = query_function(source_text, class_name, &members.queries, false) {
+        fields.push(format!("contentQueries: {}", function));
+    }
+    if let Some(function) = query_function(source_text, class_name, &members.queries, true) {
+        fields.push(format!("viewQuery: {}", function));
+    }
+
+    let host: Vec<_> = options.host.iter().chain(&members.host).cloned().collect();
+    let host = compile_host(class_name, &host);
+    for diagnostic in host.diagnostics {
+        context.error(diagnostic);
+    }
+    if !host.attributes.is_empty() {
+        fields.push(format!("hostAttrs: [{}]", host.attributes.join(", ")));
+    }
+    if host.vars > 0 {
+        fields.push(format!("hostVars: {}", host.vars));
+    }
+    if let Some(function) = host.function {
+        fields.push(format!("hostBindings: {}", function));
+    }
+
+    let inputs: Vec<_> = options.inputs.iter().chain(&members.inputs).collect();
+    if !inputs.is_empty() {
+        let inputs: Vec<_> = inputs
+            .into_iter()
+            .map(|input| {
+                // A plain string unless the input is aliased or transformed.
+                let value = if input.public_name == input.name && input.transform.is_none() {
+                    string_literal(&input.public_name)
+                } else {
+                    let mut entry = vec![
+                        (match input.transform {
+                            Some(_) => InputFlags::HasDecoratorInputTransform,
+                            None => InputFlags::None,
+                        } as u8)
+                            .to_string(),
+                        string_literal(&input.public_name),
+                        string_literal(&input.name),
+                    ];
+                    entry.extend(input.transform.map(source));
+                    format!("[{}]", entry.join(", "))
+                };
+                format!("{}: {}", property_key(&input.name), value)
+            })
+            .collect();
+        fields.push(format!("inputs: {{ {} }}", inputs.join(", ")));
+    }
+    let outputs: Vec<_> = options.outputs.iter().chain(&members.outputs).collect();
+    if !outputs.is_empty() {
+        let outputs: Vec<_> = outputs
+            .into_iter()
+            .map(|output| {
+                format!(
+                    "{}: {}",
+                    property_key(&output.name),
+                    string_literal(&output.public_name)
+                )
+            })
+            .collect();
+        fields.push(format!("outputs: {{ {} }}", outputs.join(", ")));
+    }
+    if !options.export_as.is_empty() {
+        let names: Vec<_> = options
+            .export_as
+            .iter()
+            .map(|name| string_literal(name))
+            .collect();
+        fields.push(format!("exportAs: [{}]", names.join(", ")));
+    }
+    if options.standalone {
+        fields.push("standalone: true".to_string());
+    }
+    let features = features(context, class, options, component, members);
+    if !features.is_empty() {
+        fields.push(format!("features: [{}]", features.join(", ")));
+    }
+    fields
+}
+
+/// The decorated members of `class`, with the decorators removed as they are compiled into the
+/// definition.
+pub(super) fn take_members(context: &mut AngularCtx, class: &mut Class) -> DirectiveMembers {
+    let mut diagnostics = Vec::new();
+    let members = DirectiveMembers::parse(class, &mut diagnostics);
+    for diagnostic in diagnostics {
+        context.error(diagnostic);
+    }
+    for element in class.body.body.iter_mut() {
+        let decorators = match element {
+            ClassElement::PropertyDefinition(property) => &mut property.decorators,
+            ClassElement::AccessorProperty(accessor) => &mut accessor.decorators,
+            ClassElement::MethodDefinition(method) => &mut method.decorators,
+            _ => continue,
+        };
+        decorators.retain(|decorator| member_decorator(decorator).is_none());
+    }
+    members
+}
+
+/// `contentQueries` or `viewQuery` for the decorator queries of the view, creating each query in
+/// creation mode and assigning its results to the property in update mode.
+fn query_function(
+    source_text: &str,
+    class_name: &str,
+    queries: &[DecoratorQuery],
+    view: bool,
+) -> Option<String> {
+    let source = |span: Span| span.source_text(source_text).to_string();
+    let queries: Vec<_> = queries.iter().filter(|query| query.view == view).collect();
+    if queries.is_empty() {
+        return None;
+    }
+
+    let mut creation = Vec::new();
+    let mut update = Vec::new();
+    for query in queries {
+        let predicate = match &query.predicate {
+            QueryPredicate::References(references) => {
+                let references: Vec<_> = references
+                    .iter()
+                    .map(|reference| string_literal(reference))
+                    .collect();
+                format!("[{}]", references.join(", "))
+            }
+            QueryPredicate::Type(span) => source(*span),
+        };
+        let mut args = vec![predicate, query_flags(query).to_string()];
+        args.extend(query.read.map(source));
+        let identifier = if view {
+            Identifier::ViewQuery
+        } else {
+            args.insert(0, "dirIndex".to_string());
+            Identifier::ContentQuery
+        };
+        creation.push(format!("{}({});", core(identifier), args.join(", ")));
+        update.push(format!(
+            "{}(_t = {}()) && (ctx{} = {});",
+            core(Identifier::QueryRefresh),
+            core(Identifier::LoadQuery),
+            member_access(&query.property),
+            if query.first { "_t.first" } else { "_t" }
+        ));
+    }
+
+    let (function, parameters) = if view {
+        (format!("{}_Query", class_name), "rf, ctx")
+    } else {
+        (
+            format!("{}_ContentQueries", class_name),
+            "rf, ctx, dirIndex",
+        )
+    };
+    Some(format!(
+        "function {}({}) {{\n    if (rf & 1) {{\n      {}\n    }}\n    if (rf & 2) {{\n      let _t;\n      {}\n    }}\n  }}",
+        function,
+        parameters,
+        creation.join("\n      "),
+        update.join("\n      ")
+    ))
+}
+
+fn query_flags(query: &DecoratorQuery) -> u8 {
+    let mut flags = QueryFlags::None as u8;
+    if query.descendants {
+        flags |= QueryFlags::Descendants as u8;
+    }
+    if query.is_static {
+        flags |= QueryFlags::IsStatic as u8;
+    }
+    if query.emit_distinct_changes_only {
+        flags |= QueryFlags::EmitDistinctChangesOnly as u8;
+    }
+    flags
+}
+
+/// The features of a definition, found from its options and its class, in the order Angular
+/// applies them: `ɵɵProvidersFeature`, `ɵɵHostDirectivesFeature`, `ɵɵInheritDefinitionFeature`,
+/// `ɵɵInputTransformsFeature`, `ɵɵCopyDefinitionFeature`, `ɵɵNgOnChangesFeature` and, for
+/// standalone components, `ɵɵStandaloneFeature`.
+fn features(
+    context: &AngularCtx,
+    class: &Class,
+    options: &DirectiveOptions,
+    component: Option<&ComponentOptions>,
+    members: &DirectiveMembers,
+) -> Vec<String> {
+    let source_text = context.source_text();
+    let source = |span: Span| span.source_text(source_text).to_string();
+
+    let view_providers = component.and_then(|component| component.view_providers);
+    let mut features = Vec::new();
+    if options.providers.is_some() || view_providers.is_some() {
+        let mut args = vec![options.providers.map_or("[]".to_string(), source)];
+        args.extend(view_providers.map(source));
+        features.push(format!(
+            "{}({})",
+            core(Identifier::ProvidersFeature),
+            args.join(", ")
+        ));
+    }
+    if !options.host_directives.is_empty() {
+        let mapping = |pairs: &[(String, String)]| {
+            let names: Vec<_> = pairs
+                .iter()
+                .flat_map(|(name, public_name)| [string_literal(name), string_literal(public_name)])
+                .collect();
+            format!("[{}]", names.join(", "))
+        };
+        let host_directives: Vec<_> = options
+            .host_directives
+            .iter()
+            .map(|host_directive| {
+                let directive = source(host_directive.directive);
+                if host_directive.inputs.is_empty() && host_directive.outputs.is_empty() {
+                    return directive;
+                }
+                let mut fields = vec![format!("directive: {}", directive)];
+                if !host_directive.inputs.is_empty() {
+                    fields.push(format!("inputs: {}", mapping(&host_directive.inputs)));
+                }
+                if !host_directive.outputs.is_empty() {
+                    fields.push(format!("outputs: {}", mapping(&host_directive.outputs)));
+                }
+                format!("{{ {} }}", fields.join(", "))
+            })
+            .collect();
+        features.push(format!(
+            "{}([{}])",
+            core(Identifier::HostDirectivesFeature),
+            host_directives.join(", ")
+        ));
+    }
+    if class.super_class.is_some() {
+        features.push(core(Identifier::InheritDefinitionFeature));
+    }
+    // Applies the `transform` of inputs as they are set.
+    if options
+        .inputs
+        .iter()
+        .chain(&members.inputs)
+        .any(|input| input.transform.is_some())
+    {
+        features.push(core(Identifier::InputTransformsFeature));
+    }
+    // A component without a template of its own takes on the view of the one it extends.
+    if class.super_class.is_some()
+        && component.is_some_and(|component| {
+            component.template.is_none() && component.template_url.is_none()
+        })
+    {
+        features.push(core(Identifier::CopyDefinitionFeature));
+    }
+    if uses_on_changes(class) {
+        features.push(core(Identifier::NgOnChangesFeature));
+    }
+    if component.is_some() && options.standalone {
+        features.push(core(Identifier::StandaloneFeature));
+    }
+    features
+}
+
+/// Whether instances have an `ngOnChanges` hook, which only gets called once
+/// `ɵɵNgOnChangesFeature` tracks the changes to inputs.
+fn uses_on_changes(class: &Class) -> bool {
+    class.body.body.iter().any(|element| {
+        !element.r#static()
+            && element
+                .property_key()
+                .and_then(PropertyKey::static_name)
+                .is_some_and(|name| name == "ngOnChanges")
+    })
+}
+
+/// `static name = i0.ɵɵdefineX({ fields })`, parsed from the generated code.
+pub(super) fn definition_property<'a>(
+    ast: &Rc<AstBuilder<'a>>,
+    context: &mut AngularCtx<'a>,
+    name: &str,
+    define: Identifier,
+    fields: &[String],
+    span: Span,
+) -> Option<ClassElement<'a>> {
+    let code = format!("{}({{\n  {}\n}})", core(define), fields.join(",\n  "));
+    static_property(ast, context, name, &code, span)
+}
+
+/// `static name = code`, reporting the code when it does not parse. The property maps to `span`,
+/// that of the decorator it comes from.
+pub(super) fn static_property<'a>(
+    ast: &Rc<AstBuilder<'a>>,
+    context: &mut AngularCtx<'a>,
+    name: &str,
+    code: &str,
+    span: Span,
+) -> Option<ClassElement<'a>> {
+    let source_type = SourceType::default().with_typescript(true);
+    let mut value = match Parser::new(ast.allocator, ast.str(code), source_type).parse_expression()
+    {
+        Ok(expression) => expression,
+        Err(errors) => {
+            for error in errors {
+                context.error(error.with_source_code(code.to_string()));
+            }
+            return None;
+        }
+    };
+    Respan(span).visit_expression(&mut value);
+
+    Some(ast.class_element_property_definition(
+        PropertyDefinitionType::PropertyDefinition,
+        span,
+        ast.vec(),
+        ast.property_key_identifier_name(Span::empty(span.start), ast.atom(name)),
+        Some(value),
+        false,
+        true,
+        false,
+        false,
+        false,
+        false,
+        false,
+        None::<TSTypeAnnotation>,
+        None,
+    ))
+}
+
+/// A dependency read by `inject`, either `ɵɵinject` or `ɵɵdirectiveInject`, with its flags when it
+/// has any; attributes are read with `ɵɵinjectAttribute`.
+pub(super) fn inject_dependency(
+    inject: Identifier,
+    dependency: &Dependency,
+    source_text: &str,
+) -> String {
+    let token = dependency.token.source_text(source_text);
+    if dependency.attribute {
+        format!("{}({})", core(Identifier::InjectAttribute), token)
+    } else if dependency.flags == InjectFlags::Default as u8 {
+        format!("{}({})", core(inject), token)
+    } else {
+        format!("{}({}, {})", core(inject), token, dependency.flags)
+    }
+}
+
+/// Top-level statements parsed from the generated code, mapping to `span`.
+pub(super) fn statements<'a>(ast: &AstBuilder<'a>, code: &str, span: Span) -> Vec<Statement<'a>> {
+    let ret = Parser::new(ast.allocator, ast.str(code), SourceType::mjs()).parse();
+    let mut statements: Vec<_> = ret.program.body.into_iter().collect();
+    for statement in &mut statements {
+        Respan(span).visit_statement(statement);
+    }
+    statements
+}
+
+/// Points every node of generated code at `span`, so source maps lead from the output back to the
+/// decorator it was generated for rather than to offsets in the generated code. Identifiers get an
+/// empty span at its start, as their names are not those of the source.
+struct Respan(Span);
+
+impl<'a> VisitMut<'a> for Respan {
+    fn visit_statement(&mut self, it: &mut Statement<'a>) {
+        *it.span_mut() = self.0;
+        walk_mut::walk_statement(self, it);
+    }
+
+    fn visit_expression(&mut self, it: &mut Expression<'a>) {
+        *it.span_mut() = self.0;
+        walk_mut::walk_expression(self, it);
+    }
+
+    fn visit_function(&mut self, it: &mut Function<'a>, flags: ScopeFlags) {
+        it.span = self.0;
+        walk_mut::walk_function(self, it, flags);
+    }
+
+    fn visit_function_body(&mut self, it: &mut FunctionBody<'a>) {
+        it.span = self.0;
+        walk_mut::walk_function_body(self, it);
+    }
+
+    fn visit_formal_parameters(&mut self, it: &mut FormalParameters<'a>) {
+        it.span = self.0;
+        walk_mut::walk_formal_parameters(self, it);
+    }
+
+    fn visit_class(&mut self, it: &mut Class<'a>) {
+        it.span = self.0;
+        walk_mut::walk_class(self, it);
+    }
+
+    fn visit_class_element(&mut self, it: &mut ClassElement<'a>) {
+        *it.span_mut() = self.0;
+        walk_mut::walk_class_element(self, it);
+    }
+
+    fn visit_object_property(&mut self, it: &mut ObjectProperty<'a>) {
+        it.span = self.0;
+        walk_mut::walk_object_property(self, it);
+    }
+
+    fn visit_spread_element(&mut self, it: &mut SpreadElement<'a>) {
+        it.span = self.0;
+        walk_mut::walk_spread_element(self, it);
+    }
+
+    fn visit_binding_pattern(&mut self, it: &mut BindingPattern<'a>) {
+        *it.kind.span_mut() = self.0;
+        walk_mut::walk_binding_pattern(self, it);
+    }
+
+    fn visit_identifier_reference(&mut self, it: &mut IdentifierReference<'a>) {
+        it.span = Span::empty(self.0.start);
+    }
+
+    fn visit_binding_identifier(&mut self, it: &mut BindingIdentifier<'a>) {
+        it.span = Span::empty(self.0.start);
+    }
+
+    fn visit_identifier_name(&mut self, it: &mut IdentifierName<'a>) {
+        it.span = Span::empty(self.0.start);
+    }
+}
+
+pub(super) fn string_literal(value: &str) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+/// `name`, quoted when it is not an identifier.
+fn property_key(name: &str) -> String {
+    if is_identifier(name) {
+        name.to_string()
+    } else {
+        string_literal(name)
+    }
+}
+
+/// `.name`, or `["name"]` when it is not an identifier.
+fn member_access(name: &str) -> String {
+    if is_identifier(name) {
+        format!(".{}", name)
+    } else {
+        format!("[{}]", string_literal(name))
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().enumerate().all(|(i, c)| {
+            c == '_' || c == '$' || c.is_ascii_alphabetic() || i > 0 && c.is_ascii_digit()
+        })
+}
@@ -1,48 +1,67 @@
 use crate::angular::context::AngularContext;
+use crate::angular::runtime::{Identifier, InjectFlags};
 use crate::angular::TopLevelDecorator;
-use oxc::syntax::operator::LogicalOperator;
-use oxc_span::SPAN;
+use oxc_span::{GetSpan, Span, SPAN};
+use std::collections::HashMap;
 use std::rc::Rc;
 
-use oxc_ast::{ast::*, AstBuilder, AstKind};
+use oxc::syntax::number::NumberBase;
+use oxc_ast::{ast::*, visit::walk_mut, AstBuilder, VisitMut};
+use tracing::{debug, trace};
 
-use crate::angular::{context::AngularCtx, InjectableOptions, ParamDecorator};
+use crate::angular::diagnostics::missing_injection_token;
+use crate::angular::inject::{inject_calls, InjectCall, InjectionContext};
+use crate::angular::{context::AngularCtx, Dependency, ParamDecorator};
+
+use super::definition::{core, inject_dependency, static_property};
 
 pub struct DependencyInjection<'a> {
     ast: Rc<AstBuilder<'a>>,
-    nodes: oxc_allocator::Vec<'a, AstKind<'a>>,
     context: AngularCtx<'a>,
-    constructor_params: Vec<ParamDecorator>,
 }
 
 impl<'a> DependencyInjection<'a> {
-    pub fn new(ast: Rc<AstBuilder<'a>>, context: AngularCtx<'a>) -> Option<Self> {
-        let nodes: oxc_allocator::Vec<'_, AstKind<'_>> = ast.new_vec();
-        Some(Self {
-            ast,
-            nodes,
-            context,
-            constructor_params: Vec::new(),
-        })
+    pub fn new(ast: Rc<AstBuilder<'a>>, context: AngularCtx<'a>) -> Self {
+        Self { ast, context }
     }
 
     pub fn specifier_to_remove() -> Vec<&'a str> {
         vec!["inject"]
     }
 
-    pub fn transform_class(&mut self, class: &mut Class<'a>, top_level_decorators: &Vec<(TopLevelDecorator, usize)>) {
-        let has_decorator = !class.decorators.is_empty();
-        let class_name = if has_decorator {
-            class
-                .id
-                .clone()
-                .map(|id| self.context.scopes().generate_uid(&id.name))
-                .or_else(|| Some(self.context.scopes().generate_uid("class")))
-        } else {
-            None
+    /// Rewrites the calls to the `inject` imported from `@angular/core`, whose import is removed,
+    /// into the instruction of where they run, reporting those outside of an injection context.
+    pub fn transform_program(&mut self, program: &mut Program<'a>) {
+        let (calls, diagnostics) =
+            inject_calls(&self.context.symbols(), program, InjectionContext::Module);
+        for diagnostic in diagnostics {
+            self.context.error(diagnostic);
+        }
+        RewriteInjectCalls {
+            ast: &self.ast,
+            calls: calls.into_iter().map(|call| (call.span, call)).collect(),
+        }
+        .visit_program(program);
+    }
+
+    pub fn transform_class(&mut self, class: &mut Class<'a>, top_level_decorators: &[(TopLevelDecorator, usize)]) {
+        // Only classes with an Angular decorator get a factory; others are plain TypeScript. The
+        // factory serves the definition, so `@Injectable` next to `@Component` or `@Pipe` defers
+        // to it.
+        let Some((decorator, index)) = top_level_decorators
+            .iter()
+            .find(|(decorator, _)| !matches!(decorator, TopLevelDecorator::Injectable { .. }))
+            .or_else(|| top_level_decorators.first())
+        else {
+            return;
         };
-        println!("{:#?}", class_name);
+        let factory_name = self.context.generate_uid(&format!(
+            "factory{}",
+            class.id.as_ref().map_or("", |id| id.name.as_str())
+        ));
 
+        let mut dependencies = Vec::new();
+        let mut has_constructor = false;
         if let Some(constructor) = class.body.body.iter_mut().find_map(|element| {
             if let ClassElement::MethodDefinition(method_def) = element {
                 if method_def.kind == MethodDefinitionKind::Constructor {
@@ -51,273 +70,288 @@ impl<'a> DependencyInjection<'a> {
             }
             None
         }) {
-            // Now constructor is a mutable reference to the MethodDefinition of the constructor.
-            let constructor_has_params = !constructor.value.params.is_empty();
-
-            if constructor_has_params {
-                // Use the std::mem::replace strategy if direct mutation isn't possible due to the Vec's traits.
-                let mut temp_params =
-                    std::mem::replace(&mut constructor.value.params.items, self.ast.new_vec());
-
-                for param in temp_params.iter_mut() {
-                    let mut decorators_to_remove = Vec::new();
-                    let mut found_param_decorator = false;
-                    for (index, decorator) in param.decorators.iter().enumerate() {
-                        let identifier_name =
-                            if let Expression::CallExpression(boxed_expr) = &decorator.expression {
-                                if let Expression::Identifier(identifier) = &boxed_expr.callee {
-                                    Some(identifier.name.as_str())
-                                } else {
-                                    None
-                                }
+            has_constructor = true;
+            // One dependency per parameter, in order: the token from `@Inject` or the type, with
+            // the flags of the other decorators.
+            for param in constructor.value.params.items.iter_mut() {
+                let mut dependency = Dependency {
+                    token: SPAN,
+                    flags: InjectFlags::Default as u8,
+                    attribute: false,
+                };
+                let mut token = Self::type_span(&param.pattern.type_annotation);
+                let mut decorators_to_remove = Vec::new();
+                let mut diagnostics = Vec::new();
+                for (index, decorator) in param.decorators.iter().enumerate() {
+                    let identifier_name =
+                        if let Expression::CallExpression(boxed_expr) = &decorator.expression {
+                            if let Expression::Identifier(identifier) = &boxed_expr.callee {
+                                Some(identifier.name.as_str())
                             } else {
                                 None
-                            };
-
-                        if let Some(name) = identifier_name {
-                            if let Some(param_decorator) =
-                                ParamDecorator::from_str(name, param, decorator)
-                            {
-                                match &param_decorator {
-                                    ParamDecorator::Inject(ref _name)
-                                    | ParamDecorator::ASelf(ref _name)
-                                    | ParamDecorator::Optional(ref _name)
-                                    | ParamDecorator::SkipSelf(ref _name) => {
-                                        found_param_decorator = true;
-                                        self.constructor_params.push(param_decorator);
-                                        decorators_to_remove.push(index);
-                                    }
-                                    _ => {}
-                                }
                             }
+                        } else {
+                            None
+                        };
+                    let Some(name) = identifier_name.filter(|name| ParamDecorator::NAMES.contains(name)) else {
+                        continue;
+                    };
+                    // Stripped even when invalid, as the factory replaces it.
+                    decorators_to_remove.push(index);
+                    let Some(param_decorator) = ParamDecorator::from_str(name, decorator, &mut diagnostics) else {
+                        continue;
+                    };
+                    match param_decorator {
+                        ParamDecorator::Optional => dependency.flags |= InjectFlags::Optional as u8,
+                        ParamDecorator::ASelf => dependency.flags |= InjectFlags::ASelf as u8,
+                        ParamDecorator::SkipSelf => dependency.flags |= InjectFlags::SkipSelf as u8,
+                        ParamDecorator::Host => dependency.flags |= InjectFlags::Host as u8,
+                        ParamDecorator::Inject(span) => token = Some(span),
+                        ParamDecorator::Attribute(span) => {
+                            token = Some(span);
+                            dependency.attribute = true;
                         }
                     }
-                    for index in decorators_to_remove.into_iter().rev() {
-                        param.decorators.remove(index);
-                    }
+                }
+                for index in decorators_to_remove.into_iter().rev() {
+                    param.decorators.remove(index);
+                }
+                // An invalid decorator is the one diagnostic of its parameter.
+                let reported = !diagnostics.is_empty();
+                for diagnostic in diagnostics {
+                    self.context.error(diagnostic);
+                }
 
-                    if !found_param_decorator {
-                        if let Some(type_name) = self.extract_type_name_from_type_annotation(
-                            &param.pattern.type_annotation.as_ref(),
-                        ) {
-                            self.constructor_params
-                                .push(ParamDecorator::Inject(type_name));
+                match token {
+                    Some(token) => {
+                        dependency.token = token;
+                        dependencies.push(Some(dependency));
+                    }
+                    None => {
+                        if !reported {
+                            self.context.error(missing_injection_token(param.span));
                         }
+                        dependencies.push(None);
                     }
                 }
-                constructor.value.params.items = temp_params;
             }
         }
 
-        let factory_name = format!("factory{}", class_name.unwrap_or_default());
-        // Iterate over top_level_decorators and remove processed decorators
-        for (decorator, _) in top_level_decorators.into_iter() {
-            match decorator {
-                TopLevelDecorator::Injectable { options } => {
-                    println!("Processing Injectable decorator with options: {:?}", options);
-                    let property_definition = self.ng_factory_builder(factory_name.clone(), Some(&options));
-                    class.body.body.insert(0, property_definition);
-                }
-                _ => {
-                    let property_definition = self.ng_factory_builder(factory_name.clone(), None);
-                    class.body.body.insert(0, property_definition);
-                }
-            }
+        // The class the factory constructs, unless a subclass is passed in as `t`.
+        let type_name = class
+            .id
+            .as_ref()
+            .map_or_else(|| factory_name.to_string(), |id| id.name.to_string());
+        if let TopLevelDecorator::Injectable { options } = decorator {
+            trace!(class = %type_name, ?options, "injectable options");
         }
-    }
-
-    fn extract_type_name_from_type_annotation(
-        &self,
-        type_annotation: &Option<&oxc_allocator::Box<oxc_ast::ast::TSTypeAnnotation>>,
-    ) -> Option<String> {
-        type_annotation.as_ref().and_then(|ta| {
-            match &ta.type_annotation {
-                TSType::TSTypeReference(tstype_ref) => {
-                    // Directly handle the type reference to extract the type name
-                    self.extract_type_name_from_tstype_name(&tstype_ref.type_name)
-                }
-                // Potentially handle other TSType variants if needed
-                _ => None,
+        // Directives and pipes resolve their dependencies from the element they are on.
+        let (inject, flags) = match decorator {
+            TopLevelDecorator::Component { .. } | TopLevelDecorator::Directive { .. } => {
+                (Identifier::DirectiveInject, InjectFlags::Default as u8)
             }
-        })
-    }
-
-    fn extract_type_name_from_tstype_name(&self, tstype_name: &TSTypeName) -> Option<String> {
-        match tstype_name {
-            TSTypeName::IdentifierReference(identifier_ref) => {
-                // Direct extraction of the name from IdentifierReference
-                Some(identifier_ref.name.to_string())
+            TopLevelDecorator::Pipe { .. } => {
+                (Identifier::DirectiveInject, InjectFlags::ForPipe as u8)
             }
-            TSTypeName::QualifiedName(qualified_name) => {
-                // For QualifiedName, handle the left and right parts
-                let left_name = self.extract_type_name_from_tstype_name(&qualified_name.left)?;
-                Some(format!("{}.{}", left_name, qualified_name.right.name))
+            TopLevelDecorator::NgModule { .. } | TopLevelDecorator::Injectable { .. } => {
+                (Identifier::Inject, InjectFlags::Default as u8)
             }
+        };
+        let source_text = self.context.source_text();
+        let args: Vec<_> = dependencies
+            .iter()
+            .enumerate()
+            .map(|(index, dependency)| match dependency {
+                Some(dependency) => {
+                    let dependency = Dependency {
+                        flags: dependency.flags | flags,
+                        ..dependency.clone()
+                    };
+                    inject_dependency(inject, &dependency, source_text)
+                }
+                None => format!("{}({})", core(Identifier::InvalidFactoryDep), index),
+            })
+            .collect();
+        let code = if class.super_class.is_some() && !has_constructor {
+            // Constructed with the dependencies of the class it extends, found at runtime.
+            let base_factory = format!("ɵ{}_BaseFactory", type_name);
+            format!(
+                "(() => {{ let {base}; return function {}(t) {{ return ({base} || ({base} = {}({})))(t || {}); }}; }})()",
+                factory_name,
+                core(Identifier::GetInheritedFactory),
+                type_name,
+                type_name,
+                base = base_factory
+            )
+        } else {
+            format!(
+                "function {}(t) {{ return new (t || {})({}); }}",
+                factory_name,
+                type_name,
+                args.join(", ")
+            )
+        };
+        // The decorators are still on the class, removed once every definition is built.
+        let span = class.decorators[*index].span;
+        debug!(class = %type_name, dependencies = args.len(), "generated ɵfac");
+        if let Some(property_definition) =
+            static_property(&self.ast, &mut self.context, "ɵfac", &code, span)
+        {
+            class.body.body.insert(0, property_definition);
         }
     }
 
-    fn ng_factory_builder(
-        &self,
-        factory_name: String,
-        injectable_options: Option<&InjectableOptions>,
-    ) -> ClassElement<'a> {
-        let identifier_name = self.ast.new_atom("ɵfac");
-        let identifier_span = SPAN;
-        let identifier_key = self.ast.property_key_identifier(IdentifierName {
-            span: identifier_span,
-            name: identifier_name,
-        });
-
-        let function_identifier_name = self.ast.new_atom(&factory_name);
-        let function_identifier_ref =
-            BindingIdentifier::new(identifier_span, function_identifier_name.clone());
-        let function_identifier =
-            self.ast
-                .identifier_reference_expression(IdentifierReference::new(
-                    identifier_span,
-                    function_identifier_name,
-                ));
+    /// The span of the class a parameter is typed with, e.g. `Router` or `ng.Router`.
+    fn type_span(type_annotation: &Option<oxc_allocator::Box<TSTypeAnnotation>>) -> Option<Span> {
+        match &type_annotation.as_ref()?.type_annotation {
+            TSType::TSTypeReference(tstype_ref) => Some(tstype_ref.type_name.span()),
+            _ => None,
+        }
+    }
+}
 
-        let param_identifier_name = self.ast.new_atom("t");
-        let param_identifier_expression =
-            self.ast
-                .identifier_reference_expression(IdentifierReference::new(
-                    identifier_span,
-                    param_identifier_name.clone(),
-                ));
+/// Rewrites the `inject()` calls found by `inject_calls`, e.g. `inject(Token, { optional: true })`
+/// -> `i0.ɵɵinject(Token, 8)`.
+struct RewriteInjectCalls<'c, 'a> {
+    ast: &'c AstBuilder<'a>,
+    calls: HashMap<Span, InjectCall>,
+}
 
-        let param_identifier = self.ast.alloc(BindingIdentifier::new(
-            identifier_span,
-            param_identifier_name,
+impl<'c, 'a> VisitMut<'a> for RewriteInjectCalls<'c, 'a> {
+    fn visit_call_expression(&mut self, call: &mut CallExpression<'a>) {
+        walk_mut::walk_call_expression(self, call);
+        let Some(inject) = self.calls.remove(&call.span) else {
+            return;
+        };
+        let ast = self.ast;
+        call.callee = ast.expression_member(ast.member_expression_static(
+            inject.callee,
+            ast.expression_identifier_reference(Span::empty(inject.callee.start), "i0"),
+            ast.identifier_name(Span::empty(inject.callee.start), inject.inject.name()),
+            false,
         ));
+        call.arguments.truncate(1);
+        if inject.flags != InjectFlags::Default as u8 {
+            let raw = ast.str(&inject.flags.to_string());
+            call.arguments
+                .push(Argument::from(ast.expression_numeric_literal(
+                    inject.options.unwrap_or(inject.callee),
+                    f64::from(inject.flags),
+                    raw,
+                    NumberBase::Decimal,
+                )));
+        }
+    }
+}
 
-        let param_binding_pattern =
-            BindingPattern::new_with_kind(BindingPatternKind::BindingIdentifier(param_identifier));
-
-        let formal_parameter = self.ast.formal_parameter(
-            identifier_span,
-            param_binding_pattern,
-            None,
-            false,
-            false,
-            self.ast.new_vec(),
+#[cfg(test)]
+mod tests {
+    use super::super::compile;
+
+    #[test]
+    fn constructor_parameters_with_flags() {
+        let (code, codes) = compile(
+            "import { Injectable, Inject, Optional, Self, Attribute } from '@angular/core';\n\
+             import { Http, TOKEN } from './deps';\n\
+             @Injectable()\n\
+             export class Service {\n\
+               constructor(private http: Http, @Optional() @Self() @Inject(TOKEN) token: string, \
+                           @Attribute('title') title: string) {}\n\
+             }\n",
         );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(code.contains(
+            "return new (t || Service)(i0.ɵɵinject(Http), i0.ɵɵinject(TOKEN, 10), i0.ɵɵinjectAttribute(\"title\"));"
+        ));
+        assert!(code.contains("constructor(private http: Http, token: string, title: string) {}"));
+    }
 
-        let params: oxc_allocator::Box<'_, FormalParameters<'_>> = self.ast.formal_parameters(
-            identifier_span,
-            FormalParameterKind::FormalParameter,
-            self.ast.new_vec_single(formal_parameter),
-            None,
+    #[test]
+    fn parameter_without_token() {
+        let (code, codes) = compile(
+            "import { Injectable } from '@angular/core';\n\
+             @Injectable()\n\
+             export class Service { constructor(value) {} }\n",
         );
+        assert_eq!(codes, ["angular(missing-injection-token)"]);
+        assert!(code.contains("i0.ɵɵinvalidFactoryDep(0)"));
+    }
 
-        // Collecting the injection tokens
-        let inject_tokens: Vec<String> = self
-            .constructor_params
-            .iter()
-            .filter_map(|param| {
-                if let ParamDecorator::Inject(token) = param {
-                    Some(token.clone())
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        // Prepare a vector to hold all call expressions as arguments for the new expression
-        let mut new_expression_arguments = self
-            .ast
-            .new_vec_with_capacity::<Argument<'a>>(inject_tokens.len());
-
-        for token in inject_tokens {
-            let token_identifier_name = self.ast.new_atom(&token);
-            let token_identifier =
-                self.ast
-                    .identifier_reference_expression(IdentifierReference::new(
-                        identifier_span,
-                        token_identifier_name,
-                    ));
-
-            let i0_identifier_name = self.ast.new_atom("i0");
-            let i0_identifier = self
-                .ast
-                .identifier_reference_expression(IdentifierReference::new(
-                    identifier_span,
-                    i0_identifier_name,
-                ));
-
-            let inject_identifier_name = self.ast.new_atom("ɵɵinject");
-
-            // Create the call expression for each token
-            let inject_call_expression = self.ast.call_expression(
-                identifier_span,
-                self.ast.static_member_expression(
-                    identifier_span,
-                    i0_identifier,
-                    IdentifierName {
-                        span: identifier_span,
-                        name: inject_identifier_name.clone(),
-                    },
-                    false,
-                ),
-                self.ast
-                    .new_vec_single(Argument::Expression(token_identifier)),
-                false,
-                None,
-            );
-
-            // Add the call expression as an argument to the new_expression_arguments
-            new_expression_arguments.push(Argument::Expression(inject_call_expression));
-        }
+    #[test]
+    fn inject_without_token_is_reported_once() {
+        let (code, codes) = compile(
+            "import { Injectable, Inject } from '@angular/core';\n\
+             @Injectable()\n\
+             export class Service { constructor(@Inject() value) {} }\n",
+        );
+        assert_eq!(codes, ["angular(missing-decorator-argument)"]);
+        assert!(code.contains("i0.ɵɵinvalidFactoryDep(0)"));
+        assert!(code.contains("constructor(value) {}"));
+        assert!(!code.contains("import {"), "{}", code);
+    }
 
-        // Creating the new expression with the aggregated arguments
-        let new_expression = self.ast.new_expression(
-            identifier_span,
-            self.ast.parenthesized_expression(
-                identifier_span,
-                self.ast.logical_expression(
-                    identifier_span,
-                    param_identifier_expression,
-                    LogicalOperator::Or,
-                    function_identifier,
-                ),
-            ),
-            new_expression_arguments, // Use the aggregated call expressions here
-            None,
+    #[test]
+    fn inject_calls_are_rewritten() {
+        let (code, codes) = compile(
+            "import { Injectable, inject } from '@angular/core';\n\
+             @Injectable()\n\
+             export class Service {\n\
+               private logger = inject(Logger, { optional: true });\n\
+               async load() { return inject(Http); }\n\
+             }\n",
         );
+        assert_eq!(codes, ["angular(inject-outside-context)"]);
+        assert!(code.contains("private logger = i0.ɵɵinject(Logger, 8);"));
+    }
 
-        let return_statement = self
-            .ast
-            .return_statement(identifier_span, Some(new_expression));
+    #[test]
+    fn inject_in_undecorated_class_is_reported() {
+        let (code, codes) = compile(
+            "import { inject } from '@angular/core';\n\
+             export class Plain { http = inject(Http); }\n\
+             export function useHttp() { return inject(Http); }\n",
+        );
+        assert_eq!(codes, ["angular(inject-outside-context)"]);
+        assert!(code.contains("http = inject(Http);"));
+        assert!(code.contains("return i0.ɵɵinject(Http);"));
+    }
 
-        let function_body = self.ast.function_body(
-            identifier_span,
-            self.ast.new_vec(),
-            self.ast.new_vec_single(return_statement),
+    #[test]
+    fn undecorated_class_is_left_alone() {
+        let (code, codes) = compile(
+            "import { Http } from './http';\n\
+             export class Plain { constructor(@Optional() http: Http) {} }\n",
         );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(!code.contains("ɵfac"));
+        assert!(code.contains("@Optional()"));
+    }
 
-        let function_expression = self.ast.function_expression(self.ast.function(
-            FunctionType::FunctionExpression,
-            identifier_span,
-            Some(function_identifier_ref),
-            false,
-            false,
-            None,
-            params,
-            Some(function_body),
-            None,
-            None,
-            Modifiers::empty(),
+    #[test]
+    fn subclass_without_constructor_inherits_its_factory() {
+        let (code, codes) = compile(
+            "import { Injectable } from '@angular/core';\n\
+             import { Service } from './service';\n\
+             @Injectable()\n\
+             export class Child extends Service {}\n",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(code.contains("let ɵChild_BaseFactory;"));
+        assert!(code.contains(
+            "return (ɵChild_BaseFactory || (ɵChild_BaseFactory = i0.ɵɵgetInheritedFactory(Child)))(t || Child);"
         ));
+    }
 
-        let property_definition = self.ast.class_property(
-            PropertyDefinitionType::PropertyDefinition,
-            identifier_span,
-            identifier_key,
-            Some(function_expression),
-            false,
-            true,
-            self.ast.new_vec(),
+    #[test]
+    fn one_factory_for_the_definition() {
+        let (code, codes) = compile(
+            "import { Injectable, Pipe } from '@angular/core';\n\
+             import { Http } from './http';\n\
+             @Injectable() @Pipe({ name: 'p' }) export class P { constructor(http: Http) {} }\n",
         );
-        property_definition
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert_eq!(code.matches("static ɵfac").count(), 1);
+        assert!(code.contains("return new (t || P)(i0.ɵɵdirectiveInject(Http, 16));"));
+        assert!(code.contains("factory: P.ɵfac"));
     }
 }
@@ -0,0 +1,171 @@
+use crate::angular::runtime::Identifier;
+use crate::angular::TopLevelDecorator;
+use crate::angular::{context::AngularContext, DirectiveMembers, DirectiveOptions};
+
+use std::rc::Rc;
+
+use oxc_ast::{ast::*, AstBuilder};
+
+use crate::angular::context::AngularCtx;
+
+use super::definition::{definition_property, directive_fields, take_members};
+
+pub struct DirectiveCreator<'a> {
+    ast: Rc<AstBuilder<'a>>,
+    context: AngularCtx<'a>,
+}
+
+impl<'a> DirectiveCreator<'a> {
+    pub fn new(ast: Rc<AstBuilder<'a>>, context: AngularCtx<'a>) -> Option<Self> {
+        Some(Self { ast, context })
+    }
+
+    pub fn specifier_to_remove() -> Vec<&'a str> {
+        vec!["Directive"]
+    }
+
+    pub fn transform_class(
+        &mut self,
+        class: &mut Class<'a>,
+        top_level_decorators: &[(TopLevelDecorator, usize)],
+    ) {
+        let class_name = if !top_level_decorators.is_empty() {
+            class
+                .id
+                .clone()
+                .map(|id| id.name.to_compact_str())
+                .or_else(|| Some(self.context.generate_uid("class")))
+        } else {
+            None
+        };
+        for (decorator, index) in top_level_decorators {
+            if let TopLevelDecorator::Directive { options } = decorator {
+                let span = class.decorators[*index].span;
+                let members = take_members(&mut self.context, class);
+                if let Some(property_definition) = self.ng_directive_builder(
+                    class,
+                    class_name.as_deref().unwrap_or_default(),
+                    options,
+                    &members,
+                    span,
+                ) {
+                    class.body.body.insert(1, property_definition);
+                }
+            }
+        }
+    }
+
+    /// `static ɵdir = i0.ɵɵdefineDirective({ ... })`.
+    fn ng_directive_builder(
+        &mut self,
+        class: &Class<'a>,
+        class_name: &str,
+        options: &DirectiveOptions,
+        members: &DirectiveMembers,
+        span: Span,
+    ) -> Option<ClassElement<'a>> {
+        let fields = directive_fields(&mut self.context, class, class_name, options, None, members);
+        definition_property(
+            &self.ast,
+            &mut self.context,
+            "ɵdir",
+            Identifier::DefineDirective,
+            &fields,
+            span,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::compile;
+
+    #[test]
+    fn selector_inputs_outputs_and_export_as() {
+        let (code, codes) = compile(
+            "import { Directive } from '@angular/core';\n\
+             @Directive({\n\
+               selector: '[appHighlight]:not(.off)',\n\
+               standalone: true,\n\
+               inputs: ['color', 'size: highlightSize'],\n\
+               outputs: ['changed: highlightChange'],\n\
+               exportAs: 'highlight, hl',\n\
+             })\n\
+             export class HighlightDirective {}\n",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(code.contains("static ɵdir = i0.ɵɵdefineDirective({"));
+        assert!(code.contains("selectors: [[\"\", \"appHighlight\", \"\", 9, \"off\"]]"));
+        assert!(code.contains("color: \"color\""));
+        assert!(code.contains("size: [0, \"highlightSize\", \"size\"]"));
+        assert!(code.contains("outputs: { changed: \"highlightChange\" }"));
+        assert!(code.contains("exportAs: [\"highlight\", \"hl\"]"));
+        assert!(!code.contains("features"));
+    }
+
+    #[test]
+    fn host_bindings() {
+        let (code, _) = compile(
+            "import { Directive } from '@angular/core';\n\
+             @Directive({\n\
+               selector: '[a]',\n\
+               host: { 'class': 'hl', '[style.color]': 'color', '(window:resize)': 'resize()' },\n\
+             })\n\
+             export class A { color = 'red'; }\n",
+        );
+        assert!(code.contains("hostAttrs: [1, \"hl\"]"));
+        assert!(code.contains("hostVars: 2"));
+        assert!(code.contains("i0.ɵɵstyleProp(\"color\", ctx.color);"));
+        assert!(code.contains("}, false, i0.ɵɵresolveWindow);"));
+    }
+
+    #[test]
+    fn unsupported_option_is_a_warning() {
+        let (code, codes) = compile(
+            "import { Directive } from '@angular/core';\n\
+             @Directive({ selector: '[a]', selecter: '[b]' })\n\
+             export class A {}\n",
+        );
+        assert_eq!(codes, ["angular(unsupported-option)"]);
+        assert!(code.contains("i0.ɵɵdefineDirective"));
+    }
+
+    #[test]
+    fn member_decorators() {
+        let (code, codes) = compile(
+            "import { Directive, Input, Output, HostListener, ViewChild, booleanAttribute } from '@angular/core';\n\
+             @Directive({ selector: '[tip]', standalone: true })\n\
+             export class Tip {\n\
+               @Input({ alias: 'tip', transform: booleanAttribute }) enabled = false;\n\
+               @Output() shown = new EventEmitter();\n\
+               @HostListener('mouseenter', ['$event']) show(e) {}\n\
+               @ViewChild('ref', { static: true }) ref;\n\
+             }\n",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(code.contains("inputs: { enabled: [2, \"tip\", \"enabled\", booleanAttribute] }"));
+        assert!(code.contains("outputs: { shown: \"shown\" }"));
+        assert!(code.contains("i0.ɵɵlistener(\"mouseenter\""));
+        assert!(code.contains("return ctx.show($event);"));
+        assert!(code.contains("i0.ɵɵviewQuery([\"ref\"], 7);"));
+        assert!(code.contains("(ctx.ref = _t.first)"));
+        assert!(code.contains("features: [i0.ɵɵInputTransformsFeature]"));
+        assert!(code.contains("\tenabled = false;"));
+    }
+
+    #[test]
+    fn features_from_the_class() {
+        let (code, codes) = compile(
+            "import { Directive, Input, OnChanges } from '@angular/core';\n\
+             import { Base } from './base';\n\
+             @Directive({ selector: '[a]', standalone: true })\n\
+             export class A extends Base implements OnChanges {\n\
+               @Input() value;\n\
+               constructor() { super(); }\n\
+               ngOnChanges() {}\n\
+             }\n",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(code.contains("features: [i0.ɵɵInheritDefinitionFeature, i0.ɵɵNgOnChangesFeature]"));
+    }
+}
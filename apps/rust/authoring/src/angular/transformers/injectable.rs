@@ -1,26 +1,24 @@
-use crate::angular::{context::AngularContext, InjectableOptions};
+use crate::angular::runtime::Identifier;
+use crate::angular::{context::AngularContext, Dependency, InjectableOptions, InjectableProvider};
 use crate::angular::{ProviderScope, TopLevelDecorator};
 
 use std::rc::Rc;
 
-use oxc::syntax::class;
-use oxc_ast::{ast::*, AstBuilder, AstKind};
-use oxc_span::SPAN;
+use oxc_ast::{ast::*, AstBuilder};
 
 use crate::angular::context::AngularCtx;
 
+use super::definition::{core, definition_property, inject_dependency};
+
 pub struct InjectableCreator<'a> {
     ast: Rc<AstBuilder<'a>>,
-    nodes: oxc_allocator::Vec<'a, AstKind<'a>>,
     context: AngularCtx<'a>,
 }
 
 impl<'a> InjectableCreator<'a> {
     pub fn new(ast: Rc<AstBuilder<'a>>, context: AngularCtx<'a>) -> Option<Self> {
-        let nodes: oxc_allocator::Vec<'_, AstKind<'_>> = ast.new_vec();
         Some(Self {
             ast,
-            nodes,
             context,
         })
     }
@@ -32,143 +30,158 @@ impl<'a> InjectableCreator<'a> {
     pub fn transform_class(
         &mut self,
         class: &mut Class<'a>,
-        top_level_decorators: &Vec<(TopLevelDecorator, usize)>,
+        top_level_decorators: &[(TopLevelDecorator, usize)],
     ) {
-        let class_name = if top_level_decorators.len() > 0 {
+        let class_name = if !top_level_decorators.is_empty() {
             class
                 .id
                 .clone()
-                .map(|id| id.name.to_compact_string())
-                .or_else(|| Some(self.context.scopes().generate_uid("class")))
+                .map(|id| id.name.to_compact_str())
+                .or_else(|| Some(self.context.generate_uid("class")))
         } else {
             None
         };
         for (decorator, index) in top_level_decorators {
-            match decorator {
-                TopLevelDecorator::Injectable { options } => {
-                    class.decorators.remove(*index);
-                    let property_definition = self.ng_factory_builder(
-                        class_name.clone().unwrap_or_default().to_string(),
-                        &options,
-                    );
+            if let TopLevelDecorator::Injectable { options } = decorator {
+                let span = class.decorators[*index].span;
+                if let Some(property_definition) = self.ng_factory_builder(
+                    class_name.as_deref().unwrap_or_default().to_string(),
+                    options,
+                    span,
+                ) {
                     class.body.body.insert(1, property_definition);
                 }
-                _ => (),
             }
         }
     }
 
+    /// `static ɵprov = i0.ɵɵdefineInjectable({ token, factory, providedIn })`, the factory
+    /// creating the class or whatever its `use*` option provides instead.
     fn ng_factory_builder(
-        &self,
+        &mut self,
         class_name: String,
         injectable_options: &InjectableOptions,
-    ) -> ClassElement<'a> {
-        let static_property_key = self.ast.property_key_identifier(IdentifierName {
-            span: SPAN,
-            name: self.ast.new_atom("ɵprov"),
-        });
-
-        let i0_identifier_name = self.ast.new_atom("i0");
-        let i0_identifier = self
-            .ast
-            .identifier_reference_expression(IdentifierReference::new(SPAN, i0_identifier_name));
-
-        // Create a new vector for properties
-        let mut properties = self.ast.new_vec();
-
-        properties.push(ObjectPropertyKind::ObjectProperty(
-            self.ast.object_property(
-                SPAN,
-                PropertyKind::Init,
-                self.ast
-                    .property_key_identifier(IdentifierName::new(SPAN, "token".into())),
-                self.ast
-                    .identifier_reference_expression(IdentifierReference::new(
-                        SPAN,
-                        self.ast.new_atom(&class_name),
-                    )),
-                None,
-                false,
-                false,
-                false,
-            ),
-        ));
+        span: Span,
+    ) -> Option<ClassElement<'a>> {
+        let source_text = self.context.source_text();
+        let source = |span: Span| span.source_text(source_text).to_string();
+        let deps = |deps: &[Dependency]| {
+            deps.iter()
+                .map(|dep| inject_dependency(Identifier::Inject, dep, source_text))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        // Subclasses construct themselves; everything else gets what is provided.
+        let delegated = |expression: String| {
+            format!(
+                "function {}_Factory(t) {{ let r = null; if (t) {{ r = new t(); }} else {{ r = {}; }} return r; }}",
+                class_name, expression
+            )
+        };
 
-        properties.push(ObjectPropertyKind::ObjectProperty(
-            self.ast.object_property(
-                SPAN,
-                PropertyKind::Init,
-                self.ast
-                    .property_key_identifier(IdentifierName::new(SPAN, "factory".into())),
-                self.ast.static_member_expression(
-                    SPAN,
-                    self.ast
-                        .identifier_reference_expression(IdentifierReference::new(
-                            SPAN,
-                            class_name.into(),
-                        )),
-                    IdentifierName {
-                        span: SPAN,
-                        name: "ɵfac".into(),
-                    },
-                    false,
-                ),
-                None,
-                false,
-                false,
-                false,
-            ),
-        ));
+        let own_factory = format!("{}.ɵfac", class_name);
+        let factory = match &injectable_options.detail {
+            InjectableProvider::None | InjectableProvider::Constructor(_) => own_factory,
+            InjectableProvider::Class(provider) if source(provider.use_class) == class_name => {
+                own_factory
+            }
+            InjectableProvider::Class(provider) => {
+                format!("(t) => {}.ɵfac(t)", source(provider.use_class))
+            }
+            InjectableProvider::StaticClass(provider) => delegated(format!(
+                "new ({})({})",
+                source(provider.use_class),
+                deps(&provider.deps)
+            )),
+            InjectableProvider::Factory(provider) => match &provider.deps {
+                Some(dependencies) => delegated(format!(
+                    "({})({})",
+                    source(provider.use_factory),
+                    deps(dependencies)
+                )),
+                None => format!("() => ({})()", source(provider.use_factory)),
+            },
+            InjectableProvider::Value(provider) => delegated(source(provider.use_value)),
+            InjectableProvider::Existing(provider) => delegated(format!(
+                "{}({})",
+                core(Identifier::Inject),
+                source(provider.use_existing)
+            )),
+        };
 
-        if injectable_options.provided_in != ProviderScope::None {
-            properties.push(ObjectPropertyKind::ObjectProperty(
-                self.ast.object_property(
-                    SPAN,
-                    PropertyKind::Init,
-                    self.ast
-                        .property_key_identifier(IdentifierName::new(SPAN, "providedIn".into())),
-                    self.ast.literal_string_expression(StringLiteral::new(
-                        SPAN,
-                        self.ast
-                            .new_atom(&injectable_options.provided_in.to_string()),
-                    )),
-                    None,
-                    false,
-                    false,
-                    false,
-                ),
-            ));
+        let mut fields = vec![
+            format!("token: {}", class_name),
+            format!("factory: {}", factory),
+        ];
+        let provided_in = match &injectable_options.provided_in {
+            ProviderScope::Root => Some("\"root\"".to_string()),
+            ProviderScope::Platform => Some("\"platform\"".to_string()),
+            ProviderScope::Any => Some("\"any\"".to_string()),
+            ProviderScope::Token(token) => Some(source(*token)),
+            ProviderScope::None => None,
+        };
+        if let Some(provided_in) = provided_in {
+            fields.push(format!("providedIn: {}", provided_in));
         }
+        definition_property(
+            &self.ast,
+            &mut self.context,
+            "ɵprov",
+            Identifier::DefineInjectable,
+            &fields,
+            span,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::compile;
+
+    #[test]
+    fn use_factory_with_deps() {
+        let (code, codes) = compile(
+            "import { Injectable } from '@angular/core';\n\
+             import { Http } from './http';\n\
+             @Injectable({ providedIn: 'root', useFactory: (http: Http) => http, deps: [Http] })\n\
+             export class Api {}\n",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(code.contains("r = ((http: Http) => http)(i0.ɵɵinject(Http));"));
+        assert!(code.contains("providedIn: \"root\""));
+    }
 
-        let define_injectable_object = self.ast.object_expression(SPAN, properties, None);
-
-        let define_injectable_name = self.ast.new_atom("ɵɵdefineInjectable");
-        let define_injectable_call_expression = self.ast.call_expression(
-            SPAN,
-            self.ast.static_member_expression(
-                SPAN,
-                i0_identifier,
-                IdentifierName {
-                    span: SPAN,
-                    name: define_injectable_name.clone(),
-                },
-                false,
-            ),
-            self.ast
-                .new_vec_single(Argument::Expression(define_injectable_object)),
-            false,
-            None,
+    #[test]
+    fn use_value() {
+        let (code, codes) = compile(
+            "import { Injectable } from '@angular/core';\n\
+             @Injectable({ providedIn: 'root', useValue: 42 })\n\
+             export class Answer {}\n",
         );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(code.contains("r = 42;"));
+    }
+
+    #[test]
+    fn constructor_provider_uses_the_factory() {
+        let (code, codes) = compile(
+            "import { Injectable } from '@angular/core';\n\
+             @Injectable()\n\
+             export class Service {}\n",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(code.contains(
+            "static ɵprov = i0.ɵɵdefineInjectable({\n\t\ttoken: Service,\n\t\tfactory: Service.ɵfac\n\t});"
+        ));
+    }
 
-        let property_definition = self.ast.class_property(
-            PropertyDefinitionType::PropertyDefinition,
-            SPAN,
-            static_property_key,
-            Some(define_injectable_call_expression),
-            false,
-            true,
-            self.ast.new_vec(),
+    #[test]
+    fn invalid_provided_in_is_reported() {
+        let (_, codes) = compile(
+            "import { Injectable } from '@angular/core';\n\
+             @Injectable({ providedIn: 'everywhere' })\n\
+             export class Service {}\n",
         );
-        property_definition
+        assert_eq!(codes, ["angular(invalid-provided-in)"]);
     }
 }
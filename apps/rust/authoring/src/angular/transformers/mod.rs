@@ -1,4 +1,29 @@
+mod component;
+mod definition;
 mod dependency;
+mod directive;
 mod injectable;
+mod ng_module;
+mod pipe;
+pub use self::component::*;
 pub use self::dependency::*;
-pub use self::injectable::*;
\ No newline at end of file
+pub use self::directive::*;
+pub use self::injectable::*;
+pub use self::ng_module::*;
+pub use self::pipe::*;
+
+/// Compiles `source` as a TypeScript module, returning the code and the codes of its
+/// diagnostics, e.g. `angular(unsupported-option)`.
+#[cfg(test)]
+fn compile(source: &str) -> (String, Vec<String>) {
+    use super::{transform, TransformOptions};
+
+    let result = transform(source, "test.ts", &TransformOptions::default());
+    let codes = result
+        .diagnostics
+        .iter()
+        .filter_map(|diagnostic| diagnostic.code())
+        .map(|code| code.to_string())
+        .collect();
+    (result.code, codes)
+}
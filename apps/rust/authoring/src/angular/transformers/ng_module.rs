@@ -0,0 +1,218 @@
+use crate::angular::runtime::Identifier;
+use crate::angular::TopLevelDecorator;
+use crate::angular::{context::AngularContext, NgModuleOptions};
+
+use std::rc::Rc;
+
+use oxc_ast::{ast::*, AstBuilder};
+
+use crate::angular::context::AngularCtx;
+
+use super::definition::{core, definition_property, statements, string_literal};
+
+pub struct NgModuleCreator<'a> {
+    ast: Rc<AstBuilder<'a>>,
+    context: AngularCtx<'a>,
+    /// Statements registering each module, by the name of its class, which they must follow,
+    /// with the span of its decorator.
+    side_effects: Vec<(String, String, Span)>,
+}
+
+impl<'a> NgModuleCreator<'a> {
+    pub fn new(ast: Rc<AstBuilder<'a>>, context: AngularCtx<'a>) -> Option<Self> {
+        Some(Self {
+            ast,
+            context,
+            side_effects: Vec::new(),
+        })
+    }
+
+    pub fn specifier_to_remove() -> Vec<&'a str> {
+        vec!["NgModule"]
+    }
+
+    pub fn transform_class(
+        &mut self,
+        class: &mut Class<'a>,
+        top_level_decorators: &[(TopLevelDecorator, usize)],
+    ) {
+        let class_name = if !top_level_decorators.is_empty() {
+            class
+                .id
+                .clone()
+                .map(|id| id.name.to_compact_str())
+                .or_else(|| Some(self.context.generate_uid("class")))
+        } else {
+            None
+        };
+        for (decorator, index) in top_level_decorators {
+            if let TopLevelDecorator::NgModule { options } = decorator {
+                let span = class.decorators[*index].span;
+                let class_name = class_name.as_deref().unwrap_or_default();
+                if let Some(injector) = self.ng_injector_builder(options, span) {
+                    class.body.body.insert(1, injector);
+                }
+                if let Some(module) = self.ng_module_builder(class_name, options, span) {
+                    class.body.body.insert(1, module);
+                }
+                self.side_effects_builder(class_name, options, span);
+            }
+        }
+    }
+
+    /// The statements registering the modules so far, parsed, with the name of the class each
+    /// must follow.
+    pub fn take_side_effects(&mut self) -> Vec<(String, Statement<'a>)> {
+        std::mem::take(&mut self.side_effects)
+            .into_iter()
+            .filter_map(|(class_name, code, span)| {
+                let statement = statements(&self.ast, &code, span).into_iter().next()?;
+                Some((class_name, statement))
+            })
+            .collect()
+    }
+
+    /// `static ɵmod = i0.ɵɵdefineNgModule({ type, bootstrap, declarations, imports, exports })`.
+    fn ng_module_builder(
+        &mut self,
+        class_name: &str,
+        options: &NgModuleOptions,
+        span: Span,
+    ) -> Option<ClassElement<'a>> {
+        let source_text = self.context.source_text();
+        let source = |span: Span| span.source_text(source_text).to_string();
+
+        let mut fields = vec![format!("type: {}", class_name)];
+        let arrays = [
+            ("bootstrap", options.bootstrap),
+            ("declarations", options.declarations),
+            ("imports", options.imports),
+            ("exports", options.exports),
+            ("schemas", options.schemas),
+        ];
+        for (name, span) in arrays {
+            if let Some(span) = span {
+                fields.push(format!("{}: {}", name, source(span)));
+            }
+        }
+        if let Some(id) = &options.id {
+            fields.push(format!("id: {}", string_literal(&id.value)));
+        }
+        definition_property(
+            &self.ast,
+            &mut self.context,
+            "ɵmod",
+            Identifier::DefineNgModule,
+            &fields,
+            span,
+        )
+    }
+
+    /// `static ɵinj = i0.ɵɵdefineInjector({ providers, imports })`.
+    fn ng_injector_builder(
+        &mut self,
+        options: &NgModuleOptions,
+        span: Span,
+    ) -> Option<ClassElement<'a>> {
+        let source_text = self.context.source_text();
+        let source = |span: Span| span.source_text(source_text).to_string();
+
+        let mut fields = Vec::new();
+        if let Some(providers) = options.providers {
+            fields.push(format!("providers: {}", source(providers)));
+        }
+        if let Some(imports) = options.imports {
+            fields.push(format!("imports: {}", source(imports)));
+        }
+        definition_property(
+            &self.ast,
+            &mut self.context,
+            "ɵinj",
+            Identifier::DefineInjector,
+            &fields,
+            span,
+        )
+    }
+
+    /// `ɵɵsetNgModuleScope`, for modules compiled just in time to look up their scope, and
+    /// `ɵɵregisterNgModuleType` for modules with an `id`.
+    fn side_effects_builder(&mut self, class_name: &str, options: &NgModuleOptions, span: Span) {
+        let source_text = self.context.source_text();
+        let source = |span: Span| span.source_text(source_text).to_string();
+
+        let scope: Vec<_> = [
+            ("declarations", options.declarations),
+            ("imports", options.imports),
+            ("exports", options.exports),
+        ]
+        .into_iter()
+        .filter_map(|(name, span)| Some(format!("{}: {}", name, source(span?))))
+        .collect();
+        if !scope.is_empty() {
+            self.side_effects.push((
+                class_name.to_string(),
+                format!(
+                    "(function () {{ (typeof ngJitMode === \"undefined\" || ngJitMode) && {}({}, {{ {} }}); }})();",
+                    core(Identifier::SetNgModuleScope),
+                    class_name,
+                    scope.join(", ")
+                ),
+                span,
+            ));
+        }
+        if let Some(id) = &options.id {
+            self.side_effects.push((
+                class_name.to_string(),
+                format!(
+                    "{}({}, {});",
+                    core(Identifier::RegisterNgModuleType),
+                    class_name,
+                    string_literal(&id.value)
+                ),
+                span,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::compile;
+
+    #[test]
+    fn module_injector_and_scope() {
+        let (code, codes) = compile(
+            "import { NgModule } from '@angular/core';\n\
+             import { CommonModule } from '@angular/common';\n\
+             import { AppComponent, Other } from './app.component';\n\
+             @NgModule({\n\
+               declarations: [AppComponent, Other],\n\
+               imports: [CommonModule],\n\
+               exports: [Other],\n\
+               bootstrap: [AppComponent],\n\
+               providers: [Other],\n\
+               id: 'app',\n\
+             })\n\
+             export class AppModule {}\n",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(code.contains("static ɵmod = i0.ɵɵdefineNgModule({"));
+        assert!(code.contains("bootstrap: [AppComponent],"));
+        assert!(code.contains("static ɵinj = i0.ɵɵdefineInjector({"));
+        assert!(code.contains("providers: [Other],"));
+        assert!(code.contains("i0.ɵɵsetNgModuleScope(AppModule, {"));
+        assert!(code.contains("i0.ɵɵregisterNgModuleType(AppModule, \"app\");"));
+    }
+
+    #[test]
+    fn empty_module() {
+        let (code, _) = compile(
+            "import { NgModule } from '@angular/core';\n\
+             @NgModule({})\n\
+             export class EmptyModule {}\n",
+        );
+        assert!(code.contains("static ɵmod = i0.ɵɵdefineNgModule({ type: EmptyModule });"));
+        assert!(code.contains("static ɵinj = i0.ɵɵdefineInjector({});"));
+        assert!(!code.contains("setNgModuleScope"));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::angular::diagnostics::pipe_missing_name;
+use crate::angular::runtime::Identifier;
+use crate::angular::TopLevelDecorator;
+use crate::angular::{context::AngularContext, PipeOptions};
+
+use std::rc::Rc;
+
+use oxc_ast::{ast::*, AstBuilder};
+
+use crate::angular::context::AngularCtx;
+
+use super::definition::{definition_property, string_literal};
+
+pub struct PipeCreator<'a> {
+    ast: Rc<AstBuilder<'a>>,
+    context: AngularCtx<'a>,
+}
+
+impl<'a> PipeCreator<'a> {
+    pub fn new(ast: Rc<AstBuilder<'a>>, context: AngularCtx<'a>) -> Option<Self> {
+        Some(Self { ast, context })
+    }
+
+    pub fn specifier_to_remove() -> Vec<&'a str> {
+        vec!["Pipe"]
+    }
+
+    pub fn transform_class(
+        &mut self,
+        class: &mut Class<'a>,
+        top_level_decorators: &[(TopLevelDecorator, usize)],
+    ) {
+        let class_name = if !top_level_decorators.is_empty() {
+            class
+                .id
+                .clone()
+                .map(|id| id.name.to_compact_str())
+                .or_else(|| Some(self.context.generate_uid("class")))
+        } else {
+            None
+        };
+        for (decorator, index) in top_level_decorators {
+            if let TopLevelDecorator::Pipe { options } = decorator {
+                let span = class.decorators[*index].span;
+                if let Some(property_definition) = self.ng_pipe_builder(
+                    class_name.as_deref().unwrap_or_default(),
+                    options,
+                    span,
+                ) {
+                    class.body.body.insert(1, property_definition);
+                }
+            }
+        }
+    }
+
+    /// `static ɵpipe = i0.ɵɵdefinePipe({ name, type, pure, standalone })`.
+    fn ng_pipe_builder(
+        &mut self,
+        class_name: &str,
+        options: &PipeOptions,
+        span: Span,
+    ) -> Option<ClassElement<'a>> {
+        let Some(name) = &options.name else {
+            self.context.error(pipe_missing_name(span));
+            return None;
+        };
+
+        let mut fields = vec![
+            format!("name: {}", string_literal(&name.value)),
+            format!("type: {}", class_name),
+            format!("pure: {}", options.pure),
+        ];
+        if options.standalone {
+            fields.push("standalone: true".to_string());
+        }
+        definition_property(
+            &self.ast,
+            &mut self.context,
+            "ɵpipe",
+            Identifier::DefinePipe,
+            &fields,
+            span,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::compile;
+
+    #[test]
+    fn pipe_definition() {
+        let (code, codes) = compile(
+            "import { Pipe } from '@angular/core';\n\
+             @Pipe({ name: 'truncate', standalone: true, pure: false })\n\
+             export class TruncatePipe { transform(value: string) { return value; } }\n",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+        assert!(code.contains(
+            "static ɵpipe = i0.ɵɵdefinePipe({\n\t\tname: \"truncate\",\n\t\ttype: TruncatePipe,\n\t\tpure: false,\n\t\tstandalone: true\n\t});"
+        ));
+        assert!(code.contains("return new (t || TruncatePipe)();"));
+    }
+
+    #[test]
+    fn missing_name_is_reported() {
+        let (code, codes) = compile(
+            "import { Pipe } from '@angular/core';\n\
+             @Pipe({ standalone: true })\n\
+             export class Nameless {}\n",
+        );
+        assert_eq!(codes, ["angular(pipe-missing-name)"]);
+        assert!(!code.contains("ɵpipe"));
+    }
+}
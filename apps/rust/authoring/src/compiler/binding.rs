@@ -0,0 +1,283 @@
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_span::Span;
+
+use crate::angular::runtime::Identifier;
+use crate::html::{Attribute, AttributeKind};
+use crate::treaty::ast::{Element, Expression};
+
+use super::script::Script;
+use super::spread::SpreadObject;
+use super::template::{Reads, TemplateCompiler, View};
+use super::{instruction, runtime, string_literal};
+
+impl<'a> TemplateCompiler<'a> {
+    /// Allocates the slots of the `#references` of an element, each after it, returning the
+    /// index of their names in `consts`.
+    pub(super) fn local_refs(
+        &mut self,
+        view: &mut View,
+        slot: usize,
+        element: &Element,
+    ) -> Option<usize> {
+        let mut names = Vec::new();
+        for attribute in &element.attributes {
+            if let AttributeKind::Reference(name) = &attribute.kind {
+                let reference_slot = view.allocate_slot();
+                self.assign_reference_slot(name, reference_slot);
+                // `@defer` triggers point at the element itself.
+                view.references.push((name.clone(), slot));
+                names.push(string_literal(name));
+                names.push(string_literal(&attribute.value));
+            }
+        }
+        (!names.is_empty()).then(|| self.add_const(format!("[{}]", names.join(", "))))
+    }
+
+    /// `(event)` and `[(twoWay)]` listeners, created right after their element.
+    pub(super) fn visit_listeners(&mut self, view: &mut View, slot: usize, element: &Element) {
+        for attribute in &element.attributes {
+            match &attribute.kind {
+                AttributeKind::Event(event) => {
+                    let handler = self.listener(view, slot, element, event, attribute, false);
+                    view.creation.push(instruction(
+                        Identifier::Listener,
+                        &[string_literal(event), handler],
+                    ));
+                }
+                AttributeKind::TwoWay(name) => {
+                    let event = format!("{}Change", name);
+                    let handler = self.listener(view, slot, element, &event, attribute, true);
+                    view.creation.push(instruction(
+                        Identifier::TwoWayListener,
+                        &[string_literal(&event), handler],
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Property, attribute and styling bindings, in the order Angular applies them: style and
+    /// class maps, then single styles and classes, then properties, then attributes.
+    pub(super) fn visit_bindings(&mut self, view: &mut View, slot: usize, element: &Element) {
+        let order = |kind: &AttributeKind| match kind {
+            AttributeKind::Property(name) if name == "style" => Some(0),
+            AttributeKind::Property(name) if name == "class" => Some(1),
+            AttributeKind::Style { .. } => Some(2),
+            AttributeKind::Class(_) => Some(3),
+            AttributeKind::Property(_)
+            | AttributeKind::TwoWay(_)
+            | AttributeKind::Animation(_)
+            | AttributeKind::Spread(_) => Some(4),
+            AttributeKind::Attribute(_) => Some(5),
+            AttributeKind::Static | AttributeKind::Event(_) | AttributeKind::Reference(_) => None,
+        };
+        let mut bindings: Vec<_> = element
+            .attributes
+            .iter()
+            .filter_map(|attribute| Some((order(&attribute.kind)?, attribute)))
+            .collect();
+        bindings.sort_by_key(|(order, _)| *order);
+
+        for (_, attribute) in bindings {
+            if let AttributeKind::Spread(object) = &attribute.kind {
+                self.visit_spread(view, slot, attribute, object);
+                continue;
+            }
+            // `@trigger` without a value still starts the animation.
+            let value = match &attribute.kind {
+                AttributeKind::Animation(_) if attribute.value.trim().is_empty() => {
+                    "undefined".to_string()
+                }
+                _ => self.expression(view, &value(attribute)),
+            };
+            // Styling bindings take two binding slots.
+            let (identifier, args, vars) = match &attribute.kind {
+                AttributeKind::Property(name) if name == "style" => {
+                    (Identifier::StyleMap, vec![value], 2)
+                }
+                AttributeKind::Property(name) if name == "class" => {
+                    (Identifier::ClassMap, vec![value], 2)
+                }
+                AttributeKind::Style { name, unit } => {
+                    let mut args = vec![string_literal(name), value];
+                    args.extend(unit.as_deref().map(string_literal));
+                    (Identifier::StyleProp, args, 2)
+                }
+                AttributeKind::Class(name) => {
+                    (Identifier::ClassProp, vec![string_literal(name), value], 2)
+                }
+                AttributeKind::Property(name) => {
+                    (Identifier::Property, vec![string_literal(name), value], 1)
+                }
+                AttributeKind::TwoWay(name) => (
+                    Identifier::TwoWayProperty,
+                    vec![string_literal(name), value],
+                    1,
+                ),
+                AttributeKind::Animation(name) => {
+                    let name = string_literal(&format!("@{}", name));
+                    (Identifier::Property, vec![name, value], 1)
+                }
+                AttributeKind::Attribute(name) => {
+                    (Identifier::Attribute, vec![string_literal(name), value], 1)
+                }
+                AttributeKind::Static
+                | AttributeKind::Event(_)
+                | AttributeKind::Reference(_)
+                | AttributeKind::Spread(_) => continue,
+            };
+            view.vars += vars;
+            view.update(slot, instruction(identifier, &args));
+        }
+    }
+
+    /// `{...object}`: a property binding for each key of the object, which must be known
+    /// statically.
+    fn visit_spread(&mut self, view: &mut View, slot: usize, attribute: &Attribute, object: &str) {
+        let Some(spread) = self.spread_object(object) else {
+            self.diagnostics.push(
+                OxcDiagnostic::error(format!(
+                    "Cannot resolve the keys of \"{}\" statically",
+                    object
+                ))
+                .with_help(
+                    "Spread an object literal, a signal of one, or a variable typed with an interface",
+                )
+                .with_label(attribute.span),
+            );
+            return;
+        };
+
+        let offset = attribute.name.find(object).unwrap_or_default() as u32;
+        let start = attribute.span.start + offset;
+        let object = self.expression(
+            view,
+            &Expression {
+                source: object.to_string(),
+                span: Span::new(start, start + object.len() as u32),
+            },
+        );
+        let object = if spread.signal {
+            format!("{}()", object)
+        } else {
+            object
+        };
+        for (key, signal) in &spread.keys {
+            let value = member(&object, key) + if *signal { "()" } else { "" };
+            view.vars += 1;
+            view.update(
+                slot,
+                instruction(Identifier::Property, &[string_literal(key), value]),
+            );
+        }
+    }
+
+    /// The keys of a script object spread onto an element.
+    pub(super) fn spread_object(&self, name: &str) -> Option<&'a SpreadObject> {
+        let script: &'a Script = self.script;
+        script
+            .objects
+            .iter()
+            .find(|(object, _)| object == name)
+            .map(|(_, spread)| spread)
+    }
+
+    /// The function handling `event`, evaluating the attribute value with `$event` in scope.
+    ///
+    /// A listener reading other views or references restores its view first, since it runs
+    /// outside of change detection.
+    fn listener(
+        &mut self,
+        view: &mut View,
+        slot: usize,
+        element: &Element,
+        event: &str,
+        attribute: &Attribute,
+        two_way: bool,
+    ) -> String {
+        let mut reads = Reads::default();
+        let mut uses_event = two_way;
+        let handler =
+            self.expression_reading(view.depth, &mut reads, &value(attribute), &mut |name| {
+                (name == "$event").then(|| {
+                    uses_event = true;
+                    name.to_string()
+                })
+            });
+
+        let restores = !reads.is_empty();
+        let mut body = Vec::new();
+        if restores {
+            let current_view = view
+                .current_view
+                .get_or_insert_with(|| {
+                    self.current_views += 1;
+                    format!("_r{}", self.current_views)
+                })
+                .clone();
+            body.push(format!(
+                "{}({});",
+                runtime(Identifier::RestoreView),
+                current_view
+            ));
+            body.extend(reads.declarations());
+        }
+        let result = if two_way {
+            body.push(format!(
+                "{}({}, $event) || ({} = $event);",
+                runtime(Identifier::TwoWayBindingSet),
+                handler,
+                handler
+            ));
+            "$event".to_string()
+        } else {
+            handler
+        };
+        body.push(if restores {
+            format!("return {}({});", runtime(Identifier::ResetView), result)
+        } else {
+            format!("return {};", result)
+        });
+
+        let name = format!(
+            "{}_Template_{}_{}_{}_listener",
+            view.name,
+            identifier_part(&element.tag_name),
+            identifier_part(event),
+            slot
+        );
+        let parameter = if uses_event { "$event" } else { "" };
+        format!("function {}({}) {{ {} }}", name, parameter, body.join(" "))
+    }
+}
+
+/// The value of an attribute as an expression, with the span of its trimmed source.
+pub(super) fn value(attribute: &Attribute) -> Expression {
+    let leading = attribute.value.len() - attribute.value.trim_start().len();
+    let source = attribute.value.trim();
+    let start = attribute.value_span.start + leading as u32;
+    Expression {
+        source: source.to_string(),
+        span: Span::new(start, start + source.len() as u32),
+    }
+}
+
+/// `object.key`, or `object["key"]` when the key is not an identifier.
+fn member(object: &str, key: &str) -> String {
+    let is_identifier = key.chars().enumerate().all(|(i, c)| {
+        c == '_' || c == '$' || c.is_ascii_alphabetic() || i > 0 && c.is_ascii_digit()
+    });
+    if is_identifier && !key.is_empty() {
+        format!("{}.{}", object, key)
+    } else {
+        format!("{}[{}]", object, string_literal(key))
+    }
+}
+
+/// `keydown.enter` -> `keydown_enter`, for use in a function name.
+pub(super) fn identifier_part(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
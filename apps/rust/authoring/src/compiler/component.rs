@@ -0,0 +1,304 @@
+use oxc_diagnostics::OxcDiagnostic;
+
+use crate::angular::runtime::{Identifier, InputFlags};
+use crate::treaty::ast::{Ast, AstNode};
+use crate::treaty::lexer::Lexer;
+use crate::treaty::parser::Parser;
+
+use super::defer::lazy_components;
+use super::naming::{
+    extract_file_name, to_camel_case, to_hyphen_case, to_identifier, to_pascal_case,
+};
+use super::query::query_functions;
+use super::script::Script;
+use super::shim::shim_css;
+use super::template::{compile_template, CompiledTemplate};
+use super::{runtime, string_literal, CORE};
+use tracing::debug;
+
+pub struct CompiledComponent {
+    /// Name of the component function, e.g. `componentLogo` for `component/logo.treaty`.
+    pub name: String,
+    /// The ES module replacing the `.treaty` file.
+    pub code: String,
+    pub diagnostics: Vec<OxcDiagnostic>,
+}
+
+/// Compiles a parsed `.treaty` file into a standalone component.
+///
+/// The script becomes the body of the component function, which returns its top-level bindings
+/// as the template context. The markup is compiled into the template function of `ɵcmp`.
+pub fn compile(source_text: &str, ast: &Ast, file_path: &str) -> CompiledComponent {
+    let file_name = extract_file_name(file_path);
+    let (script, mut diagnostics) = Script::new(source_text, ast);
+    // Declared next to the imports, so it must not take one of their names.
+    let mut name = to_identifier(&to_camel_case(&file_name));
+    while name == CORE
+        || script
+            .imports
+            .iter()
+            .flat_map(|import| &import.specifiers)
+            .any(|(local, _)| *local == name)
+    {
+        name.insert(0, '_');
+    }
+
+    let mut styles = Vec::new();
+    let mut template_nodes = Vec::new();
+    for node in &ast.nodes {
+        match node {
+            AstNode::JavaScript(_) => {}
+            // Scoped to the component, which is left to the default `ViewEncapsulation.Emulated`.
+            AstNode::Style(style) => {
+                styles.push(string_literal(&shim_css(&minify_css(&style.content))))
+            }
+            _ => template_nodes.push(node.clone()),
+        }
+    }
+    let lazy_components = lazy_components(&template_nodes, &script);
+    let mut template = compile_template(&name, &template_nodes, &script, &lazy_components);
+    diagnostics.append(&mut template.diagnostics);
+
+    let selectors = [
+        to_hyphen_case(&file_name),
+        to_camel_case(&file_name),
+        to_pascal_case(&file_name),
+    ]
+    .iter()
+    .map(|selector| format!("[{}]", string_literal(selector)))
+    .collect::<Vec<_>>()
+    .join(", ");
+
+    let queries = query_functions(&name, &script);
+    let mut fields = vec![
+        format!("type: {}", name),
+        format!("selectors: [{}]", selectors),
+    ];
+    if let Some(content_queries) = &queries.content_queries {
+        fields.push(format!("contentQueries: {}", content_queries));
+    }
+    if let Some(view_query) = &queries.view_query {
+        fields.push(format!("viewQuery: {}", view_query));
+    }
+    if !script.inputs.is_empty() {
+        fields.push(format!("inputs: {{ {} }}", inputs(&script)));
+    }
+    if !script.outputs.is_empty() {
+        fields.push(format!("outputs: {{ {} }}", outputs(&script)));
+    }
+    fields.extend([
+        "standalone: true".to_string(),
+        "signals: true".to_string(),
+        format!("features: [{}]", runtime(Identifier::StandaloneFeature)),
+        format!("decls: {}", template.decls),
+        format!("vars: {}", template.vars),
+    ]);
+    if !template.consts.is_empty() {
+        fields.push(format!("consts: [{}]", template.consts.join(", ")));
+    }
+    fields.push(format!("template: {}", template.function));
+    if !template.dependencies.is_empty() {
+        fields.push(format!(
+            "dependencies: [{}]",
+            template.dependencies.join(", ")
+        ));
+    }
+    if !styles.is_empty() {
+        fields.push(format!("styles: [{}]", styles.join(", ")));
+    }
+
+    let mut code = String::new();
+    for import in &script.imports {
+        // Only loaded by `@defer` blocks, so it must not be imported statically.
+        let is_lazy = !import.specifiers.is_empty()
+            && import
+                .specifiers
+                .iter()
+                .all(|(local, _)| lazy_components.iter().any(|(name, _)| name == local));
+        if !is_lazy {
+            code.push_str(&import.source);
+            code.push('\n');
+        }
+    }
+    code.push_str(&format!("import * as {} from \"@angular/core\";\n\n", CORE));
+    for declaration in queries.declarations.iter().chain(&template.declarations) {
+        code.push_str(declaration);
+        code.push_str("\n\n");
+    }
+    code.push_str(&format!("function {}() {{\n", name));
+    // Not re-indented, which would change the contents of multi-line template literals.
+    if !script.body.is_empty() {
+        code.push_str(&script.body);
+        code.push('\n');
+    }
+    code.push_str(&format!(
+        "  return {{ {} }};\n}}\n\n",
+        script.bindings.join(", ")
+    ));
+    code.push_str(&format!(
+        "{name}.ɵfac = function {name}_Factory(__ngFactoryType__) {{ return (__ngFactoryType__ || {name})(); }};\n"
+    ));
+    code.push_str(&format!(
+        "{}.ɵcmp = /*@__PURE__*/ {}({{\n  {}\n}});\n\n",
+        name,
+        runtime(Identifier::DefineComponent),
+        fields.join(",\n  ")
+    ));
+    code.push_str(&format!("export default {};\n", name));
+    debug!(
+        component = %name,
+        diagnostics = diagnostics.len(),
+        "compiled treaty component"
+    );
+
+    CompiledComponent {
+        name,
+        code,
+        diagnostics,
+    }
+}
+
+/// `name: [flags, "publicName"]`, with the property name last when the input is aliased. Signal
+/// inputs apply their own `transform`, so none is listed here.
+fn inputs(script: &Script) -> String {
+    script
+        .inputs
+        .iter()
+        .map(|input| {
+            let mut entry = vec![
+                (InputFlags::SignalBased as u8).to_string(),
+                string_literal(&input.public_name),
+            ];
+            if input.public_name != input.name {
+                entry.push(string_literal(&input.name));
+            }
+            format!("{}: [{}]", input.name, entry.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `name: "publicName"`.
+fn outputs(script: &Script) -> String {
+    script
+        .outputs
+        .iter()
+        .map(|output| format!("{}: {}", output.name, string_literal(&output.public_name)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The inline `template` of a `@Component` class, found at `offset` in its TypeScript source.
+///
+/// Names are read from the class instance, and `<style>` elements are returned minified, left to
+/// be scoped by the encapsulation of the component.
+pub fn compile_inline_template(
+    name: &str,
+    template: &str,
+    offset: u32,
+) -> (CompiledTemplate, Vec<String>) {
+    // Padded to its offset, so diagnostics point into the TypeScript source.
+    let source_text = " ".repeat(offset as usize) + template;
+    let mut lexer = Lexer::template(&source_text);
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token() {
+        tokens.push(token);
+    }
+    let mut diagnostics = lexer.take_errors();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse();
+    diagnostics.append(&mut parser.take_errors());
+    let (script, _) = Script::new(&source_text, &ast);
+
+    let mut styles = Vec::new();
+    let mut template_nodes = Vec::new();
+    for node in ast.nodes {
+        match node {
+            AstNode::JavaScript(_) => {}
+            AstNode::Style(style) => styles.push(minify_css(&style.content)),
+            _ => template_nodes.push(node),
+        }
+    }
+    let mut template = compile_template(name, &template_nodes, &script, &[]);
+    diagnostics.append(&mut template.diagnostics);
+    template.diagnostics = diagnostics;
+    (template, styles)
+}
+
+/// Drops comments and insignificant whitespace, as the styles are inlined into `ɵcmp`.
+pub fn minify_css(css: &str) -> String {
+    let mut without_comments = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        without_comments.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+    without_comments.push_str(rest);
+
+    let mut result = String::with_capacity(without_comments.len());
+    let mut pending_space = false;
+    for c in without_comments.chars() {
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        let separator = |c: char| "{}:;,".contains(c);
+        if pending_space && !separator(c) && !result.is_empty() && !result.ends_with(separator) {
+            result.push(' ');
+        }
+        pending_space = false;
+        result.push(c);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::treaty::lexer::Lexer;
+    use crate::treaty::parser::Parser;
+
+    fn compile_file(source_text: &str, file_path: &str) -> CompiledComponent {
+        let mut lexer = Lexer::new(source_text);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token() {
+            tokens.push(token);
+        }
+        compile(source_text, &Parser::new(tokens).parse(), file_path)
+    }
+
+    #[test]
+    fn name_is_a_valid_identifier() {
+        for (file_path, name) in [
+            ("src/pages/404.treaty", "_404"),
+            ("src/pages/class.treaty", "_class"),
+            ("src/pages/my-page.v2.treaty", "myPageV2"),
+            ("src/pages/i0.treaty", "_i0"),
+        ] {
+            let component = compile_file("<p>x</p>\n", file_path);
+            assert_eq!(component.name, name);
+            assert!(component.code.contains(&format!("function {}() {{", name)));
+        }
+    }
+
+    #[test]
+    fn name_does_not_shadow_an_import() {
+        let component = compile_file(
+            "import logo from '../component/logo.treaty';\n<logo />\n",
+            "src/pages/logo.treaty",
+        );
+        assert_eq!(component.name, "_logo");
+        assert!(component.code.contains("export default _logo;"));
+    }
+
+    #[test]
+    fn factory_parameter_does_not_collide_with_the_name() {
+        let component = compile_file("<p>x</p>\n", "src/pages/t.treaty");
+        assert!(component.code.contains(
+            "t.ɵfac = function t_Factory(__ngFactoryType__) { return (__ngFactoryType__ || t)(); };"
+        ));
+    }
+}
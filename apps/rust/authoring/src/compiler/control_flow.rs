@@ -0,0 +1,291 @@
+use oxc_diagnostics::OxcDiagnostic;
+
+use crate::angular::runtime::Identifier;
+use crate::treaty::ast::{AstNode, ForBlock, IfBlock, SwitchBlock};
+
+use super::template::{Local, Read, TemplateCompiler, View};
+use super::{instruction, runtime};
+
+/// Context variables of a `@for` view, available under their own names and through `let` aliases.
+const REPEATER_CONTEXT: [&str; 6] = ["$index", "$first", "$last", "$even", "$odd", "$count"];
+
+impl<'a> TemplateCompiler<'a> {
+    /// `@if`: one embedded view per branch, switched by `ɵɵconditional`.
+    pub(super) fn visit_if(&mut self, view: &mut View, block: &IfBlock) {
+        let has_alias = block.branches.iter().any(|branch| branch.alias.is_some());
+        let alias_value = has_alias.then(|| view.temporary());
+
+        let mut conditions = Vec::new();
+        let mut fallback = "-1".to_string();
+        for branch in &block.branches {
+            let slot = view.allocate_slot();
+            // The aliased value is passed to the branch view as its context.
+            let locals = branch
+                .alias
+                .iter()
+                .map(|alias| Local {
+                    name: alias.source.clone(),
+                    read: Read::Context,
+                })
+                .collect();
+            let name = format!("{}_Conditional_{}", view.name, slot);
+            self.template(view, slot, name, locals, &branch.children);
+
+            match &branch.condition {
+                Some(condition) => {
+                    let condition = self.expression(view, condition);
+                    let condition = match &alias_value {
+                        Some(temporary) => format!("({} = {})", temporary, condition),
+                        None => condition,
+                    };
+                    conditions.push((condition, slot));
+                }
+                None => fallback = slot.to_string(),
+            }
+        }
+        let Some(&(_, first_slot)) = conditions.first() else {
+            return;
+        };
+
+        let mut args = vec![ternary(conditions, fallback)];
+        args.extend(alias_value);
+        view.vars += 1;
+        view.update(first_slot, instruction(Identifier::Conditional, &args));
+    }
+
+    /// `@switch`: one embedded view per case, switched by `ɵɵconditional`.
+    pub(super) fn visit_switch(&mut self, view: &mut View, block: &SwitchBlock) {
+        let value = self.expression(view, &block.expression);
+        let temporary = view.temporary();
+
+        let mut conditions = Vec::new();
+        let mut fallback = "-1".to_string();
+        let mut first_slot = None;
+        for case in &block.cases {
+            let slot = view.allocate_slot();
+            first_slot.get_or_insert(slot);
+            let name = format!("{}_Case_{}", view.name, slot);
+            self.template(view, slot, name, Vec::new(), &case.children);
+
+            match &case.expression {
+                Some(expression) => {
+                    let expression = self.expression(view, expression);
+                    // The switch value is evaluated once, by the first comparison.
+                    let subject = if conditions.is_empty() {
+                        format!("({} = {})", temporary, value)
+                    } else {
+                        temporary.clone()
+                    };
+                    conditions.push((format!("{} === {}", subject, expression), slot));
+                }
+                None => fallback = slot.to_string(),
+            }
+        }
+        let Some(first_slot) = first_slot else {
+            return;
+        };
+
+        view.vars += 1;
+        view.update(
+            first_slot,
+            instruction(Identifier::Conditional, &[ternary(conditions, fallback)]),
+        );
+    }
+
+    /// `@for`: a repeater with its item view and optional `@empty` view.
+    pub(super) fn visit_for(&mut self, view: &mut View, block: &ForBlock) {
+        // The repeater metadata takes the first slot, the item template the next one.
+        let slot = view.allocate_slot();
+        view.allocate_slot();
+        let empty_slot = block.empty.as_ref().map(|_| view.allocate_slot());
+
+        let mut locals = vec![Local {
+            name: block.item.source.clone(),
+            read: Read::Property("$implicit".to_string()),
+        }];
+        locals.extend(REPEATER_CONTEXT.iter().map(|variable| Local {
+            name: variable.to_string(),
+            read: Read::Property(variable.to_string()),
+        }));
+        locals.extend(block.context_aliases.iter().map(|(alias, variable)| Local {
+            name: alias.source.clone(),
+            read: Read::Property(variable.source.clone()),
+        }));
+
+        let (tag_name, attributes) = self.root_element(&block.children);
+        let name = format!("{}_For_{}", view.name, slot + 1);
+        let (function, decls, vars) = self.embedded_view(view, name, locals, &block.children);
+        let (track_by, uses_component) = self.track_function(block);
+
+        let mut args = vec![
+            slot.to_string(),
+            function,
+            decls.to_string(),
+            vars.to_string(),
+            tag_name,
+            attributes,
+            track_by,
+        ];
+        if let (Some(empty), Some(empty_slot)) = (&block.empty, empty_slot) {
+            let (tag_name, attributes) = self.root_element(&empty.children);
+            let name = format!("{}_ForEmpty_{}", view.name, empty_slot);
+            let (function, decls, vars) =
+                self.embedded_view(view, name, Vec::new(), &empty.children);
+            args.extend([
+                uses_component.to_string(),
+                function,
+                decls.to_string(),
+                vars.to_string(),
+            ]);
+            if tag_name != "null" {
+                args.extend([tag_name, attributes]);
+            }
+        } else if uses_component {
+            args.push("true".to_string());
+        }
+        view.creation
+            .push(instruction(Identifier::RepeaterCreate, &args));
+
+        let iterable = self.expression(view, &block.expression);
+        view.vars += 1;
+        view.update(slot, instruction(Identifier::Repeater, &[iterable]));
+    }
+
+    /// Declares the embedded view for `ɵɵtemplate` at `slot`.
+    fn template(
+        &mut self,
+        view: &mut View,
+        slot: usize,
+        name: String,
+        locals: Vec<Local>,
+        nodes: &[AstNode],
+    ) {
+        let (tag_name, attributes) = self.root_element(nodes);
+        let (function, decls, vars) = self.embedded_view(view, name, locals, nodes);
+
+        let mut args = vec![
+            slot.to_string(),
+            function,
+            decls.to_string(),
+            vars.to_string(),
+        ];
+        if tag_name != "null" {
+            args.push(tag_name);
+            if attributes != "null" {
+                args.push(attributes);
+            }
+        }
+        view.creation.push(instruction(Identifier::Template, &args));
+    }
+
+    /// The track function of a `@for` block, and whether it reads from the component.
+    fn track_function(&mut self, block: &ForBlock) -> (String, bool) {
+        let Some(track) = &block.track else {
+            self.diagnostics.push(
+                OxcDiagnostic::error("@for loop must have a \"track\" expression")
+                    .with_label(block.span),
+            );
+            return (runtime(Identifier::RepeaterTrackByIdentity), false);
+        };
+
+        let item = block.item.source.as_str();
+        let index_names: Vec<&str> = block
+            .context_aliases
+            .iter()
+            .filter(|(_, variable)| variable.source == "$index")
+            .map(|(alias, _)| alias.source.as_str())
+            .chain(["$index"])
+            .collect();
+        if track.source == item {
+            return (runtime(Identifier::RepeaterTrackByIdentity), false);
+        }
+        if index_names.contains(&track.source.as_str()) {
+            return (runtime(Identifier::RepeaterTrackByIndex), false);
+        }
+
+        let mut uses_component = false;
+        let code = self.compile(track, &mut |name| {
+            if name == item {
+                "$item".to_string()
+            } else if index_names.contains(&name) {
+                "$index".to_string()
+            } else {
+                uses_component = true;
+                format!("this.{}", name)
+            }
+        });
+
+        let name = format!("_forTrack{}", self.track_functions);
+        self.track_functions += 1;
+        // Reading from the component needs `this`, so an arrow function will not do.
+        self.declarations.push(if uses_component {
+            format!(
+                "function {}($index, $item) {{\n  return {};\n}}",
+                name, code
+            )
+        } else {
+            format!("const {} = ($index, $item) => {};", name, code)
+        });
+        (name, uses_component)
+    }
+}
+
+/// `c0 ? s0 : c1 ? s1 : fallback`
+fn ternary(conditions: Vec<(String, usize)>, fallback: String) -> String {
+    conditions
+        .into_iter()
+        .rev()
+        .fold(fallback, |otherwise, (condition, slot)| {
+            format!("{} ? {} : {}", condition, slot, otherwise)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::compile_source;
+
+    #[test]
+    fn for_block_with_track_and_empty() {
+        let component = compile_source(
+            "const items = [];\n\
+             @for (item of items; track item.id; let i = $index) {<li>{{ i }}: {{ item.name }}</li>} @empty {<p>None</p>}\n",
+        );
+        let code = &component.code;
+        assert!(component.diagnostics.is_empty());
+        assert!(code.contains("const _forTrack0 = ($index, $item) => $item.id;"));
+        assert!(code.contains(
+            "i0.ɵɵrepeaterCreate(0, test_For_1_Template, 2, 2, \"li\", null, _forTrack0, false, test_ForEmpty_2_Template, 2, 0, \"p\", null);"
+        ));
+        assert!(code.contains(
+            "i0.ɵɵtextInterpolate2(\"\", ctx.$index, \": \", ctx.$implicit.name, \"\");"
+        ));
+        assert!(code.contains("i0.ɵɵrepeater(ctx.items);"));
+    }
+
+    #[test]
+    fn switch_block() {
+        let component = compile_source(
+            "const mode = 'a';\n\
+             @switch (mode) { @case ('a') {<b>A</b>} @case ('b') {<u>B</u>} @default {<i>?</i>} }\n",
+        );
+        let code = &component.code;
+        assert!(component.diagnostics.is_empty());
+        assert!(code.contains("i0.ɵɵtemplate(0, test_Case_0_Template, 2, 0, \"b\");"));
+        assert!(code.contains("i0.ɵɵtemplate(2, test_Case_2_Template, 2, 0, \"i\");"));
+        assert!(code.contains(
+            "i0.ɵɵconditional((tmp_0_0 = ctx.mode) === 'a' ? 0 : tmp_0_0 === 'b' ? 1 : 2);"
+        ));
+    }
+
+    #[test]
+    fn if_block_with_alias_and_else() {
+        let component = compile_source(
+            "const user = null;\n\
+             @if (user; as u) {<span>{{ u.name }}</span>} @else {<em>Guest</em>}\n",
+        );
+        let code = &component.code;
+        assert!(component.diagnostics.is_empty());
+        assert!(code.contains("i0.ɵɵconditional((tmp_0_0 = ctx.user) ? 0 : 1, tmp_0_0);"));
+        assert!(code.contains("i0.ɵɵtextInterpolate(ctx.name);"));
+    }
+}
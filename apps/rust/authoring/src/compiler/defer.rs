@@ -0,0 +1,441 @@
+use oxc_diagnostics::OxcDiagnostic;
+
+use crate::angular::runtime::Identifier;
+use crate::treaty::ast::{AstNode, DeferBlock, DeferSubBlock, DeferTrigger, Expression};
+
+use super::script::Script;
+use super::template::{single_root_element, TemplateCompiler, View};
+use super::{instruction, runtime};
+
+/// An element trigger waiting for the slot of the element it references.
+pub(super) struct PendingTrigger {
+    /// Index of the trigger instruction in the creation block.
+    index: usize,
+    identifier: Identifier,
+    reference: Expression,
+}
+
+/// Imported components that are only rendered inside `@defer` blocks, each with the `import()`
+/// loading it on demand.
+///
+/// A component qualifies when its import declaration binds nothing the script reads or the
+/// template renders eagerly, so that the declaration can be dropped and the component split
+/// into its own chunk.
+pub(super) fn lazy_components(nodes: &[AstNode], script: &Script) -> Vec<(String, String)> {
+    let mut eager = Vec::new();
+    let mut deferred_contents = Vec::new();
+    used_components(
+        nodes,
+        &script.imported_names,
+        &mut eager,
+        &mut deferred_contents,
+    );
+    let mut deferred = Vec::new();
+    while let Some(children) = deferred_contents.pop() {
+        used_components(
+            children,
+            &script.imported_names,
+            &mut deferred,
+            &mut deferred_contents,
+        );
+    }
+
+    let mut lazy = Vec::new();
+    for import in &script.imports {
+        let loaders: Option<Vec<_>> = import
+            .specifiers
+            .iter()
+            .map(|(local, _)| {
+                let name = local.as_str();
+                if !deferred.contains(&name)
+                    || eager.contains(&name)
+                    || script.references.contains(local)
+                {
+                    return None;
+                }
+                Some((local.clone(), import.dynamic(local)?))
+            })
+            .collect();
+        lazy.extend(loaders.unwrap_or_default());
+    }
+    lazy
+}
+
+/// Collects the imported components rendered by `nodes`, leaving out the contents of `@defer`
+/// blocks, which are collected into `deferred_contents` instead.
+fn used_components<'n>(
+    nodes: &'n [AstNode],
+    imported_names: &[String],
+    components: &mut Vec<&'n str>,
+    deferred_contents: &mut Vec<&'n [AstNode]>,
+) {
+    let mut children: Vec<&'n [AstNode]> = Vec::new();
+    for node in nodes {
+        match node {
+            AstNode::Element(element) => {
+                let name = element.tag_name.as_str();
+                if imported_names.contains(&element.tag_name) && !components.contains(&name) {
+                    components.push(name);
+                }
+                children.push(&element.children);
+            }
+            AstNode::If(block) => children.extend(
+                block
+                    .branches
+                    .iter()
+                    .map(|branch| branch.children.as_slice()),
+            ),
+            AstNode::For(block) => {
+                children.push(&block.children);
+                children.extend(block.empty.iter().map(|empty| empty.children.as_slice()));
+            }
+            AstNode::Switch(block) => {
+                children.extend(block.cases.iter().map(|case| case.children.as_slice()))
+            }
+            AstNode::Defer(block) => {
+                children.extend(
+                    [&block.placeholder, &block.loading, &block.error]
+                        .into_iter()
+                        .flatten()
+                        .map(|sub_block| sub_block.children.as_slice()),
+                );
+                deferred_contents.push(&block.children);
+            }
+            AstNode::JavaScript(_)
+            | AstNode::Style(_)
+            | AstNode::Text(_)
+            | AstNode::Comment(_)
+            | AstNode::TemplateExpression(_) => {}
+        }
+    }
+    for nodes in children {
+        used_components(nodes, imported_names, components, deferred_contents);
+    }
+}
+
+impl<'a> TemplateCompiler<'a> {
+    /// `@defer`: the main content, `@loading`, `@placeholder` and `@error` each get an embedded
+    /// view, followed by `ɵɵdefer` and its triggers.
+    pub(super) fn visit_defer(&mut self, view: &mut View, block: &DeferBlock) {
+        let main_slot = self.defer_view(view, "Defer", &block.children);
+        let loading_slot = block
+            .loading
+            .as_ref()
+            .map(|loading| self.defer_view(view, "DeferLoading", &loading.children));
+        let placeholder_slot = block
+            .placeholder
+            .as_ref()
+            .map(|placeholder| self.defer_view(view, "DeferPlaceholder", &placeholder.children));
+        let error_slot = block
+            .error
+            .as_ref()
+            .map(|error| self.defer_view(view, "DeferError", &error.children));
+        // The block takes a second slot for its details.
+        let slot = view.allocate_slot();
+        view.allocate_slot();
+
+        let dependency_resolver = self.dependency_resolver(view, slot, block);
+        let loading_config = block.loading.as_ref().and_then(|loading| {
+            let minimum = self.time(&loading.minimum)?;
+            let after = self.time(&loading.after)?;
+            (minimum.is_some() || after.is_some()).then(|| {
+                let config = [minimum, after].map(|time| time.unwrap_or_else(|| "null".into()));
+                self.add_const(format!("[{}]", config.join(", ")))
+            })
+        });
+        let placeholder_config = block.placeholder.as_ref().and_then(|placeholder| {
+            let minimum = self.time(&placeholder.minimum)??;
+            Some(self.add_const(format!("[{}]", minimum)))
+        });
+        let timer_scheduling = (loading_config.is_some() || placeholder_config.is_some())
+            .then(|| runtime(Identifier::DeferEnableTimerScheduling));
+
+        let mut args = vec![
+            Some(slot.to_string()),
+            Some(main_slot.to_string()),
+            dependency_resolver,
+            loading_slot.map(|slot| slot.to_string()),
+            placeholder_slot.map(|slot| slot.to_string()),
+            error_slot.map(|slot| slot.to_string()),
+            loading_config.map(|index| index.to_string()),
+            placeholder_config.map(|index| index.to_string()),
+            timer_scheduling,
+        ];
+        while args.last().is_some_and(Option::is_none) {
+            args.pop();
+        }
+        let args: Vec<_> = args
+            .into_iter()
+            .map(|arg| arg.unwrap_or_else(|| "null".to_string()))
+            .collect();
+        view.creation.push(instruction(Identifier::Defer, &args));
+
+        // Without a trigger of its own, the block loads once the browser is idle.
+        if !block.triggers.iter().any(|trigger| !trigger.prefetch) {
+            view.creation
+                .push(instruction(Identifier::DeferOnIdle, &[]));
+        }
+        let (triggers, prefetch_triggers): (Vec<_>, Vec<_>) =
+            block.triggers.iter().partition(|trigger| !trigger.prefetch);
+        for trigger in triggers.into_iter().chain(prefetch_triggers) {
+            self.visit_trigger(view, slot, block.placeholder.as_ref(), trigger);
+        }
+    }
+
+    fn visit_trigger(
+        &mut self,
+        view: &mut View,
+        slot: usize,
+        placeholder: Option<&DeferSubBlock>,
+        trigger: &DeferTrigger,
+    ) {
+        let prefetch = trigger.prefetch;
+        let pick =
+            |on: Identifier, prefetch_on: Identifier| if prefetch { prefetch_on } else { on };
+        let identifier = match trigger.name.source.as_str() {
+            "when" => {
+                let Some(condition) = &trigger.parameter else {
+                    return self.report_trigger(trigger, "\"when\" trigger must have a condition");
+                };
+                let condition = self.expression(view, condition);
+                let identifier = pick(Identifier::DeferWhen, Identifier::DeferPrefetchWhen);
+                view.vars += 1;
+                view.update(slot, instruction(identifier, &[condition]));
+                return;
+            }
+            "idle" => pick(Identifier::DeferOnIdle, Identifier::DeferPrefetchOnIdle),
+            "immediate" => pick(
+                Identifier::DeferOnImmediate,
+                Identifier::DeferPrefetchOnImmediate,
+            ),
+            "timer" => {
+                let identifier = pick(Identifier::DeferOnTimer, Identifier::DeferPrefetchOnTimer);
+                match self.time(&trigger.parameter) {
+                    Some(Some(delay)) => view.creation.push(instruction(identifier, &[delay])),
+                    Some(None) => {
+                        self.report_trigger(trigger, "\"timer\" trigger must have a delay")
+                    }
+                    None => {}
+                }
+                return;
+            }
+            "hover" => pick(Identifier::DeferOnHover, Identifier::DeferPrefetchOnHover),
+            "interaction" => pick(
+                Identifier::DeferOnInteraction,
+                Identifier::DeferPrefetchOnInteraction,
+            ),
+            "viewport" => pick(
+                Identifier::DeferOnViewport,
+                Identifier::DeferPrefetchOnViewport,
+            ),
+            name => {
+                return self.report_trigger(trigger, &format!("Unrecognized trigger \"{}\"", name))
+            }
+        };
+        if matches!(trigger.name.source.as_str(), "idle" | "immediate") {
+            if trigger.parameter.is_some() {
+                let message = format!("\"{}\" trigger cannot have parameters", trigger.name.source);
+                self.report_trigger(trigger, &message);
+            }
+            view.creation.push(instruction(identifier, &[]));
+            return;
+        }
+
+        match &trigger.parameter {
+            Some(reference) => {
+                view.pending_triggers.push(PendingTrigger {
+                    index: view.creation.len(),
+                    identifier,
+                    reference: reference.clone(),
+                });
+                view.creation.push(String::new());
+            }
+            // Without a reference, the trigger is the root element of the placeholder, one
+            // view down (`-1`).
+            None => match placeholder.and_then(|p| single_root_element(&p.children)) {
+                Some(_) => view.creation.push(instruction(
+                    identifier,
+                    &["0".to_string(), "-1".to_string()],
+                )),
+                None => {
+                    let message = format!(
+                        "\"{}\" trigger with no parameters can only be placed on an @defer that has a @placeholder block with a single root element",
+                        trigger.name.source
+                    );
+                    self.report_trigger(trigger, &message);
+                }
+            },
+        }
+    }
+
+    /// Points the element triggers of `view` at the slots of the elements they reference.
+    pub(super) fn resolve_triggers(&mut self, view: &mut View) {
+        // In reverse, so that removing an unresolved trigger leaves the other indices valid.
+        for trigger in view.pending_triggers.drain(..).rev() {
+            let name = trigger.reference.source.as_str();
+            match view
+                .references
+                .iter()
+                .find(|(reference, _)| reference == name)
+            {
+                Some((_, slot)) => {
+                    view.creation[trigger.index] =
+                        instruction(trigger.identifier, &[slot.to_string()]);
+                }
+                None => {
+                    view.creation.remove(trigger.index);
+                    self.diagnostics.push(
+                        OxcDiagnostic::error(format!("Trigger cannot find reference \"{}\"", name))
+                            .with_label(trigger.reference.span),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Declares one of the views of a `@defer` block, returning its slot.
+    fn defer_view(&mut self, view: &mut View, kind: &str, nodes: &[AstNode]) -> usize {
+        let slot = view.allocate_slot();
+        let name = format!("{}_{}_{}", view.name, kind, slot);
+        let (function, decls, vars) = self.embedded_view(view, name, Vec::new(), nodes);
+        view.creation.push(instruction(
+            Identifier::Template,
+            &[
+                slot.to_string(),
+                function,
+                decls.to_string(),
+                vars.to_string(),
+            ],
+        ));
+        slot
+    }
+
+    /// Declares the function loading the lazy components of the block, returning its name.
+    fn dependency_resolver(
+        &mut self,
+        view: &View,
+        slot: usize,
+        block: &DeferBlock,
+    ) -> Option<String> {
+        let mut components = Vec::new();
+        used_components(
+            &block.children,
+            &self.script.imported_names,
+            &mut components,
+            &mut Vec::new(),
+        );
+        let mut loaders: Vec<&str> = Vec::new();
+        for component in components {
+            let loader = self
+                .lazy_components
+                .iter()
+                .find(|(name, _)| name == component);
+            if let Some((_, loader)) = loader {
+                if !loaders.contains(&loader.as_str()) {
+                    loaders.push(loader);
+                }
+            }
+        }
+        if loaders.is_empty() {
+            return None;
+        }
+
+        let name = format!("{}_Defer_{}_DepsFn", view.name, slot);
+        self.declarations
+            .push(format!("const {} = () => [{}];", name, loaders.join(", ")));
+        Some(name)
+    }
+
+    /// Milliseconds of a `minimum`, `after` or `timer` parameter, e.g. `500ms` or `1.5s`.
+    ///
+    /// `None` when the value is invalid, which is reported.
+    fn time(&mut self, parameter: &Option<Expression>) -> Option<Option<String>> {
+        let Some(parameter) = parameter else {
+            return Some(None);
+        };
+        let value = parameter.source.as_str();
+        let (number, multiplier) = match value.strip_suffix("ms") {
+            Some(number) => (number, 1.0),
+            None => match value.strip_suffix('s') {
+                Some(number) => (number, 1000.0),
+                None => (value, 1.0),
+            },
+        };
+        match number.trim().parse::<f64>() {
+            Ok(number) if number >= 0.0 => Some(Some((number * multiplier).round().to_string())),
+            _ => {
+                self.diagnostics.push(
+                    OxcDiagnostic::error(format!("Could not parse time value \"{}\"", value))
+                        .with_label(parameter.span),
+                );
+                None
+            }
+        }
+    }
+
+    fn report_trigger(&mut self, trigger: &DeferTrigger, message: &str) {
+        self.diagnostics
+            .push(OxcDiagnostic::error(message.to_string()).with_label(trigger.span));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::compile_source;
+
+    #[test]
+    fn defer_with_sub_blocks_and_triggers() {
+        let component = compile_source(
+            "import Chart from \"./chart.treaty\";\n\
+             export const ready = signal(false);\n\
+             @defer (on viewport; prefetch on idle; when ready()) {<Chart></Chart>}\
+              @placeholder (minimum 500ms) {<p>Soon</p>}\
+              @loading (after 100ms; minimum 1s) {<p>Loading</p>}\
+              @error {<p>Failed</p>}\n",
+        );
+        let code = &component.code;
+        assert!(component.diagnostics.is_empty());
+        // Only loaded by the block, so not imported statically.
+        assert!(!code.contains("import Chart"));
+        assert!(code.contains(
+            "const test_Defer_4_DepsFn = () => [import(\"./chart.treaty\").then(m => m.default)];"
+        ));
+        assert!(code.contains("consts: [[1000, 100], [500]]"));
+        assert!(code.contains(
+            "i0.ɵɵdefer(4, 0, test_Defer_4_DepsFn, 1, 2, 3, 0, 1, i0.ɵɵdeferEnableTimerScheduling);"
+        ));
+        assert!(code.contains("i0.ɵɵdeferOnViewport(0, -1);"));
+        assert!(code.contains("i0.ɵɵdeferPrefetchOnIdle();"));
+        assert!(code.contains("i0.ɵɵdeferWhen(ctx.ready());"));
+    }
+
+    #[test]
+    fn trigger_problems_are_reported() {
+        let component = compile_source("@defer (on explode; on interaction(missing)) {<p>x</p>}\n");
+        let messages: Vec<_> = component
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "Unrecognized trigger \"explode\"",
+                "Trigger cannot find reference \"missing\""
+            ]
+        );
+    }
+
+    #[test]
+    fn element_trigger_declared_after_the_block() {
+        let component = compile_source(
+            "@defer (on timer(2s), hover(trigger)) {<p>x</p>}\n<button #trigger>Load</button>\n",
+        );
+        let code = &component.code;
+        assert!(component.diagnostics.is_empty());
+        assert!(code.contains("i0.ɵɵdefer(1, 0);"));
+        assert!(code.contains("i0.ɵɵdeferOnTimer(2000);"));
+        assert!(code.contains("i0.ɵɵdeferOnHover(3);"));
+        assert!(code.contains("i0.ɵɵelementStart(3, \"button\", null, 0);"));
+    }
+}
@@ -0,0 +1,357 @@
+use std::ops::Range;
+
+use oxc_allocator::Allocator;
+use oxc_ast::{
+    ast::{ArrowFunctionExpression, IdentifierReference, ObjectProperty, ThisExpression},
+    syntax_directed_operations::BoundNames,
+    visit::walk,
+    Visit,
+};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_parser::Parser;
+use oxc_span::{GetSpan, SourceType, Span};
+
+use crate::treaty::ast::Expression;
+
+/// Binds a pipe by its name to its compiled arguments, the value first.
+pub type PipeBinding<'p> = dyn FnMut(&str, Vec<String>) -> String + 'p;
+
+/// Compiles a template expression into JavaScript for a template function.
+///
+/// Every free identifier is passed to `resolve`, which returns what it reads from in the
+/// generated code, e.g. `ctx.name` for a component binding. Pipes, `value | name:arg`, are passed
+/// to `pipe` with their name and compiled arguments, the value first; they are reported where
+/// `pipe` is `None`.
+pub fn compile_expression(
+    expression: &Expression,
+    resolve: &mut dyn FnMut(&str) -> String,
+    mut pipe: Option<&mut PipeBinding>,
+) -> Result<String, Vec<OxcDiagnostic>> {
+    // Padded to its offset, so diagnostics point into the `.treaty` source.
+    let offset = expression.span.start as usize;
+    let mut source = " ".repeat(offset) + &expression.source;
+
+    // Innermost first, each compiled pipe leaving a placeholder of the same length so the spans
+    // of the rest stay put.
+    let mut pipes = Vec::new();
+    while let Some(segment) = pipe_segment(&source, offset) {
+        let span = Span::new(segment.start as u32, segment.end as u32);
+        let Some(pipe) = pipe.as_deref_mut() else {
+            return Err(vec![OxcDiagnostic::error(
+                "Pipes cannot be used in this expression",
+            )
+            .with_label(span)]);
+        };
+        let code = compile_pipes(&source, segment.clone(), resolve, pipe, &pipes)?;
+        let placeholder = format!("${}", pipes.len());
+        if placeholder.len() > segment.len() {
+            return Err(vec![OxcDiagnostic::error(
+                "Too many pipes in this expression",
+            )
+            .with_label(span)]);
+        }
+        let placeholder = format!("{:_<width$}", placeholder, width = segment.len());
+        source.replace_range(segment, &placeholder);
+        pipes.push(code);
+    }
+    compile_javascript(&source, offset..source.len(), resolve, &pipes)
+}
+
+/// Compiles `source[range]` as JavaScript, replacing the placeholders of `pipes` with their code.
+fn compile_javascript(
+    source: &str,
+    range: Range<usize>,
+    resolve: &mut dyn FnMut(&str) -> String,
+    pipes: &[String],
+) -> Result<String, Vec<OxcDiagnostic>> {
+    let offset = range.start;
+    let source = " ".repeat(offset) + &source[range];
+    let allocator = Allocator::default();
+    let parsed = Parser::new(&allocator, &source, SourceType::default()).parse_expression()?;
+    let rest = &source[parsed.span().end as usize..];
+    if !rest.is_empty() {
+        let start = source.len() - rest.trim_start().len();
+        return Err(vec![OxcDiagnostic::error("Unexpected token")
+            .with_label(Span::new(start as u32, source.len() as u32))]);
+    }
+
+    let mut collector = ReferenceCollector::default();
+    collector.visit_expression(&parsed);
+    collector
+        .references
+        .sort_by_key(|reference| reference.span.start);
+
+    let mut code = String::with_capacity(source.len() - offset + 8);
+    let mut position = offset;
+    for reference in &collector.references {
+        code.push_str(&source[position..reference.span.start as usize]);
+        let pipe = reference
+            .name
+            .as_deref()
+            .and_then(|name| name.strip_prefix('$'))
+            .and_then(|index| index.trim_end_matches('_').parse::<usize>().ok())
+            .and_then(|index| pipes.get(index));
+        let resolved = match (&reference.name, pipe) {
+            (_, Some(pipe)) => pipe.clone(),
+            (Some(name), None) => resolve(name),
+            (None, None) => "ctx".to_string(),
+        };
+        match &reference.name {
+            Some(name) if reference.shorthand => code.push_str(&format!("{}: {}", name, resolved)),
+            _ => code.push_str(&resolved),
+        }
+        position = reference.span.end as usize;
+    }
+    code.push_str(&source[position..]);
+    Ok(code)
+}
+
+/// `value | name:arg:arg | name`, compiling the pipes from left to right.
+fn compile_pipes(
+    source: &str,
+    segment: Range<usize>,
+    resolve: &mut dyn FnMut(&str) -> String,
+    pipe: &mut PipeBinding,
+    pipes: &[String],
+) -> Result<String, Vec<OxcDiagnostic>> {
+    let bars = code_chars(source, segment.clone())
+        .into_iter()
+        .filter(|&(index, c, depth)| depth == 0 && c == '|' && is_pipe(source, index))
+        .map(|(index, _, _)| index);
+    let mut parts = split(segment, bars).into_iter();
+    let head = parts.next().unwrap_or_default();
+    let mut value = compile_javascript(source, trim(source, head), resolve, pipes)?;
+
+    for part in parts {
+        // Colons of conditionals in the arguments belong to them.
+        let mut conditionals = 0;
+        let colons = code_chars(source, part.clone())
+            .into_iter()
+            .filter(|&(index, c, depth)| {
+                if depth > 0 {
+                    return false;
+                }
+                let next = source[index + 1..].chars().next();
+                match c {
+                    '?' if !matches!(next, Some('.' | '?')) && !source[..index].ends_with('?') => {
+                        conditionals += 1;
+                        false
+                    }
+                    ':' if conditionals > 0 => {
+                        conditionals -= 1;
+                        false
+                    }
+                    ':' => true,
+                    _ => false,
+                }
+            })
+            .map(|(index, _, _)| index)
+            .collect::<Vec<_>>();
+        let mut ranges = split(part.clone(), colons).into_iter();
+        let name = trim(source, ranges.next().unwrap_or_default());
+        let is_identifier = !name.is_empty()
+            && source[name.clone()].chars().enumerate().all(|(i, c)| {
+                c == '_' || c == '$' || c.is_ascii_alphabetic() || i > 0 && c.is_ascii_digit()
+            });
+        if !is_identifier {
+            let label = if name.is_empty() { part } else { name };
+            return Err(vec![OxcDiagnostic::error("Expected a pipe name")
+                .with_label(Span::new(label.start as u32, label.end as u32))]);
+        }
+
+        let mut args = vec![value];
+        for arg in ranges {
+            args.push(compile_javascript(
+                source,
+                trim(source, arg),
+                resolve,
+                pipes,
+            )?);
+        }
+        value = pipe(&source[name], args);
+    }
+    Ok(value)
+}
+
+/// An open bracket, or the whole expression, while looking for pipes.
+struct Frame {
+    /// Start of the current argument, element or property value.
+    start: usize,
+    /// In an object literal, where values start after the first colon.
+    object: bool,
+    key: bool,
+    pipe: bool,
+}
+
+/// The first of the innermost segments with pipes: the contents of brackets, an argument or
+/// element between commas, a property value or the whole expression, trimmed.
+fn pipe_segment(source: &str, start: usize) -> Option<Range<usize>> {
+    let mut frames = vec![Frame {
+        start,
+        object: false,
+        key: false,
+        pipe: false,
+    }];
+    let mut innermost: Option<(usize, Range<usize>)> = None;
+    let mut close = |frame: &Frame, depth: usize, end: usize| {
+        if frame.pipe && !matches!(&innermost, Some((d, _)) if *d >= depth) {
+            innermost = Some((depth, trim(source, frame.start..end)));
+        }
+    };
+
+    for (index, c, _) in code_chars(source, start..source.len()) {
+        let depth = frames.len() - 1;
+        let frame = frames.last_mut().unwrap();
+        match c {
+            '(' | '[' | '{' => frames.push(Frame {
+                start: index + 1,
+                object: c == '{',
+                key: c == '{',
+                pipe: false,
+            }),
+            ')' | ']' | '}' if depth > 0 => {
+                let frame = frames.pop().unwrap();
+                close(&frame, depth, index);
+            }
+            ',' => {
+                close(frame, depth, index);
+                frame.start = index + 1;
+                frame.key = frame.object;
+                frame.pipe = false;
+            }
+            ':' if frame.key => {
+                frame.start = index + 1;
+                frame.key = false;
+            }
+            '|' if is_pipe(source, index) => frame.pipe = true,
+            _ => {}
+        }
+    }
+    while let Some(frame) = frames.pop() {
+        close(&frame, frames.len(), source.len());
+    }
+    innermost.map(|(_, segment)| segment)
+}
+
+/// The characters of `source[range]` outside of strings, with their bracket depth relative to
+/// the start of the range. Brackets are at the depth outside of them.
+fn code_chars(source: &str, range: Range<usize>) -> Vec<(usize, char, usize)> {
+    let mut chars = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in source[range.clone()].char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '\'' | '"' | '`' => {
+                quote = Some(c);
+                continue;
+            }
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        chars.push((range.start + index, c, depth));
+        if matches!(c, '(' | '[' | '{') {
+            depth += 1;
+        }
+    }
+    chars
+}
+
+/// A `|` that is not part of `||` or `|=`.
+fn is_pipe(source: &str, index: usize) -> bool {
+    let bytes = source.as_bytes();
+    bytes[index] == b'|'
+        && !matches!(bytes.get(index + 1), Some(b'|' | b'='))
+        && (index == 0 || bytes[index - 1] != b'|')
+}
+
+/// Splits `range` at each of `separators`, dropping them.
+fn split(range: Range<usize>, separators: impl IntoIterator<Item = usize>) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = range.start;
+    for separator in separators {
+        ranges.push(start..separator);
+        start = separator + 1;
+    }
+    ranges.push(start..range.end);
+    ranges
+}
+
+fn trim(source: &str, range: Range<usize>) -> Range<usize> {
+    let text = &source[range.clone()];
+    let start = range.start + (text.len() - text.trim_start().len());
+    start..start + text.trim().len()
+}
+
+struct Reference {
+    span: Span,
+    /// `None` for `this`.
+    name: Option<String>,
+    /// A shorthand property, `{ name }`.
+    shorthand: bool,
+}
+
+#[derive(Default)]
+struct ReferenceCollector {
+    references: Vec<Reference>,
+    /// Parameters of the enclosing arrow functions.
+    locals: Vec<String>,
+}
+
+impl ReferenceCollector {
+    fn is_free(&self, name: &str) -> bool {
+        name != "undefined" && !self.locals.iter().any(|local| local == name)
+    }
+}
+
+impl<'a> Visit<'a> for ReferenceCollector {
+    fn visit_identifier_reference(&mut self, it: &IdentifierReference<'a>) {
+        if self.is_free(&it.name) {
+            self.references.push(Reference {
+                span: it.span,
+                name: Some(it.name.to_string()),
+                shorthand: false,
+            });
+        }
+    }
+
+    fn visit_this_expression(&mut self, it: &ThisExpression) {
+        self.references.push(Reference {
+            span: it.span,
+            name: None,
+            shorthand: false,
+        });
+    }
+
+    fn visit_object_property(&mut self, it: &ObjectProperty<'a>) {
+        if let (true, oxc_ast::ast::Expression::Identifier(ident)) = (it.shorthand, &it.value) {
+            if self.is_free(&ident.name) {
+                self.references.push(Reference {
+                    span: ident.span,
+                    name: Some(ident.name.to_string()),
+                    shorthand: true,
+                });
+            }
+            return;
+        }
+        walk::walk_object_property(self, it);
+    }
+
+    fn visit_arrow_function_expression(&mut self, it: &ArrowFunctionExpression<'a>) {
+        let depth = self.locals.len();
+        it.params
+            .bound_names(&mut |ident| self.locals.push(ident.name.to_string()));
+        walk::walk_arrow_function_expression(self, it);
+        self.locals.truncate(depth);
+    }
+}
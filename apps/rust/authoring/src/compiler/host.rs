@@ -0,0 +1,174 @@
+use oxc_diagnostics::OxcDiagnostic;
+
+use crate::angular::runtime::Identifier;
+use crate::html::{Attribute, AttributeKind};
+
+use super::binding::{identifier_part, value};
+use super::expression::compile_expression;
+use super::template::StaticAttributes;
+use super::{instruction, runtime, string_literal};
+
+/// The host element fields of a component or directive definition.
+pub struct CompiledHost {
+    /// Items of `hostAttrs`, the static attributes set on the host element.
+    pub attributes: Vec<String>,
+    /// Binding slots used by `hostBindings`.
+    pub vars: usize,
+    /// `hostBindings`, when anything is bound or listened to.
+    pub function: Option<String>,
+    pub diagnostics: Vec<OxcDiagnostic>,
+}
+
+/// Compiles host bindings written as template attributes, e.g. the `host` map of `@Component`
+/// with keys such as `class`, `[title]` or `(click)`. Names are read from the instance, `ctx`.
+pub fn compile_host(name: &str, bindings: &[Attribute]) -> CompiledHost {
+    let mut statics = StaticAttributes::default();
+    let mut creation = Vec::new();
+    let mut update = Vec::new();
+    let mut vars = 0;
+    let mut diagnostics = Vec::new();
+
+    // Styling first, as in templates.
+    let order = |kind: &AttributeKind| match kind {
+        AttributeKind::Property(name) if name == "style" || name == "class" => 0,
+        AttributeKind::Style { .. } | AttributeKind::Class(_) => 1,
+        _ => 2,
+    };
+    let mut bindings: Vec<_> = bindings.iter().collect();
+    bindings.sort_by_key(|binding| order(&binding.kind));
+
+    for binding in bindings {
+        let mut compile = |binding| compile_binding(binding, &mut diagnostics).0;
+        let (identifier, args, binding_vars) = match &binding.kind {
+            AttributeKind::Static => {
+                statics.push(binding);
+                continue;
+            }
+            AttributeKind::Event(event) => {
+                let (handler, uses_event) = compile_binding(binding, &mut diagnostics);
+                creation.push(listener(name, event, handler, uses_event));
+                continue;
+            }
+            AttributeKind::Property(property) if property == "style" => {
+                (Identifier::StyleMap, vec![compile(binding)], 2)
+            }
+            AttributeKind::Property(property) if property == "class" => {
+                (Identifier::ClassMap, vec![compile(binding)], 2)
+            }
+            AttributeKind::Style { name, unit } => {
+                let mut args = vec![string_literal(name), compile(binding)];
+                args.extend(unit.as_deref().map(string_literal));
+                (Identifier::StyleProp, args, 2)
+            }
+            AttributeKind::Class(name) => (
+                Identifier::ClassProp,
+                vec![string_literal(name), compile(binding)],
+                2,
+            ),
+            AttributeKind::Property(property) => (
+                Identifier::HostProperty,
+                vec![string_literal(property), compile(binding)],
+                1,
+            ),
+            AttributeKind::Attribute(attribute) => (
+                Identifier::Attribute,
+                vec![string_literal(attribute), compile(binding)],
+                1,
+            ),
+            AttributeKind::Animation(animation) => {
+                let value = if binding.value.trim().is_empty() {
+                    "undefined".to_string()
+                } else {
+                    compile(binding)
+                };
+                let name = string_literal(&format!("@{}", animation));
+                (Identifier::SyntheticHostProperty, vec![name, value], 1)
+            }
+            AttributeKind::TwoWay(_) | AttributeKind::Reference(_) | AttributeKind::Spread(_) => {
+                diagnostics.push(
+                    OxcDiagnostic::error(format!(
+                        "\"{}\" is not supported in host bindings",
+                        binding.name
+                    ))
+                    .with_label(binding.span),
+                );
+                continue;
+            }
+        };
+        vars += binding_vars;
+        update.push(instruction(identifier, &args));
+    }
+
+    let function = (!creation.is_empty() || !update.is_empty()).then(|| {
+        let mut function = format!("function {}_HostBindings(rf, ctx) {{", name);
+        for (mode, instructions) in [(1, creation), (2, update)] {
+            if instructions.is_empty() {
+                continue;
+            }
+            function.push_str(&format!("\n    if (rf & {}) {{", mode));
+            for instruction in instructions {
+                function.push_str(&format!("\n      {}", instruction));
+            }
+            function.push_str("\n    }");
+        }
+        function.push_str("\n  }");
+        function
+    });
+
+    CompiledHost {
+        attributes: statics.into_values(),
+        vars,
+        function,
+        diagnostics,
+    }
+}
+
+/// The value of a binding as code reading from `ctx`, and whether it uses `$event`.
+fn compile_binding(binding: &Attribute, diagnostics: &mut Vec<OxcDiagnostic>) -> (String, bool) {
+    let expression = value(binding);
+    let mut uses_event = false;
+    let mut resolve = |name: &str| {
+        if name == "$event" {
+            uses_event = true;
+            name.to_string()
+        } else {
+            format!("ctx.{}", name)
+        }
+    };
+    let code = match compile_expression(&expression, &mut resolve, None) {
+        Ok(code) => code,
+        Err(errors) => {
+            diagnostics.extend(errors);
+            expression.source
+        }
+    };
+    (code, uses_event)
+}
+
+/// Listens to `event` on the host, or on a global target given as `window:resize`.
+fn listener(name: &str, event: &str, handler: String, uses_event: bool) -> String {
+    let (target, event_name) = match event.split_once(':') {
+        Some(("window", event)) => (Some(Identifier::ResolveWindow), event),
+        Some(("document", event)) => (Some(Identifier::ResolveDocument), event),
+        Some(("body", event)) => (Some(Identifier::ResolveBody), event),
+        _ => (None, event),
+    };
+    let function = format!(
+        "function {}_{}_HostBindingHandler({}) {{ return {}; }}",
+        name,
+        identifier_part(event),
+        if uses_event { "$event" } else { "" },
+        handler
+    );
+    let identifier = if event_name.starts_with('@') {
+        Identifier::SyntheticHostListener
+    } else {
+        Identifier::Listener
+    };
+    let mut args = vec![string_literal(event_name), function];
+    if let Some(target) = target {
+        args.push("false".to_string());
+        args.push(runtime(target));
+    }
+    instruction(identifier, &args)
+}
@@ -0,0 +1,71 @@
+use oxc_ast::ast::Program;
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_semantic::Semantic;
+use oxc_span::Span;
+
+use crate::angular::inject::{inject_calls as find_inject_calls, InjectionContext};
+use crate::angular::runtime::{Identifier, InjectFlags};
+
+use super::runtime;
+
+/// Rewrites the `inject()` calls of the script, run by the component factory, into
+/// `ɵɵdirectiveInject`, as edits of the source: each span with its replacement.
+///
+/// Only calls at the top level of the script run while the component is created; those nested in
+/// functions and classes are reported.
+pub(super) fn inject_calls<'a>(
+    semantic: &Semantic<'a>,
+    program: &Program<'a>,
+) -> (Vec<(Span, String)>, Vec<OxcDiagnostic>) {
+    let (calls, diagnostics) = find_inject_calls(
+        semantic.symbols(),
+        program,
+        InjectionContext::Factory {
+            inject: Identifier::DirectiveInject,
+            flags: InjectFlags::Default as u8,
+        },
+    );
+    let mut edits = Vec::new();
+    for call in calls {
+        edits.push((call.callee, runtime(call.inject)));
+        // Everything after the token, up to the closing parenthesis.
+        let rest = Span::new(call.token.end, call.span.end - 1);
+        let flags = if call.flags == InjectFlags::Default as u8 {
+            String::new()
+        } else {
+            format!(", {}", call.flags)
+        };
+        edits.push((rest, flags));
+    }
+    (edits, diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::compile_source;
+
+    #[test]
+    fn top_level_calls_are_rewritten() {
+        let component = compile_source(
+            "import { inject } from '@angular/core';\n\
+             const http = inject(Http, { optional: true });\n\
+             function later() { return inject(Router); }\n\
+             <p>x</p>\n",
+        );
+        assert!(component
+            .code
+            .contains("const http = i0.ɵɵdirectiveInject(Http, 8);"));
+        assert!(component
+            .code
+            .contains("function later() { return inject(Router); }"));
+        let messages: Vec<_> = component
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            ["`inject()` must be called from an injection context"]
+        );
+    }
+}
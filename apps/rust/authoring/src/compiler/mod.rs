@@ -0,0 +1,58 @@
+//! Compiles a parsed `.treaty` file into an Angular Ivy standalone component, replacing the
+//! `@angular/compiler` based pipeline of the Vite plugin.
+
+mod binding;
+mod component;
+mod control_flow;
+mod defer;
+mod expression;
+mod host;
+mod inject;
+mod naming;
+mod query;
+mod script;
+mod selector;
+mod shim;
+mod signals;
+mod spread;
+mod template;
+
+pub use self::component::{compile, compile_inline_template, minify_css, CompiledComponent};
+pub use self::host::{compile_host, CompiledHost};
+pub use self::selector::parse_selector;
+pub use self::shim::shim_css;
+pub use self::template::CompiledTemplate;
+
+use crate::angular::runtime::Identifier;
+
+/// Namespace import of `@angular/core` in the compiled module.
+const CORE: &str = "i0";
+
+/// A reference to a runtime symbol, e.g. `i0.ɵɵelementStart`.
+fn runtime(identifier: Identifier) -> String {
+    format!("{}.{}", CORE, identifier.name())
+}
+
+/// A call to a runtime instruction as a statement, e.g. `i0.ɵɵtext(1, "Hello");`.
+fn instruction(identifier: Identifier, args: &[String]) -> String {
+    format!("{}({});", runtime(identifier), args.join(", "))
+}
+
+fn string_literal(value: &str) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+/// Lexes, parses and compiles a treaty file, as `main` does.
+#[cfg(test)]
+fn compile_source(source_text: &str) -> CompiledComponent {
+    use crate::treaty::lexer::Lexer;
+    use crate::treaty::parser::Parser;
+
+    let mut lexer = Lexer::new(source_text);
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token() {
+        tokens.push(token);
+    }
+    let ast = Parser::new(tokens).parse();
+    compile(source_text, &ast, "src/pages/test.treaty")
+}
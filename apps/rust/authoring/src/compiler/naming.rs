@@ -0,0 +1,105 @@
+use std::path::Path;
+
+/// `src/pages/index.treaty` -> `index`, `src/component/logo.treaty` -> `component-logo`.
+pub fn extract_file_name(file_path: &str) -> String {
+    let path = Path::new(file_path);
+    let file_name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    match path
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .map(|dir| dir.to_string_lossy())
+    {
+        Some(folder) if folder != "pages" => format!("{}-{}", folder, file_name),
+        _ => file_name,
+    }
+}
+
+/// Splits on `.` into parts, and each part on runs of anything that is not alphanumeric.
+fn words(name: &str) -> Vec<Vec<&str>> {
+    name.split('.')
+        .map(|part| {
+            part.split(|c: char| !c.is_ascii_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .collect()
+        })
+        .collect()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => {
+            first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+        }
+        None => String::new(),
+    }
+}
+
+pub fn to_pascal_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .flatten()
+        .map(|word| capitalize(word))
+        .collect()
+}
+
+pub fn to_camel_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(index, word)| {
+            if index == 0 {
+                word.to_ascii_lowercase()
+            } else {
+                capitalize(word)
+            }
+        })
+        .collect()
+}
+
+/// Words JavaScript reserves in modules, which cannot name a function.
+#[rustfmt::skip]
+const RESERVED_WORDS: [&str; 46] = [
+    "await", "break", "case", "catch", "class", "const", "continue", "debugger", "default",
+    "delete", "do", "else", "enum", "export", "extends", "false", "finally", "for", "function",
+    "if", "implements", "import", "in", "instanceof", "interface", "let", "new", "null",
+    "package", "private", "protected", "public", "return", "static", "super", "switch", "this",
+    "throw", "true", "try", "typeof", "var", "void", "while", "with", "yield",
+];
+
+/// `name`, made of ASCII letters and digits as the cases above leave it, as an identifier:
+/// prefixed with `_` when it is empty, starts with a digit or is a reserved word.
+pub fn to_identifier(name: &str) -> String {
+    if name.is_empty()
+        || name.starts_with(|c: char| c.is_ascii_digit())
+        || RESERVED_WORDS.contains(&name)
+    {
+        format!("_{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+pub fn to_hyphen_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && previous.is_some_and(|p| p.is_ascii_lowercase()) {
+            result.push('-');
+        }
+        if c.is_whitespace() || c == '_' || c == '.' || c == '-' {
+            if !result.ends_with('-') {
+                result.push('-');
+            }
+        } else {
+            result.push(c.to_ascii_lowercase());
+        }
+        previous = Some(c);
+    }
+    result
+}
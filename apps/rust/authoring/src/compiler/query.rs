@@ -0,0 +1,97 @@
+use crate::angular::runtime::{Identifier, QueryFlags};
+
+use super::script::Script;
+use super::signals::{Predicate, Query};
+use super::{instruction, string_literal};
+
+/// The `viewQuery` and `contentQueries` functions of a component.
+pub struct QueryFunctions {
+    /// Predicates of reference queries, declared once in the module, e.g. `const _c0 = ["a"];`.
+    pub declarations: Vec<String>,
+    pub content_queries: Option<String>,
+    pub view_query: Option<String>,
+}
+
+/// Creates each query signal in creation mode and refreshes them all in update mode, where
+/// `ɵɵqueryAdvance` moves on to the query that follows.
+pub fn query_functions(name: &str, script: &Script) -> QueryFunctions {
+    let mut declarations = Vec::new();
+    let mut render = |view: bool| {
+        let queries: Vec<_> = script
+            .queries
+            .iter()
+            .filter(|query| query.view == view)
+            .collect();
+        if queries.is_empty() {
+            return None;
+        }
+
+        let creation: Vec<_> = queries
+            .iter()
+            .map(|query| {
+                let predicate = match &query.predicate {
+                    Predicate::References(references) => {
+                        let constant = format!("_c{}", declarations.len());
+                        let references: Vec<_> = references
+                            .iter()
+                            .map(|reference| string_literal(reference))
+                            .collect();
+                        declarations.push(format!(
+                            "const {} = [{}];",
+                            constant,
+                            references.join(", ")
+                        ));
+                        constant
+                    }
+                    Predicate::Type(predicate) => predicate.clone(),
+                };
+                let mut args = vec![format!("ctx.{}", query.name), predicate, flags(query)];
+                args.extend(query.read.clone());
+                if view {
+                    instruction(Identifier::ViewQuerySignal, &args)
+                } else {
+                    args.insert(0, "dirIndex".to_string());
+                    instruction(Identifier::ContentQuerySignal, &args)
+                }
+            })
+            .collect();
+        let advance = match queries.len() {
+            1 => Vec::new(),
+            count => vec![count.to_string()],
+        };
+        let update = instruction(Identifier::QueryAdvance, &advance);
+
+        let (function, parameters) = if view {
+            (format!("{}_Query", name), "rf, ctx")
+        } else {
+            (format!("{}_ContentQueries", name), "rf, ctx, dirIndex")
+        };
+        let mut source = format!("function {}({}) {{", function, parameters);
+        source.push_str("\n    if (rf & 1) {");
+        for instruction in creation {
+            source.push_str(&format!("\n      {}", instruction));
+        }
+        source.push_str(&format!(
+            "\n    }}\n    if (rf & 2) {{\n      {}\n    }}\n  }}",
+            update
+        ));
+        Some(source)
+    };
+
+    let content_queries = render(false);
+    let view_query = render(true);
+    QueryFunctions {
+        declarations,
+        content_queries,
+        view_query,
+    }
+}
+
+/// Signal queries only notify when their results change, and are never static.
+fn flags(query: &Query) -> String {
+    let mut flags = QueryFlags::EmitDistinctChangesOnly as u8;
+    if query.descendants {
+        flags |= QueryFlags::Descendants as u8;
+    }
+    flags.to_string()
+}
@@ -0,0 +1,272 @@
+use oxc_allocator::Allocator;
+use oxc_ast::{
+    ast::{IdentifierReference, ImportDeclarationSpecifier, Statement},
+    syntax_directed_operations::BoundNames,
+    Visit,
+};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_parser::Parser;
+use oxc_semantic::SemanticBuilder;
+use oxc_span::{GetSpan, SourceType, Span};
+
+use crate::treaty::ast::{Ast, AstNode};
+
+use super::inject::inject_calls;
+use super::signals::{angular_calls, inputs_and_outputs, Input, Output, Query};
+use super::spread::{spread_objects, SpreadObject};
+
+/// The component script: every JavaScript chunk of a `.treaty` file, analysed as one module.
+pub struct Script {
+    /// Import declarations, hoisted out of the component function.
+    pub imports: Vec<Import>,
+    /// Local names bound by the imports, which the template may render as components. Those of
+    /// `@angular/core` are left out, as it exports no components: its `input` is no `<input>`.
+    pub imported_names: Vec<String>,
+    /// Names read by the script itself, outside of its imports.
+    pub references: Vec<String>,
+    /// Top-level bindings, returned from the component function as the template context.
+    pub bindings: Vec<String>,
+    /// Top-level objects with statically known keys, which the template may spread.
+    pub objects: Vec<(String, SpreadObject)>,
+    /// Signal inputs, including those of models.
+    pub inputs: Vec<Input>,
+    /// Outputs, including the `Change` events of models.
+    pub outputs: Vec<Output>,
+    /// Signal view and content queries.
+    pub queries: Vec<Query>,
+    /// The script without its imports, placed inside the component function.
+    pub body: String,
+}
+
+pub struct Import {
+    /// Source of the whole declaration.
+    pub source: String,
+    /// The module specifier, e.g. `./logo.treaty`.
+    pub module: String,
+    /// Local names, each with the export it binds; `None` for a namespace import.
+    pub specifiers: Vec<(String, Option<String>)>,
+}
+
+impl Import {
+    /// `import("./logo.treaty").then(m => m.default)`, loading `local` on demand.
+    pub fn dynamic(&self, local: &str) -> Option<String> {
+        let (_, export) = self.specifiers.iter().find(|(name, _)| name == local)?;
+        Some(format!(
+            "import({}).then(m => m.{})",
+            super::string_literal(&self.module),
+            export.as_deref()?
+        ))
+    }
+}
+
+impl Script {
+    pub fn new(source_text: &str, ast: &Ast) -> (Self, Vec<OxcDiagnostic>) {
+        let chunks: Vec<(&str, Span)> = ast
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                AstNode::JavaScript(js) => Some((js.code.as_str(), js.span)),
+                _ => None,
+            })
+            .collect();
+
+        // Laid out at the original offsets, so spans match the `.treaty` source. Whitespace between
+        // chunks is kept as is, anything else (markup, styles) is blanked out.
+        let mut source = String::with_capacity(source_text.len());
+        for (code, span) in &chunks {
+            let gap = &source_text[source.len()..span.start as usize];
+            if gap.trim().is_empty() {
+                source.push_str(gap);
+            } else {
+                source.push_str(&" ".repeat(gap.len()));
+            }
+            source.push_str(code);
+        }
+
+        let allocator = Allocator::default();
+        let source_type = SourceType::default()
+            .with_module(true)
+            .with_typescript(true);
+
+        let mut imports = Vec::new();
+        let mut imported_names = Vec::new();
+        let mut bindings = Vec::new();
+        // Spans replaced in the body: whole import declarations and `export` keywords are cut out,
+        // `inject()` calls rewritten.
+        let mut edits = Vec::new();
+
+        let mut collector = ReferenceCollector::default();
+        let objects;
+        let (inputs, outputs, queries);
+        let errors = {
+            let ret = Parser::new(&allocator, &source, source_type).parse();
+            for statement in &ret.program.body {
+                match statement {
+                    Statement::ImportDeclaration(decl) => {
+                        edits.push((decl.span, String::new()));
+                        let mut specifiers = Vec::new();
+                        for specifier in decl.specifiers.iter().flatten() {
+                            let (local, export) = match specifier {
+                                ImportDeclarationSpecifier::ImportSpecifier(s) => {
+                                    (&s.local, Some(s.imported.name().to_string()))
+                                }
+                                ImportDeclarationSpecifier::ImportDefaultSpecifier(s) => {
+                                    (&s.local, Some("default".to_string()))
+                                }
+                                ImportDeclarationSpecifier::ImportNamespaceSpecifier(s) => {
+                                    (&s.local, None)
+                                }
+                            };
+                            if decl.source.value != "@angular/core" {
+                                imported_names.push(local.name.to_string());
+                            }
+                            specifiers.push((local.name.to_string(), export));
+                        }
+                        imports.push(Import {
+                            source: decl.span.source_text(&source).to_string(),
+                            module: decl.source.value.to_string(),
+                            specifiers,
+                        });
+                    }
+                    Statement::ExportNamedDeclaration(decl) => {
+                        if let Some(declaration) = &decl.declaration {
+                            edits.push((
+                                Span::new(decl.span.start, declaration.span().start),
+                                String::new(),
+                            ));
+                            declaration
+                                .bound_names(&mut |ident| bindings.push(ident.name.to_string()));
+                        }
+                    }
+                    _ => {
+                        if let Some(declaration) = statement.as_declaration() {
+                            declaration
+                                .bound_names(&mut |ident| bindings.push(ident.name.to_string()));
+                        }
+                    }
+                }
+            }
+            collector.visit_program(&ret.program);
+            let semantic = SemanticBuilder::new(&source).build(&ret.program).semantic;
+            objects = spread_objects(&semantic, &ret.program);
+            let calls = angular_calls(&ret.program, &imports);
+            (inputs, outputs) = inputs_and_outputs(&calls, &source);
+            queries = super::signals::queries(&calls, &source);
+            let (injects, mut diagnostics) = inject_calls(&semantic, &ret.program);
+            edits.extend(injects);
+            diagnostics.splice(0..0, ret.errors);
+            diagnostics
+        };
+        edits.sort_by_key(|(span, _)| span.start);
+
+        let mut body = String::new();
+        let mut previous_end = None;
+        for (code, span) in &chunks {
+            if let Some(end) = previous_end {
+                let gap = &source_text[end..span.start as usize];
+                body.push_str(if gap.trim().is_empty() { gap } else { "\n" });
+            }
+            body.push_str(&apply_edits(code, *span, &edits));
+            previous_end = Some(span.end as usize);
+        }
+
+        let script = Self {
+            imports,
+            imported_names,
+            references: collector.references,
+            bindings,
+            objects,
+            inputs,
+            outputs,
+            queries,
+            body: body.trim().to_string(),
+        };
+        (script, errors)
+    }
+}
+
+/// Replaces the parts of `code` (found at `span` in the source) covered by `edits`, sorted by
+/// their start. An edit covering several chunks is written in the one it starts in; the others
+/// only drop the text it covers.
+fn apply_edits(code: &str, span: Span, edits: &[(Span, String)]) -> String {
+    let mut result = String::with_capacity(code.len());
+    let mut position = span.start;
+    for (cut, replacement) in edits {
+        if cut.end <= position || cut.start >= span.end {
+            continue;
+        }
+        if cut.start > position {
+            result.push_str(
+                &code[(position - span.start) as usize..(cut.start - span.start) as usize],
+            );
+        }
+        if cut.start >= span.start {
+            result.push_str(replacement);
+        }
+        position = cut.end.min(span.end);
+    }
+    if position < span.end {
+        result.push_str(&code[(position - span.start) as usize..]);
+    }
+    result
+}
+
+#[derive(Default)]
+struct ReferenceCollector {
+    references: Vec<String>,
+}
+
+impl<'a> Visit<'a> for ReferenceCollector {
+    fn visit_identifier_reference(&mut self, it: &IdentifierReference<'a>) {
+        if !self.references.iter().any(|name| name == it.name.as_str()) {
+            self.references.push(it.name.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::treaty::lexer::Lexer;
+    use crate::treaty::parser::Parser as TreatyParser;
+
+    fn script(source_text: &str) -> Script {
+        let mut lexer = Lexer::new(source_text);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token() {
+            tokens.push(token);
+        }
+        let ast = TreatyParser::new(tokens).parse();
+        Script::new(source_text, &ast).0
+    }
+
+    #[test]
+    fn edit_across_chunks_is_written_once() {
+        // `a(b, c)` split into `a(b,` and ` c)`, the way a line break splits a script.
+        let edits = [(Span::new(0, 7), "x".to_string())];
+        assert_eq!(apply_edits("a(b,", Span::new(0, 4), &edits), "x");
+        assert_eq!(apply_edits(" c);", Span::new(4, 8), &edits), ";");
+    }
+
+    #[test]
+    fn multi_line_inject() {
+        let script = script(
+            "import { inject } from '@angular/core';\n\
+             const foo = inject(Foo, {\n  optional: true,\n});\n\
+             <p>{{ foo }}</p>\n",
+        );
+        assert_eq!(script.body.matches("ɵɵdirectiveInject").count(), 1);
+        assert!(script.body.contains("i0.ɵɵdirectiveInject(Foo, 8"));
+        assert!(!script.body.contains("8, 8"));
+    }
+
+    #[test]
+    fn angular_core_exports_are_not_components() {
+        let script = script(
+            "import { input } from '@angular/core';\n\
+             import logo from './logo.treaty';\n\
+             <input><logo />\n",
+        );
+        assert_eq!(script.imported_names, ["logo"]);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::angular::runtime::SelectorFlags;
+
+use super::string_literal;
+
+/// A compound selector, such as `button.primary[type=submit]`.
+#[derive(Default)]
+struct Compound {
+    element: String,
+    /// Names and values, the value empty for `[name]`.
+    attributes: Vec<(String, String)>,
+    classes: Vec<String>,
+    /// `:not(...)` selectors, each a compound without negations.
+    negations: Vec<Compound>,
+}
+
+/// Parses the `selector` of a component or directive into the array Angular matches elements
+/// against, e.g. `[["app-root"], ["", "appRoot", ""]]` for `app-root, [appRoot]`.
+///
+/// Every compound selector becomes its element name followed by its attributes as name and
+/// value pairs, then its classes after `SelectorFlags.Class`, then each `:not()` flagged with
+/// what it negates.
+pub fn parse_selector(selector: &str) -> String {
+    let selectors: Vec<_> = selector
+        .split(',')
+        .map(str::trim)
+        .filter(|selector| !selector.is_empty())
+        .map(|selector| {
+            let compound = compound(selector);
+            let mut parts = vec![string_literal(if compound.element == "*" {
+                ""
+            } else {
+                &compound.element
+            })];
+            parts.extend(attributes_and_classes(&compound));
+            for negation in &compound.negations {
+                parts.extend(negative(negation));
+            }
+            format!("[{}]", parts.join(", "))
+        })
+        .collect();
+    format!("[{}]", selectors.join(", "))
+}
+
+fn attributes_and_classes(compound: &Compound) -> Vec<String> {
+    let mut parts = Vec::new();
+    for (name, value) in &compound.attributes {
+        parts.push(string_literal(name));
+        parts.push(string_literal(value));
+    }
+    if !compound.classes.is_empty() {
+        parts.push((SelectorFlags::Class as u8).to_string());
+        parts.extend(compound.classes.iter().map(|class| string_literal(class)));
+    }
+    parts
+}
+
+/// A `:not()` selector, flagged with the first thing it has: an element, attributes or classes.
+fn negative(compound: &Compound) -> Vec<String> {
+    let (flag, mut parts) = if !compound.element.is_empty() {
+        (
+            SelectorFlags::Element,
+            vec![string_literal(&compound.element)],
+        )
+    } else if !compound.attributes.is_empty() {
+        (SelectorFlags::Attribute, Vec::new())
+    } else if !compound.classes.is_empty() {
+        let classes = compound.classes.iter().map(|class| string_literal(class));
+        let flags = SelectorFlags::Not as u8 | SelectorFlags::Class as u8;
+        return std::iter::once(flags.to_string()).chain(classes).collect();
+    } else {
+        return Vec::new();
+    };
+    parts.insert(0, (SelectorFlags::Not as u8 | flag as u8).to_string());
+    parts.extend(attributes_and_classes(compound));
+    parts
+}
+
+fn compound(selector: &str) -> Compound {
+    let mut compound = Compound::default();
+    let mut rest = selector;
+    let name_end = |s: &str| {
+        s.find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(s.len())
+    };
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '.' => {
+                let end = name_end(&rest[1..]) + 1;
+                compound.classes.push(rest[1..end].to_string());
+                rest = &rest[end..];
+            }
+            '[' => {
+                let end = rest.find(']').unwrap_or(rest.len());
+                let inner = &rest[1..end];
+                let (name, value) = match inner.split_once('=') {
+                    Some((name, value)) => (name, value.trim_matches(|c| c == '"' || c == '\'')),
+                    None => (inner, ""),
+                };
+                compound
+                    .attributes
+                    .push((name.trim().to_string(), value.to_string()));
+                rest = rest.get(end + 1..).unwrap_or_default();
+            }
+            ':' if rest.starts_with(":not(") => {
+                let end = rest.find(')').unwrap_or(rest.len());
+                compound
+                    .negations
+                    .push(self::compound(&rest[":not(".len()..end]));
+                rest = rest.get(end + 1..).unwrap_or_default();
+            }
+            '*' => {
+                compound.element = "*".to_string();
+                rest = &rest[1..];
+            }
+            _ => {
+                let end = name_end(rest).max(c.len_utf8());
+                compound.element = rest[..end].to_string();
+                rest = &rest[end..];
+            }
+        }
+    }
+    compound
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elements_and_attributes() {
+        assert_eq!(
+            parse_selector("app-root, [appRoot]"),
+            r#"[["app-root"], ["", "appRoot", ""]]"#
+        );
+        assert_eq!(parse_selector("*"), r#"[[""]]"#);
+    }
+
+    #[test]
+    fn attributes_then_classes() {
+        assert_eq!(
+            parse_selector("button.primary.large[type=\"submit\"]"),
+            r#"[["button", "type", "submit", 8, "primary", "large"]]"#
+        );
+    }
+
+    #[test]
+    fn negations() {
+        assert_eq!(
+            parse_selector("[appTooltip]:not(.disabled)"),
+            r#"[["", "appTooltip", "", 9, "disabled"]]"#
+        );
+        assert_eq!(
+            parse_selector("input:not([type=hidden])"),
+            r#"[["input", 3, "type", "hidden"]]"#
+        );
+        assert_eq!(parse_selector(":not(p)"), r#"[["", 5, "p"]]"#);
+    }
+}
@@ -0,0 +1,265 @@
+/// Attribute Angular puts on the elements of a component template, `%COMP%` replaced with the
+/// id of the component at runtime.
+const CONTENT_ATTRIBUTE: &str = "[_ngcontent-%COMP%]";
+/// Attribute Angular puts on the host element of a component.
+const HOST_ATTRIBUTE: &str = "[_nghost-%COMP%]";
+
+/// At-rules holding style rules, whose selectors are scoped as well.
+const GROUPING_RULES: &[&str] = &["@media", "@supports", "@container", "@layer", "@document"];
+
+/// Scopes minified `css` to a component the way `ViewEncapsulation.Emulated` does, e.g.
+/// `:host .title:hover{}` -> `[_nghost-%COMP%] .title[_ngcontent-%COMP%]:hover{}`.
+///
+/// Every compound selector matches elements of the template, `:host`, `:host(selector)` and
+/// `:host-context(selector)` match the host element, and whatever follows `::ng-deep` is left
+/// global. Rules inside `@keyframes`, `@font-face` and the like are kept as they are.
+pub fn shim_css(css: &str) -> String {
+    let mut result = String::with_capacity(css.len());
+    let mut rest = css;
+    while !rest.is_empty() {
+        let Some(end) = find_top_level(rest, |c| c == '{' || c == ';') else {
+            result.push_str(rest);
+            break;
+        };
+        let prelude = &rest[..end];
+        if rest[end..].starts_with(';') {
+            // A statement such as `@import url(a.css);`.
+            result.push_str(&rest[..=end]);
+            rest = &rest[end + 1..];
+            continue;
+        }
+        let body_end = matching(rest, end);
+        let body = &rest[end + 1..body_end];
+        if prelude.starts_with('@') {
+            result.push_str(prelude);
+            result.push('{');
+            let name = prelude.split([' ', '(']).next().unwrap_or_default();
+            if GROUPING_RULES.contains(&name) {
+                result.push_str(&shim_css(body));
+            } else {
+                result.push_str(body);
+            }
+        } else {
+            result.push_str(&shim_selector_list(prelude));
+            result.push('{');
+            result.push_str(body);
+        }
+        result.push('}');
+        rest = rest.get(body_end + 1..).unwrap_or_default();
+    }
+    result
+}
+
+/// `a, b`, each selector of the list scoped on its own.
+fn shim_selector_list(selectors: &str) -> String {
+    split_top_level(selectors, ',')
+        .into_iter()
+        .flat_map(host_context)
+        .map(|selector| shim_selector(&selector))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// `:host-context(.dark) a` matches a host that is `.dark` itself or inside it, spelled out as
+/// `:host(.dark) a` and `.dark :host a`.
+fn host_context(selector: &str) -> Vec<String> {
+    let selector = selector.trim();
+    let Some(start) = selector.find(":host-context(") else {
+        return vec![selector.to_string()];
+    };
+    let open = start + ":host-context".len();
+    let close = matching(selector, open);
+    let context = &selector[open + 1..close];
+    let before = &selector[..start];
+    let after = selector.get(close + 1..).unwrap_or_default();
+    vec![
+        format!("{}:host({}){}", before, context, after),
+        format!("{}{} :host{}", before, context, after),
+    ]
+}
+
+/// A complex selector such as `ul > li.item`, each compound scoped up to `::ng-deep`.
+fn shim_selector(selector: &str) -> String {
+    // What comes before the host is outside of the component, e.g. `.dark :host`.
+    let host = selector
+        .find(":host")
+        .filter(|&index| index > 0 && selector[..index].ends_with([' ', '>', '+', '~']));
+    if let Some(index) = host {
+        return format!(
+            "{}{}",
+            &selector[..index],
+            shim_selector(&selector[index..])
+        );
+    }
+    let mut result = String::with_capacity(selector.len());
+    let mut deep = false;
+    let mut compound = String::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut chars = selector.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth -= 1,
+            (None, ' ' | '>' | '+' | '~') if depth == 0 => {
+                if compound == "::ng-deep" {
+                    // Dropped along with the whitespace around it.
+                    deep = true;
+                    compound.clear();
+                    while chars.next_if(|c| *c == ' ').is_some() {}
+                    continue;
+                }
+                if !compound.is_empty() {
+                    result.push_str(&shim_compound(&compound, deep));
+                    compound.clear();
+                }
+                result.push(c);
+                continue;
+            }
+            _ => {}
+        }
+        compound.push(c);
+    }
+    if compound == "::ng-deep" {
+        return result.trim_end().to_string();
+    }
+    result.push_str(&shim_compound(&compound, deep));
+    result
+}
+
+/// `a.b:hover` -> `a.b[_ngcontent-%COMP%]:hover`, `:host(.b)` -> `.b[_nghost-%COMP%]`.
+fn shim_compound(compound: &str, deep: bool) -> String {
+    if compound.is_empty() || deep {
+        return compound.to_string();
+    }
+    if let Some(rest) = compound.strip_prefix(":host") {
+        if let Some(argument) = rest.strip_prefix('(') {
+            let close = matching(rest, 0);
+            let selector = &argument[..close - 1];
+            let (selector, pseudo) = split_pseudo(selector);
+            return format!(
+                "{}{}{}{}",
+                selector,
+                HOST_ATTRIBUTE,
+                pseudo,
+                rest.get(close + 1..).unwrap_or_default()
+            );
+        }
+        if !rest.starts_with(|c: char| c.is_alphanumeric() || c == '-') {
+            return format!("{}{}", HOST_ATTRIBUTE, rest);
+        }
+    }
+    let (selector, pseudo) = split_pseudo(compound);
+    format!("{}{}{}", selector, CONTENT_ATTRIBUTE, pseudo)
+}
+
+/// Splits a compound ahead of its first pseudo-class or pseudo-element, e.g. `a` and `:hover`.
+fn split_pseudo(compound: &str) -> (&str, &str) {
+    let index = find_top_level(compound, |c| c == ':').unwrap_or(compound.len());
+    compound.split_at(index)
+}
+
+/// The first `c` matching `predicate` outside of strings, parentheses and brackets.
+fn find_top_level(text: &str, predicate: impl Fn(char) -> bool) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth = depth.saturating_sub(1),
+            (None, c) if depth == 0 && predicate(c) => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `text` split at every top-level `separator`.
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some(index) = find_top_level(rest, |c| c == separator) {
+        parts.push(&rest[..index]);
+        rest = &rest[index + separator.len_utf8()..];
+    }
+    parts.push(rest);
+    parts
+}
+
+/// The index of the bracket closing the one at `open`, `{` or `(`, or the end of `text` when it
+/// is missing.
+fn matching(text: &str, open: usize) -> usize {
+    let (opening, closing) = match &text[open..open + 1] {
+        "(" => ('(', ')'),
+        _ => ('{', '}'),
+    };
+    let mut depth = 0;
+    for (index, c) in text[open..].char_indices() {
+        if c == opening {
+            depth += 1;
+        } else if c == closing {
+            depth -= 1;
+            if depth == 0 {
+                return open + index;
+            }
+        }
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_every_compound() {
+        assert_eq!(
+            shim_css("ul>li.item a:hover{color:red}"),
+            "ul[_ngcontent-%COMP%]>li.item[_ngcontent-%COMP%] a[_ngcontent-%COMP%]:hover{color:red}"
+        );
+        assert_eq!(
+            shim_css("p::before,[type=\"a:b\"]{content:\"\"}"),
+            "p[_ngcontent-%COMP%]::before,[type=\"a:b\"][_ngcontent-%COMP%]{content:\"\"}"
+        );
+    }
+
+    #[test]
+    fn host_selectors() {
+        assert_eq!(
+            shim_css(":host{display:block}:host(.active) p{color:red}"),
+            "[_nghost-%COMP%]{display:block}.active[_nghost-%COMP%] p[_ngcontent-%COMP%]{color:red}"
+        );
+        assert_eq!(
+            shim_css(":host:hover{}:host-context(.dark) p{}"),
+            "[_nghost-%COMP%]:hover{}.dark[_nghost-%COMP%] p[_ngcontent-%COMP%],\
+             .dark [_nghost-%COMP%] p[_ngcontent-%COMP%]{}"
+        );
+    }
+
+    #[test]
+    fn ng_deep_leaves_the_rest_global() {
+        assert_eq!(
+            shim_css(":host ::ng-deep .mat-icon{}"),
+            "[_nghost-%COMP%] .mat-icon{}"
+        );
+        assert_eq!(shim_css("::ng-deep p{}"), "p{}");
+    }
+
+    #[test]
+    fn at_rules() {
+        assert_eq!(
+            shim_css("@media (max-width:600px){p{margin:0}}@keyframes spin{from{top:0}}"),
+            "@media (max-width:600px){p[_ngcontent-%COMP%]{margin:0}}@keyframes spin{from{top:0}}"
+        );
+        assert_eq!(
+            shim_css("@import url(a.css);p{}"),
+            "@import url(a.css);p[_ngcontent-%COMP%]{}"
+        );
+    }
+}
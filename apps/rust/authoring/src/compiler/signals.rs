@@ -0,0 +1,318 @@
+use oxc_ast::ast::{
+    Argument, BindingPatternKind, CallExpression, Declaration, Expression, ObjectPropertyKind,
+    Program, Statement,
+};
+
+use oxc_span::GetSpan;
+
+use super::script::Import;
+
+/// A signal input, from `input()`, `input.required()` or the input half of `model()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Input {
+    /// The binding holding the signal.
+    pub name: String,
+    /// The name templates bind to, the `alias` option when given.
+    pub public_name: String,
+    pub required: bool,
+    /// Source of the `transform` option, which the signal applies itself.
+    pub transform: Option<String>,
+}
+
+/// An output, from `output()`, `outputFromObservable()` or the output half of `model()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Output {
+    pub name: String,
+    pub public_name: String,
+}
+
+/// An `@angular/core` function called by the script, resolved through renamed and namespace
+/// imports, e.g. `import { input as prop }` or `core.input.required`.
+pub(super) struct AngularCall<'c, 'a> {
+    /// The exported name, e.g. `input`.
+    pub(super) function: &'static str,
+    /// Called as `function.required(...)`.
+    pub(super) required: bool,
+    pub(super) call: &'c CallExpression<'a>,
+}
+
+/// Functions recognised as declaring a member of the component.
+const FUNCTIONS: [&str; 8] = [
+    "input",
+    "model",
+    "output",
+    "outputFromObservable",
+    "viewChild",
+    "viewChildren",
+    "contentChild",
+    "contentChildren",
+];
+
+/// Top-level `const name = fn(...)` declarations calling one of the `@angular/core` functions.
+pub(super) fn angular_calls<'c, 'a>(
+    program: &'c Program<'a>,
+    imports: &[Import],
+) -> Vec<(String, AngularCall<'c, 'a>)> {
+    let mut calls = Vec::new();
+    for statement in &program.body {
+        let declaration = match statement {
+            Statement::VariableDeclaration(declaration) => declaration,
+            Statement::ExportNamedDeclaration(export) => match &export.declaration {
+                Some(Declaration::VariableDeclaration(declaration)) => declaration,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        for declarator in &declaration.declarations {
+            let (BindingPatternKind::BindingIdentifier(id), Some(Expression::CallExpression(call))) =
+                (&declarator.id.kind, &declarator.init)
+            else {
+                continue;
+            };
+            if let Some(angular_call) = angular_call(call, imports) {
+                calls.push((id.name.to_string(), angular_call));
+            }
+        }
+    }
+    calls
+}
+
+fn angular_call<'c, 'a>(
+    call: &'c CallExpression<'a>,
+    imports: &[Import],
+) -> Option<AngularCall<'c, 'a>> {
+    let core = imports
+        .iter()
+        .filter(|import| import.module == "@angular/core");
+    // The callee as a path, e.g. `["core", "input", "required"]`.
+    let mut path = Vec::new();
+    let mut callee = &call.callee;
+    loop {
+        match callee {
+            Expression::Identifier(identifier) => {
+                path.insert(0, identifier.name.as_str());
+                break;
+            }
+            Expression::StaticMemberExpression(member) => {
+                path.insert(0, member.property.name.as_str());
+                callee = &member.object;
+            }
+            _ => return None,
+        }
+    }
+
+    let (local, rest) = path.split_first()?;
+    let specifier = core
+        .flat_map(|import| &import.specifiers)
+        .find(|(name, _)| name == local)?;
+    let (function, rest) = match &specifier.1 {
+        Some(export) => (export.as_str(), rest),
+        // A namespace import, `core.input`.
+        None => rest.split_first().map(|(f, rest)| (*f, rest))?,
+    };
+    let required = match rest {
+        [] => false,
+        ["required"] => true,
+        _ => return None,
+    };
+    let function = FUNCTIONS.into_iter().find(|f| *f == function)?;
+    Some(AngularCall {
+        function,
+        required,
+        call,
+    })
+}
+
+impl<'c, 'a> AngularCall<'c, 'a> {
+    /// The options object, the argument at `index`.
+    pub(super) fn options(&self, index: usize) -> Option<&'c [ObjectPropertyKind<'a>]> {
+        match self.call.arguments.get(index)? {
+            Argument::ObjectExpression(object) => Some(&object.properties),
+            _ => None,
+        }
+    }
+
+    /// The expression of option `name`.
+    pub(super) fn option(&self, index: usize, name: &str) -> Option<&'c Expression<'a>> {
+        self.options(index)?
+            .iter()
+            .find_map(|property| match property {
+                ObjectPropertyKind::ObjectProperty(property)
+                    if property.key.static_name().as_deref() == Some(name) =>
+                {
+                    Some(&property.value)
+                }
+                _ => None,
+            })
+    }
+
+    /// The `alias` option, when a string literal.
+    fn alias(&self, index: usize) -> Option<String> {
+        match self.option(index, "alias")? {
+            Expression::StringLiteral(alias) => Some(alias.value.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// The inputs and outputs declared with signal functions.
+pub(super) fn inputs_and_outputs(
+    calls: &[(String, AngularCall)],
+    source: &str,
+) -> (Vec<Input>, Vec<Output>) {
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    for (name, call) in calls {
+        match call.function {
+            "input" | "model" => {
+                // `.required` has no initial value before its options.
+                let options = if call.required { 0 } else { 1 };
+                let public_name = call.alias(options).unwrap_or_else(|| name.clone());
+                let transform = call
+                    .option(options, "transform")
+                    .map(|transform| transform.span().source_text(source).to_string());
+                if call.function == "model" {
+                    outputs.push(Output {
+                        name: name.clone(),
+                        public_name: format!("{}Change", public_name),
+                    });
+                }
+                inputs.push(Input {
+                    name: name.clone(),
+                    public_name,
+                    required: call.required,
+                    transform,
+                });
+            }
+            "output" => outputs.push(Output {
+                name: name.clone(),
+                public_name: call.alias(0).unwrap_or_else(|| name.clone()),
+            }),
+            "outputFromObservable" => outputs.push(Output {
+                name: name.clone(),
+                public_name: call.alias(1).unwrap_or_else(|| name.clone()),
+            }),
+            _ => {}
+        }
+    }
+    (inputs, outputs)
+}
+
+/// A signal query, from `viewChild()`, `viewChildren()`, `contentChild()` or `contentChildren()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    /// The binding holding the signal.
+    pub name: String,
+    /// Whether the query looks at the template rather than the projected content.
+    pub view: bool,
+    /// Whether only the first match is kept, `viewChild` and `contentChild`.
+    pub first: bool,
+    pub required: bool,
+    pub predicate: Predicate,
+    /// Source of the `read` option, the token read from each match.
+    pub read: Option<String>,
+    /// Whether matches are looked for in nested elements as well as direct children.
+    pub descendants: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    /// `viewChild('a')` or `viewChildren('a, b')`, matching `#a` and `#b` references.
+    References(Vec<String>),
+    /// Source of a type or token, e.g. `viewChild(Item)`.
+    Type(String),
+}
+
+/// The queries declared with signal functions.
+pub(super) fn queries(calls: &[(String, AngularCall)], source: &str) -> Vec<Query> {
+    calls
+        .iter()
+        .filter_map(|(name, call)| {
+            let (view, first) = match call.function {
+                "viewChild" => (true, true),
+                "viewChildren" => (true, false),
+                "contentChild" => (false, true),
+                "contentChildren" => (false, false),
+                _ => return None,
+            };
+            let predicate = match call.call.arguments.first()?.as_expression()? {
+                Expression::StringLiteral(references) => Predicate::References(
+                    references
+                        .value
+                        .split(',')
+                        .map(|reference| reference.trim().to_string())
+                        .collect(),
+                ),
+                predicate => Predicate::Type(predicate.span().source_text(source).to_string()),
+            };
+            let read = call
+                .option(1, "read")
+                .map(|read| read.span().source_text(source).to_string());
+            // View queries always look at nested elements; only `contentChildren` doesn't by
+            // default.
+            let descendants = view
+                || match call.option(1, "descendants") {
+                    Some(Expression::BooleanLiteral(descendants)) => descendants.value,
+                    _ => first,
+                };
+            Some(Query {
+                name: name.clone(),
+                view,
+                first,
+                required: call.required,
+                predicate,
+                read,
+                descendants,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::compile_source;
+
+    #[test]
+    fn inputs_outputs_and_models() {
+        let component = compile_source(
+            "import { input, model, output, booleanAttribute } from '@angular/core';\n\
+             import * as core from '@angular/core';\n\
+             const name = input('');\n\
+             const id = input.required({ alias: 'userId' });\n\
+             const open = core.input(false, { transform: booleanAttribute });\n\
+             const value = model(0);\n\
+             const saved = output({ alias: 'save' });\n\
+             <input [value]=\"name()\">\n",
+        );
+        assert!(component.diagnostics.is_empty());
+        assert!(component.code.contains(
+            "inputs: { name: [1, \"name\"], id: [1, \"userId\", \"id\"], open: [1, \"open\"], value: [1, \"value\"] },"
+        ));
+        assert!(component
+            .code
+            .contains("outputs: { value: \"valueChange\", saved: \"save\" },"));
+    }
+
+    #[test]
+    fn view_and_content_queries() {
+        let component = compile_source(
+            "import { viewChild, viewChildren, contentChildren, ElementRef } from '@angular/core';\n\
+             import * as core from '@angular/core';\n\
+             const box = viewChild('box');\n\
+             const rows = viewChildren('a, b', { read: ElementRef });\n\
+             const items = contentChildren(Item, { descendants: true });\n\
+             const first = core.contentChild.required(Item);\n\
+             <input #box>\n",
+        );
+        let code = &component.code;
+        assert!(component.diagnostics.is_empty());
+        assert!(code.contains("const _c0 = [\"box\"];"));
+        assert!(code.contains("const _c1 = [\"a\", \"b\"];"));
+        assert!(code.contains("i0.ɵɵviewQuerySignal(ctx.box, _c0, 5);"));
+        assert!(code.contains("i0.ɵɵviewQuerySignal(ctx.rows, _c1, 5, ElementRef);"));
+        assert!(code.contains("i0.ɵɵcontentQuerySignal(dirIndex, ctx.items, Item, 5);"));
+        // `contentChild` looks at nested elements unless told otherwise.
+        assert!(code.contains("i0.ɵɵcontentQuerySignal(dirIndex, ctx.first, Item, 5);"));
+        assert_eq!(code.matches("i0.ɵɵqueryAdvance(2);").count(), 2);
+    }
+}
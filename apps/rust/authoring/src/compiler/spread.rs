@@ -0,0 +1,247 @@
+use oxc_ast::{
+    ast::{
+        BindingPatternKind, Expression, ObjectExpression, ObjectPropertyKind, Program, Statement,
+        TSSignature, TSType, TSTypeAnnotation, TSTypeName, VariableDeclarator,
+    },
+    AstKind,
+};
+use oxc_semantic::Semantic;
+
+/// Signal factories, whose result is read by calling it.
+const SIGNALS: [&str; 5] = ["signal", "computed", "input", "model", "linkedSignal"];
+/// Signal types, whose value is read by calling it.
+const SIGNAL_TYPES: [&str; 5] = [
+    "Signal",
+    "WritableSignal",
+    "InputSignal",
+    "ModelSignal",
+    "InputSignalWithTransform",
+];
+/// How many declarations are followed to resolve an object, e.g. `const a = b; const b = {}`.
+const MAX_DEPTH: usize = 8;
+
+/// The keys of an object bound with `{...object}`, each becoming an input binding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpreadObject {
+    /// Whether the object is held in a signal, `signal({ ... })`, and read as `object().key`.
+    pub signal: bool,
+    /// Each key, with whether its value is a signal, read as `object.key()`.
+    pub keys: Vec<(String, bool)>,
+}
+
+/// Resolves the keys of every top-level variable whose shape is known statically: from an
+/// object literal, a signal of one, or an interface or type literal annotating it.
+pub fn spread_objects<'a>(
+    semantic: &Semantic<'a>,
+    program: &Program<'a>,
+) -> Vec<(String, SpreadObject)> {
+    let resolver = Resolver { semantic };
+    let mut objects = Vec::new();
+    for statement in &program.body {
+        let declaration = match statement {
+            Statement::VariableDeclaration(declaration) => declaration,
+            Statement::ExportNamedDeclaration(export) => match &export.declaration {
+                Some(oxc_ast::ast::Declaration::VariableDeclaration(declaration)) => declaration,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        for declarator in &declaration.declarations {
+            let BindingPatternKind::BindingIdentifier(id) = &declarator.id.kind else {
+                continue;
+            };
+            if let Some(object) = resolver.declarator(declarator, 0) {
+                objects.push((id.name.to_string(), object));
+            }
+        }
+    }
+    objects
+}
+
+struct Resolver<'s, 'a> {
+    semantic: &'s Semantic<'a>,
+}
+
+impl<'s, 'a> Resolver<'s, 'a> {
+    /// A type annotation wins over the initializer, which may not show every key.
+    fn declarator(
+        &self,
+        declarator: &VariableDeclarator<'a>,
+        depth: usize,
+    ) -> Option<SpreadObject> {
+        match &declarator.id.type_annotation {
+            Some(annotation) => self.annotation(annotation, depth),
+            None => self.expression(declarator.init.as_ref()?, depth),
+        }
+    }
+
+    fn annotation(&self, annotation: &TSTypeAnnotation<'a>, depth: usize) -> Option<SpreadObject> {
+        self.ts_type(&annotation.type_annotation, depth)
+    }
+
+    fn expression(&self, expression: &Expression<'a>, depth: usize) -> Option<SpreadObject> {
+        match expression {
+            Expression::ObjectExpression(object) => Some(SpreadObject {
+                signal: false,
+                keys: self.object_keys(object, depth)?,
+            }),
+            Expression::CallExpression(call) if self.is_signal_factory(&call.callee) => {
+                let value = call.arguments.first()?.as_expression()?;
+                let object = self.expression(value, depth)?;
+                (!object.signal).then_some(SpreadObject {
+                    signal: true,
+                    keys: object
+                        .keys
+                        .into_iter()
+                        .map(|(key, _)| (key, false))
+                        .collect(),
+                })
+            }
+            Expression::TSAsExpression(expression) => {
+                self.ts_type(&expression.type_annotation, depth)
+            }
+            Expression::TSSatisfiesExpression(expression) => {
+                self.ts_type(&expression.type_annotation, depth)
+            }
+            Expression::ParenthesizedExpression(expression) => {
+                self.expression(&expression.expression, depth)
+            }
+            Expression::Identifier(identifier) => {
+                self.declarator(self.variable(&identifier.name, depth)?, depth + 1)
+            }
+            _ => None,
+        }
+    }
+
+    fn object_keys(
+        &self,
+        object: &ObjectExpression<'a>,
+        depth: usize,
+    ) -> Option<Vec<(String, bool)>> {
+        let mut keys = Vec::new();
+        for property in &object.properties {
+            match property {
+                ObjectPropertyKind::ObjectProperty(property) => {
+                    let key = property.key.static_name()?;
+                    keys.push((key.to_string(), self.is_signal(&property.value, depth)));
+                }
+                // `{ ...other }` brings in the keys of `other`.
+                ObjectPropertyKind::SpreadProperty(spread) => {
+                    let other = self.expression(&spread.argument, depth + 1)?;
+                    if other.signal || depth >= MAX_DEPTH {
+                        return None;
+                    }
+                    keys.extend(other.keys);
+                }
+            }
+        }
+        Some(keys)
+    }
+
+    fn ts_type(&self, ts_type: &TSType<'a>, depth: usize) -> Option<SpreadObject> {
+        match ts_type {
+            TSType::TSTypeLiteral(literal) => Some(SpreadObject {
+                signal: false,
+                keys: self.signature_keys(&literal.members),
+            }),
+            TSType::TSTypeReference(reference) => {
+                let TSTypeName::IdentifierReference(name) = &reference.type_name else {
+                    return None;
+                };
+                if SIGNAL_TYPES.contains(&name.name.as_str()) {
+                    let value = reference.type_parameters.as_ref()?.params.first()?;
+                    let object = self.ts_type(value, depth)?;
+                    return (!object.signal).then_some(SpreadObject {
+                        signal: true,
+                        keys: object
+                            .keys
+                            .into_iter()
+                            .map(|(key, _)| (key, false))
+                            .collect(),
+                    });
+                }
+                if depth >= MAX_DEPTH {
+                    return None;
+                }
+                let symbol = self.semantic.scopes().get_root_binding(&name.name)?;
+                match self.semantic.symbol_declaration(symbol).kind() {
+                    AstKind::TSInterfaceDeclaration(interface) => Some(SpreadObject {
+                        signal: false,
+                        keys: self.signature_keys(&interface.body.body),
+                    }),
+                    AstKind::TSTypeAliasDeclaration(alias) => {
+                        self.ts_type(&alias.type_annotation, depth + 1)
+                    }
+                    _ => None,
+                }
+            }
+            TSType::TSParenthesizedType(parenthesized) => {
+                self.ts_type(&parenthesized.type_annotation, depth)
+            }
+            _ => None,
+        }
+    }
+
+    fn signature_keys(&self, signatures: &[TSSignature<'a>]) -> Vec<(String, bool)> {
+        signatures
+            .iter()
+            .filter_map(|signature| match signature {
+                TSSignature::TSPropertySignature(property) => {
+                    let key = property.key.static_name()?;
+                    let signal = property.type_annotation.as_ref().is_some_and(|annotation| {
+                        matches!(
+                            &annotation.type_annotation,
+                            TSType::TSTypeReference(reference)
+                                if matches!(
+                                    &reference.type_name,
+                                    TSTypeName::IdentifierReference(name)
+                                        if SIGNAL_TYPES.contains(&name.name.as_str())
+                                )
+                        )
+                    });
+                    Some((key.to_string(), signal))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether `expression` evaluates to a signal, following top-level variables.
+    fn is_signal(&self, expression: &Expression<'a>, depth: usize) -> bool {
+        match expression.get_inner_expression() {
+            Expression::CallExpression(call) => self.is_signal_factory(&call.callee),
+            Expression::Identifier(identifier) => self
+                .variable(&identifier.name, depth)
+                .and_then(|declarator| declarator.init.as_ref())
+                .is_some_and(|init| self.is_signal(init, depth + 1)),
+            _ => false,
+        }
+    }
+
+    /// `signal`, `computed`, ... and `input.required`, `model.required`.
+    fn is_signal_factory(&self, callee: &Expression<'a>) -> bool {
+        let name = match callee {
+            Expression::Identifier(identifier) => identifier.name.as_str(),
+            Expression::StaticMemberExpression(member) if member.property.name == "required" => {
+                match &member.object {
+                    Expression::Identifier(identifier) => identifier.name.as_str(),
+                    _ => return false,
+                }
+            }
+            _ => return false,
+        };
+        SIGNALS.contains(&name)
+    }
+
+    /// The declarator of a top-level variable.
+    fn variable(&self, name: &str, depth: usize) -> Option<&'s VariableDeclarator<'a>> {
+        if depth >= MAX_DEPTH {
+            return None;
+        }
+        let symbol = self.semantic.scopes().get_root_binding(name)?;
+        match self.semantic.symbol_declaration(symbol).kind() {
+            AstKind::VariableDeclarator(declarator) => Some(declarator),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,886 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use oxc_diagnostics::OxcDiagnostic;
+
+use crate::angular::runtime::{AttributeMarker, Identifier};
+use crate::html::{Attribute, AttributeKind};
+use crate::treaty::ast::{AstNode, Element, Expression};
+
+use super::defer::PendingTrigger;
+use super::expression::{compile_expression, PipeBinding};
+use super::naming::to_hyphen_case;
+use super::script::Script;
+use super::{instruction, runtime, string_literal};
+
+/// The compiled template function and the `ɵɵdefineComponent` fields that describe it.
+pub struct CompiledTemplate {
+    pub function: String,
+    pub decls: usize,
+    pub vars: usize,
+    pub consts: Vec<String>,
+    /// Imported components used as elements, for the `dependencies` array.
+    pub dependencies: Vec<String>,
+    /// Embedded view templates and track functions, declared at the top level of the module.
+    pub declarations: Vec<String>,
+    pub diagnostics: Vec<OxcDiagnostic>,
+}
+
+/// Instructions of a single view, split by render mode.
+#[derive(Default)]
+pub(super) struct View {
+    /// Name of the template function, without the `_Template` suffix.
+    pub(super) name: String,
+    /// Nesting level of the view; the component template is at 0.
+    pub(super) depth: usize,
+    pub(super) creation: Vec<String>,
+    pub(super) update: Vec<String>,
+    pub(super) slot: usize,
+    pub(super) vars: usize,
+    /// Variables of the pipe bindings, which Angular places after those of the instructions.
+    pipe_vars: usize,
+    /// Slot selected by the update instructions emitted so far.
+    selected: usize,
+    /// What the update block reads from ancestor views and references.
+    reads: Reads,
+    temporaries: usize,
+    /// `_rN`, holding the view for the listeners that restore it.
+    pub(super) current_view: Option<String>,
+    /// Slots of the elements declaring a `#reference`.
+    pub(super) references: Vec<(String, usize)>,
+    /// Element triggers of `@defer` blocks, resolved once every reference of the view is known.
+    pub(super) pending_triggers: Vec<PendingTrigger>,
+}
+
+impl View {
+    pub(super) fn allocate_slot(&mut self) -> usize {
+        let slot = self.slot;
+        self.slot += 1;
+        slot
+    }
+
+    /// Declares a temporary variable in the update block, returning its name.
+    pub(super) fn temporary(&mut self) -> String {
+        self.temporaries += 1;
+        format!("tmp_{}_0", self.temporaries - 1)
+    }
+
+    /// Declares a pipe in the creation block, returning its binding with `args`, the value first.
+    fn pipe(&mut self, name: &str, args: Vec<String>) -> String {
+        let slot = self.allocate_slot();
+        self.creation.push(super::instruction(
+            Identifier::Pipe,
+            &[slot.to_string(), string_literal(name)],
+        ));
+        // Offset by the variables of the instructions once they are all known.
+        let offset = format!("{}{}{}", PIPE_OFFSET, self.pipe_vars, PIPE_OFFSET);
+        self.pipe_vars += args.len() + 1;
+        let (identifier, args) = match args.len() {
+            1 => (Identifier::PipeBind1, args),
+            2 => (Identifier::PipeBind2, args),
+            3 => (Identifier::PipeBind3, args),
+            4 => (Identifier::PipeBind4, args),
+            _ => (
+                Identifier::PipeBindV,
+                vec![format!("[{}]", args.join(", "))],
+            ),
+        };
+        let mut all = vec![slot.to_string(), offset];
+        all.extend(args);
+        format!("{}({})", runtime(identifier), all.join(", "))
+    }
+
+    /// Resolves the offsets of the pipe bindings, once every instruction of the view is known.
+    pub(super) fn resolve_pipe_offsets(&mut self) {
+        let vars = self.vars;
+        for instruction in &mut self.update {
+            if !instruction.contains(PIPE_OFFSET) {
+                continue;
+            }
+            *instruction = instruction
+                .split(PIPE_OFFSET)
+                .enumerate()
+                .map(|(index, part)| match part.parse::<usize>() {
+                    Ok(offset) if index % 2 == 1 => (vars + offset).to_string(),
+                    _ => part.to_string(),
+                })
+                .collect();
+        }
+        self.vars += self.pipe_vars;
+        self.pipe_vars = 0;
+    }
+
+    /// Adds an update instruction for `slot`, advancing the selected slot to it first.
+    pub(super) fn update(&mut self, slot: usize, instruction: String) {
+        if slot > self.selected {
+            let delta = slot - self.selected;
+            let args = if delta == 1 {
+                vec![]
+            } else {
+                vec![delta.to_string()]
+            };
+            self.update
+                .push(super::instruction(Identifier::Advance, &args));
+            self.selected = slot;
+        }
+        self.update.push(instruction);
+    }
+}
+
+/// Marks the offsets of pipe bindings in update instructions until they are resolved.
+const PIPE_OFFSET: char = '\u{1}';
+
+/// Marks the slots of references in the generated code until their elements are reached.
+const REFERENCE_SLOT: char = '\u{2}';
+
+/// Part of a run of text: static text or an interpolated expression.
+enum TextPart<'n> {
+    Static(&'n str),
+    Interpolation(&'n Expression),
+}
+
+/// A template variable of the view that declares it.
+pub(super) struct Local {
+    pub(super) name: String,
+    pub(super) read: Read,
+}
+
+pub(super) enum Read {
+    /// The context of the view itself.
+    Context,
+    /// A property of the context, e.g. `$implicit`.
+    Property(String),
+    /// A `#reference`, read with `ɵɵreference` from the slot after its element. Holds the index
+    /// of the reference, as the slot is only known once the element is reached.
+    Reference(usize),
+}
+
+/// Ancestor contexts and references read by a function of a view, which must be declared before
+/// reading them.
+#[derive(Default)]
+pub(super) struct Reads {
+    /// Ancestor contexts, by distance from the view.
+    contexts: BTreeSet<usize>,
+    /// References as `(variable, index)`, by distance from the view.
+    references: BTreeMap<usize, Vec<(String, usize)>>,
+}
+
+impl Reads {
+    pub(super) fn is_empty(&self) -> bool {
+        self.contexts.is_empty() && self.references.is_empty()
+    }
+
+    /// Declares the contexts and references, each `ɵɵnextContext` call continuing from the view
+    /// the previous one reached.
+    pub(super) fn declarations(&self) -> Vec<String> {
+        let distances: BTreeSet<_> = self
+            .contexts
+            .iter()
+            .chain(self.references.keys())
+            .copied()
+            .collect();
+        let mut declarations = Vec::new();
+        let mut reached = 0;
+        for distance in distances {
+            if distance > reached {
+                let delta = distance - reached;
+                let args = if delta == 1 {
+                    String::new()
+                } else {
+                    delta.to_string()
+                };
+                let call = format!("{}({});", runtime(Identifier::NextContext), args);
+                declarations.push(if self.contexts.contains(&distance) {
+                    format!("const ctx_r{} = {}", distance, call)
+                } else {
+                    call
+                });
+                reached = distance;
+            }
+            for (variable, index) in self.references.get(&distance).into_iter().flatten() {
+                declarations.push(format!(
+                    "const {} = {}({});",
+                    variable,
+                    runtime(Identifier::Reference),
+                    reference_slot(*index)
+                ));
+            }
+        }
+        declarations
+    }
+}
+
+pub(super) struct TemplateCompiler<'a> {
+    /// Its imports name the components, and its objects can be spread onto elements.
+    pub(super) script: &'a Script,
+    /// Components loaded by `@defer` blocks, with the `import()` loading each of them.
+    pub(super) lazy_components: &'a [(String, String)],
+    /// Template variables of the view being compiled and each of its ancestors.
+    scopes: Vec<Vec<Local>>,
+    /// Slots of the references declared so far, by index, each set once its element is reached.
+    reference_slots: Vec<Option<usize>>,
+    consts: Vec<String>,
+    dependencies: Vec<String>,
+    pub(super) declarations: Vec<String>,
+    pub(super) track_functions: usize,
+    /// Views held in `_rN` variables so far.
+    pub(super) current_views: usize,
+    pub(super) diagnostics: Vec<OxcDiagnostic>,
+}
+
+pub fn compile_template(
+    name: &str,
+    nodes: &[AstNode],
+    script: &Script,
+    lazy_components: &[(String, String)],
+) -> CompiledTemplate {
+    let mut compiler = TemplateCompiler {
+        script,
+        lazy_components,
+        scopes: vec![Vec::new()],
+        reference_slots: Vec::new(),
+        consts: Vec::new(),
+        dependencies: Vec::new(),
+        declarations: Vec::new(),
+        track_functions: 0,
+        current_views: 0,
+        diagnostics: Vec::new(),
+    };
+    let mut view = View {
+        name: name.to_string(),
+        ..View::default()
+    };
+    compiler.declare_references(nodes);
+    compiler.visit_nodes(&mut view, nodes);
+    compiler.resolve_triggers(&mut view);
+    view.resolve_pipe_offsets();
+
+    let slots = &compiler.reference_slots;
+    CompiledTemplate {
+        function: resolve_reference_slots(&render_function(&view, "  "), slots),
+        decls: view.slot,
+        vars: view.vars,
+        consts: compiler.consts,
+        dependencies: compiler.dependencies,
+        declarations: compiler
+            .declarations
+            .iter()
+            .map(|declaration| resolve_reference_slots(declaration, slots))
+            .collect(),
+        diagnostics: compiler.diagnostics,
+    }
+}
+
+impl<'a> TemplateCompiler<'a> {
+    pub(super) fn visit_nodes(&mut self, view: &mut View, nodes: &[AstNode]) {
+        // Adjacent text and interpolations render as a single text node.
+        let mut text = Vec::new();
+        for node in nodes {
+            match node {
+                AstNode::Text(t) => text.push(TextPart::Static(&t.value)),
+                AstNode::TemplateExpression(e) => text.push(TextPart::Interpolation(&e.expression)),
+                _ => self.visit_text(view, &mut text),
+            }
+            match node {
+                AstNode::Element(element) => self.visit_element(view, element),
+                // Comments are not rendered, and scripts and styles never reach the template.
+                AstNode::Comment(_) | AstNode::JavaScript(_) | AstNode::Style(_) => {}
+                AstNode::Text(_) | AstNode::TemplateExpression(_) => {}
+                AstNode::If(block) => self.visit_if(view, block),
+                AstNode::For(block) => self.visit_for(view, block),
+                AstNode::Switch(block) => self.visit_switch(view, block),
+                AstNode::Defer(block) => self.visit_defer(view, block),
+            }
+        }
+        self.visit_text(view, &mut text);
+    }
+
+    fn visit_element(&mut self, view: &mut View, element: &Element) {
+        let slot = view.allocate_slot();
+        let tag_name = self.tag_name(element);
+
+        let mut args = vec![slot.to_string(), string_literal(&tag_name)];
+        let attributes = self.attributes_const(&element.attributes);
+        if let Some(local_refs) = self.local_refs(view, slot, element) {
+            args.push(attributes.map_or_else(|| "null".to_string(), |index| index.to_string()));
+            args.push(local_refs.to_string());
+        } else if let Some(index) = attributes {
+            args.push(index.to_string());
+        }
+        let start = view.creation.len();
+        view.creation
+            .push(instruction(Identifier::ElementStart, &args));
+        // Ahead of the children, whose update instructions target later slots, and of the
+        // listeners, as their pipes follow the element.
+        self.visit_bindings(view, slot, element);
+        self.visit_listeners(view, slot, element);
+        self.visit_nodes(view, &element.children);
+        if view.creation.len() == start + 1 {
+            // Nothing rendered inside, so the element is created in a single instruction.
+            view.creation[start] = instruction(Identifier::Element, &args);
+        } else {
+            view.creation.push(instruction(Identifier::ElementEnd, &[]));
+        }
+    }
+
+    /// Emits the pending run of text, if any, and clears it.
+    fn visit_text(&mut self, view: &mut View, parts: &mut Vec<TextPart>) {
+        let interpolations = parts
+            .iter()
+            .filter(|part| matches!(part, TextPart::Interpolation(_)))
+            .count();
+        if interpolations == 0 {
+            // Whitespace is not preserved: runs collapse to a single space and blank text is dropped.
+            let value: String = parts
+                .drain(..)
+                .map(|part| match part {
+                    TextPart::Static(value) => value,
+                    TextPart::Interpolation(_) => "",
+                })
+                .collect();
+            if !value.trim().is_empty() {
+                let slot = view.allocate_slot();
+                view.creation.push(instruction(
+                    Identifier::Text,
+                    &[
+                        slot.to_string(),
+                        string_literal(&collapse_whitespace(&value)),
+                    ],
+                ));
+            }
+            return;
+        }
+
+        let slot = view.allocate_slot();
+        view.creation
+            .push(instruction(Identifier::Text, &[slot.to_string()]));
+
+        // Static strings and expressions alternate, starting and ending with a string.
+        let mut args = vec![String::new()];
+        for part in parts.drain(..) {
+            match part {
+                TextPart::Static(value) => args.last_mut().unwrap().push_str(value),
+                TextPart::Interpolation(expression) => {
+                    args.push(self.expression(view, expression));
+                    args.push(String::new());
+                }
+            }
+        }
+        for (index, arg) in args.iter_mut().enumerate() {
+            if index % 2 == 0 {
+                *arg = string_literal(&collapse_whitespace(arg));
+            }
+        }
+        view.vars += interpolations;
+
+        let update = match interpolations {
+            1 if args[0] == "\"\"" && args[2] == "\"\"" => {
+                instruction(Identifier::TextInterpolate, &args[1..2])
+            }
+            1..=8 => instruction(text_interpolate(interpolations), &args),
+            _ => instruction(
+                Identifier::TextInterpolateV,
+                &[format!("[{}]", args.join(", "))],
+            ),
+        };
+        view.update(slot, update);
+    }
+
+    /// Element name in the generated code; imported components are recorded as dependencies.
+    pub(super) fn tag_name(&mut self, element: &Element) -> String {
+        if self.script.imported_names.contains(&element.tag_name) {
+            // Lazy components are left to the dependency resolver of their `@defer` block.
+            let lazy = self
+                .lazy_components
+                .iter()
+                .any(|(name, _)| *name == element.tag_name);
+            if !lazy && !self.dependencies.contains(&element.tag_name) {
+                self.dependencies.push(element.tag_name.clone());
+            }
+            to_hyphen_case(&element.tag_name)
+        } else {
+            element.tag_name.clone()
+        }
+    }
+
+    /// Compiles an expression read by the update block of `view`, declaring its pipes.
+    pub(super) fn expression(&mut self, view: &mut View, expression: &Expression) -> String {
+        let scopes = &self.scopes;
+        let depth = view.depth;
+        let mut reads = std::mem::take(&mut view.reads);
+        let resolve = &mut |name: &str| resolve(scopes, depth, &mut reads, name);
+        let pipe = &mut |name: &str, args: Vec<String>| view.pipe(name, args);
+        let code = compile_or_report(expression, resolve, Some(pipe), &mut self.diagnostics);
+        view.reads = reads;
+        code
+    }
+
+    /// Compiles an expression of a view at `depth`, recording what it reads into `reads`.
+    /// `special` resolves names ahead of the template variables, such as `$event`.
+    pub(super) fn expression_reading(
+        &mut self,
+        depth: usize,
+        reads: &mut Reads,
+        expression: &Expression,
+        special: &mut dyn FnMut(&str) -> Option<String>,
+    ) -> String {
+        let scopes = &self.scopes;
+        let resolve =
+            &mut |name: &str| special(name).unwrap_or_else(|| resolve(scopes, depth, reads, name));
+        compile_or_report(expression, resolve, None, &mut self.diagnostics)
+    }
+
+    /// Declares a template variable in the view being compiled.
+    pub(super) fn declare(&mut self, local: Local) {
+        self.scopes.last_mut().unwrap().push(local);
+    }
+
+    /// Declares the `#references` of the elements of a view ahead of compiling it, as they can
+    /// be read anywhere in the view, even before their element. Those inside blocks belong to
+    /// the embedded views of the blocks.
+    fn declare_references(&mut self, nodes: &[AstNode]) {
+        for node in nodes {
+            let AstNode::Element(element) = node else {
+                continue;
+            };
+            for attribute in &element.attributes {
+                if let AttributeKind::Reference(name) = &attribute.kind {
+                    self.declare(Local {
+                        name: name.clone(),
+                        read: Read::Reference(self.reference_slots.len()),
+                    });
+                    self.reference_slots.push(None);
+                }
+            }
+            self.declare_references(&element.children);
+        }
+    }
+
+    /// Records the slot of a reference declared by the view being compiled, in document order.
+    pub(super) fn assign_reference_slot(&mut self, name: &str, slot: usize) {
+        let slots = &mut self.reference_slots;
+        let scope = self.scopes.last().unwrap();
+        let pending = scope.iter().find_map(|local| match local.read {
+            Read::Reference(index) if local.name == name && slots[index].is_none() => Some(index),
+            _ => None,
+        });
+        if let Some(index) = pending {
+            slots[index] = Some(slot);
+        }
+    }
+
+    pub(super) fn compile(
+        &mut self,
+        expression: &Expression,
+        resolve: &mut dyn FnMut(&str) -> String,
+    ) -> String {
+        compile_or_report(expression, resolve, None, &mut self.diagnostics)
+    }
+
+    /// Compiles `nodes` into an embedded view template declared at the top level, returning
+    /// the function name with its `decls` and `vars`.
+    pub(super) fn embedded_view(
+        &mut self,
+        parent: &View,
+        name: String,
+        locals: Vec<Local>,
+        nodes: &[AstNode],
+    ) -> (String, usize, usize) {
+        self.scopes.push(locals);
+        let mut view = View {
+            name,
+            depth: parent.depth + 1,
+            ..View::default()
+        };
+        self.declare_references(nodes);
+        self.visit_nodes(&mut view, nodes);
+        self.resolve_triggers(&mut view);
+        view.resolve_pipe_offsets();
+        self.scopes.pop();
+
+        self.declarations.push(render_function(&view, ""));
+        (format!("{}_Template", view.name), view.slot, view.vars)
+    }
+
+    /// Tag name and attributes index of the single root element of an embedded view, which
+    /// content projection matches against.
+    pub(super) fn root_element(&mut self, nodes: &[AstNode]) -> (String, String) {
+        match single_root_element(nodes) {
+            Some(element) => {
+                let tag_name = string_literal(&self.tag_name(element));
+                let attributes = self
+                    .attributes_const(&element.attributes)
+                    .map_or_else(|| "null".to_string(), |index| index.to_string());
+                (tag_name, attributes)
+            }
+            None => ("null".to_string(), "null".to_string()),
+        }
+    }
+
+    /// Adds the static attributes of an element to `consts`, returning their index.
+    fn attributes_const(&mut self, attributes: &[Attribute]) -> Option<usize> {
+        let mut statics = StaticAttributes::default();
+
+        // Bound properties and events, for directives to match against.
+        let mut bindings = Vec::new();
+
+        for attribute in attributes {
+            match &attribute.kind {
+                AttributeKind::Static if !attribute.name.starts_with('*') => {
+                    statics.push(attribute);
+                }
+                AttributeKind::Property(name) if name != "class" && name != "style" => {
+                    bindings.push(string_literal(name));
+                }
+                AttributeKind::Event(name) if !name.starts_with('@') => {
+                    bindings.push(string_literal(name));
+                }
+                AttributeKind::TwoWay(name) => {
+                    bindings.push(string_literal(name));
+                    bindings.push(string_literal(&format!("{}Change", name)));
+                }
+                AttributeKind::Spread(object) => {
+                    let keys = self.spread_object(object).map(|spread| &spread.keys);
+                    bindings.extend(
+                        keys.into_iter()
+                            .flatten()
+                            .map(|(key, _)| string_literal(key)),
+                    );
+                }
+                _ => {}
+            }
+        }
+        let mut values = statics.into_values();
+        if !bindings.is_empty() {
+            values.push((AttributeMarker::Bindings as u8).to_string());
+            values.extend(bindings);
+        }
+        if values.is_empty() {
+            return None;
+        }
+        Some(self.add_const(format!("[{}]", values.join(", "))))
+    }
+
+    pub(super) fn add_const(&mut self, value: String) -> usize {
+        if let Some(index) = self.consts.iter().position(|c| *c == value) {
+            return index;
+        }
+        self.consts.push(value);
+        self.consts.len() - 1
+    }
+}
+
+/// Static attributes as Angular lists them, e.g. in `consts` or `hostAttrs`: plain attributes
+/// first, then classes and styles, each section after its marker.
+#[derive(Default)]
+pub(super) struct StaticAttributes {
+    values: Vec<String>,
+    classes: Vec<String>,
+    styles: Vec<String>,
+}
+
+impl StaticAttributes {
+    pub(super) fn push(&mut self, attribute: &Attribute) {
+        match attribute.name.as_str() {
+            "class" => self
+                .classes
+                .extend(attribute.value.split_whitespace().map(string_literal)),
+            "style" => {
+                for declaration in attribute.value.split(';') {
+                    if let Some((property, value)) = declaration.split_once(':') {
+                        self.styles.push(string_literal(property.trim()));
+                        self.styles.push(string_literal(value.trim()));
+                    }
+                }
+            }
+            _ => {
+                self.values.push(string_literal(&attribute.name));
+                self.values.push(string_literal(&attribute.value));
+            }
+        }
+    }
+
+    pub(super) fn into_values(self) -> Vec<String> {
+        let mut values = self.values;
+        if !self.classes.is_empty() {
+            values.push((AttributeMarker::Classes as u8).to_string());
+            values.extend(self.classes);
+        }
+        if !self.styles.is_empty() {
+            values.push((AttributeMarker::Styles as u8).to_string());
+            values.extend(self.styles);
+        }
+        values
+    }
+}
+
+/// The element rendered by `nodes`, when it is the only thing they render.
+pub(super) fn single_root_element(nodes: &[AstNode]) -> Option<&Element> {
+    let mut roots = nodes.iter().filter(|node| match node {
+        AstNode::Text(text) => !text.value.trim().is_empty(),
+        AstNode::Comment(_) => false,
+        _ => true,
+    });
+    match (roots.next(), roots.next()) {
+        (Some(AstNode::Element(element)), None) => Some(element),
+        _ => None,
+    }
+}
+
+fn text_interpolate(interpolations: usize) -> Identifier {
+    match interpolations {
+        1 => Identifier::TextInterpolate1,
+        2 => Identifier::TextInterpolate2,
+        3 => Identifier::TextInterpolate3,
+        4 => Identifier::TextInterpolate4,
+        5 => Identifier::TextInterpolate5,
+        6 => Identifier::TextInterpolate6,
+        7 => Identifier::TextInterpolate7,
+        _ => Identifier::TextInterpolate8,
+    }
+}
+
+fn collapse_whitespace(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_whitespace() && c != '\u{a0}' {
+            if !result.ends_with(' ') {
+                result.push(' ');
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Compiles an expression, falling back to its source when it does not parse.
+fn compile_or_report(
+    expression: &Expression,
+    resolve: &mut dyn FnMut(&str) -> String,
+    pipe: Option<&mut PipeBinding>,
+    diagnostics: &mut Vec<OxcDiagnostic>,
+) -> String {
+    match compile_expression(expression, resolve, pipe) {
+        Ok(code) => code,
+        Err(errors) => {
+            diagnostics.extend(errors);
+            expression.source.clone()
+        }
+    }
+}
+
+/// Resolves a name read by a view at `depth`: a template variable of the view or one of its
+/// ancestors, otherwise a member of the component.
+fn resolve(scopes: &[Vec<Local>], depth: usize, reads: &mut Reads, name: &str) -> String {
+    let mut context = |distance: usize| {
+        if distance == 0 {
+            "ctx".to_string()
+        } else {
+            reads.contexts.insert(distance);
+            format!("ctx_r{}", distance)
+        }
+    };
+    for (level, scope) in scopes[..=depth].iter().enumerate().rev() {
+        if let Some(local) = scope.iter().find(|local| local.name == name) {
+            let distance = depth - level;
+            return match &local.read {
+                Read::Context => context(distance),
+                Read::Property(property) => format!("{}.{}", context(distance), property),
+                Read::Reference(index) => {
+                    let variable = format!("{}_r{}", name, reference_slot(*index));
+                    let references = reads.references.entry(distance).or_default();
+                    if !references.iter().any(|(v, _)| *v == variable) {
+                        references.push((variable.clone(), *index));
+                    }
+                    variable
+                }
+            };
+        }
+    }
+    format!("{}.{}", context(depth), name)
+}
+
+/// The slot of a reference, marked until every element of the template is reached.
+fn reference_slot(index: usize) -> String {
+    format!("{}{}{}", REFERENCE_SLOT, index, REFERENCE_SLOT)
+}
+
+/// Replaces the marked slots of references in `code` with the slots of their elements.
+fn resolve_reference_slots(code: &str, slots: &[Option<usize>]) -> String {
+    if !code.contains(REFERENCE_SLOT) {
+        return code.to_string();
+    }
+    code.split(REFERENCE_SLOT)
+        .enumerate()
+        .map(|(index, part)| match part.parse::<usize>() {
+            Ok(reference) if index % 2 == 1 => slots[reference].unwrap_or_default().to_string(),
+            _ => part.to_string(),
+        })
+        .collect()
+}
+
+/// Renders `function name(rf, ctx) { ... }`, with every line after the first indented by
+/// `indent`.
+fn render_function(view: &View, indent: &str) -> String {
+    let mut declarations = view.reads.declarations();
+    if view.temporaries > 0 {
+        let names: Vec<_> = (0..view.temporaries)
+            .map(|i| format!("tmp_{}_0", i))
+            .collect();
+        declarations.push(format!("let {};", names.join(", ")));
+    }
+    // Held before anything is created, for the listeners to restore.
+    let current_view: Vec<_> = view
+        .current_view
+        .iter()
+        .map(|variable| {
+            format!(
+                "const {} = {}();",
+                variable,
+                runtime(Identifier::GetCurrentView)
+            )
+        })
+        .collect();
+
+    let mut function = format!("function {}_Template(rf, ctx) {{", view.name);
+    let update: Vec<_> = declarations.iter().chain(&view.update).collect();
+    let creation: Vec<_> = current_view.iter().chain(&view.creation).collect();
+    for (mode, instructions) in [(1, creation), (2, update)] {
+        if view.creation.is_empty() && mode == 1 || view.update.is_empty() && mode == 2 {
+            continue;
+        }
+        function.push_str(&format!("\n{}  if (rf & {}) {{", indent, mode));
+        for instruction in instructions {
+            function.push_str(&format!("\n{}    {}", indent, instruction));
+        }
+        function.push_str(&format!("\n{}  }}", indent));
+    }
+    function.push_str(&format!("\n{}}}", indent));
+    function
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::treaty::lexer::Lexer;
+    use crate::treaty::parser::Parser as TreatyParser;
+
+    fn template(source_text: &str) -> CompiledTemplate {
+        let mut lexer = Lexer::new(source_text);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token() {
+            tokens.push(token);
+        }
+        let ast = TreatyParser::new(tokens).parse();
+        let (script, _) = Script::new(source_text, &ast);
+        let nodes: Vec<_> = ast
+            .nodes
+            .iter()
+            .filter(|node| !matches!(node, AstNode::JavaScript(_) | AstNode::Style(_)))
+            .cloned()
+            .collect();
+        compile_template("test", &nodes, &script, &[])
+    }
+
+    #[test]
+    fn reference_read_before_its_element() {
+        let template = template("<button (click)=\"go(box)\"></button><input #box>\n");
+        assert!(template.function.contains("i0.ɵɵreference(2)"));
+        assert!(template.function.contains("ctx.go(box_r2)"));
+        assert!(!template.function.contains(REFERENCE_SLOT));
+    }
+
+    #[test]
+    fn reference_read_by_an_earlier_embedded_view() {
+        let template = template("@if (open) {<p>{{ box.value }}</p>}\n<input #box>\n");
+        let declarations = template.declarations.join("\n");
+        assert!(declarations.contains("const box_r2 = i0.ɵɵreference(2);"));
+        assert!(!declarations.contains(REFERENCE_SLOT));
+    }
+
+    #[test]
+    fn reference_inside_a_block_stays_in_its_view() {
+        let template = template("{{ box }}\n@if (open) {<input #box>}\n");
+        assert!(template.function.contains("ctx.box"));
+    }
+
+    #[test]
+    fn spread_binds_each_key_as_an_input() {
+        let template = template(
+            "import { signal } from '@angular/core';\n\
+             interface Props { title: string; count: number }\n\
+             const card = { title: 'Hi', count: signal(1) };\n\
+             const state = signal({ open: true });\n\
+             let typed: Props;\n\
+             <app-card {...card}></app-card>\n\
+             <app-panel {...state} [title]=\"'x'\"></app-panel>\n\
+             <app-card { ...typed } />\n",
+        );
+        assert!(template.diagnostics.is_empty());
+        // The keys are bindings directives match their inputs against.
+        assert_eq!(
+            template.consts,
+            ["[3, \"title\", \"count\"]", "[3, \"open\", \"title\"]"]
+        );
+        assert!(template.function.contains(
+            "i0.ɵɵproperty(\"title\", ctx.card.title);\n      i0.ɵɵproperty(\"count\", ctx.card.count());"
+        ));
+        assert!(template
+            .function
+            .contains("i0.ɵɵproperty(\"open\", ctx.state().open);"));
+        assert!(template.function.contains(
+            "i0.ɵɵproperty(\"title\", ctx.typed.title);\n      i0.ɵɵproperty(\"count\", ctx.typed.count);"
+        ));
+        assert_eq!(template.vars, 6);
+    }
+
+    #[test]
+    fn spread_of_an_unknown_object_is_reported() {
+        let template = template("<p {...missing}></p>\n");
+        let messages: Vec<_> = template
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            ["Cannot resolve the keys of \"missing\" statically"]
+        );
+        assert!(!template.function.contains("ɵɵproperty"));
+    }
+
+    #[test]
+    fn text_interpolation_arity_and_static_parts() {
+        let nine = "{{ a }}{{ b }}{{ c }}{{ d }}{{ e }}{{ f }}{{ g }}{{ h }}{{ i }}";
+        for (text, update, vars) in [
+            ("{{ a }}", "i0.ɵɵtextInterpolate(ctx.a);", 1),
+            ("Hi {{ a }}!", "i0.ɵɵtextInterpolate1(\"Hi \", ctx.a, \"!\");", 1),
+            (
+                "{{ a }}:\n   {{ b }}",
+                "i0.ɵɵtextInterpolate2(\"\", ctx.a, \": \", ctx.b, \"\");",
+                2,
+            ),
+            (
+                nine,
+                "i0.ɵɵtextInterpolateV([\"\", ctx.a, \"\", ctx.b, \"\", ctx.c, \"\", ctx.d, \"\", ctx.e, \"\", ctx.f, \"\", ctx.g, \"\", ctx.h, \"\", ctx.i, \"\"]);",
+                9,
+            ),
+        ] {
+            let template = template(&format!("<p>{}</p>\n", text));
+            assert!(template.diagnostics.is_empty(), "{}", text);
+            assert!(template.function.contains("i0.ɵɵtext(1);"), "{}", text);
+            assert!(
+                template.function.contains(update),
+                "{}\n{}",
+                text,
+                template.function
+            );
+            assert_eq!(template.vars, vars, "{}", text);
+        }
+
+        let template = template("<p>Hi\n   there</p>\n");
+        assert!(template.function.contains("i0.ɵɵtext(1, \"Hi there\");"));
+        assert!(!template.function.contains("ɵɵtextInterpolate"));
+        assert_eq!(template.vars, 0);
+    }
+}
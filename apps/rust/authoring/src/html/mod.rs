@@ -1,4 +1,4 @@
 mod parser;
 mod tokenizer;
 pub use self::parser::{DomNode, Parser};
-pub use self::tokenizer::HtmlTokenizer;
+pub use self::tokenizer::{is_void_element, Attribute, AttributeKind, HtmlTokenizer, Token};
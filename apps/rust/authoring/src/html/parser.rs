@@ -1,5 +1,6 @@
 use super::tokenizer::{Attribute, Token};
 use std::fmt;
+use tracing::trace;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DomNode {
@@ -26,9 +27,9 @@ impl fmt::Display for ElementNode {
         }
         write!(f, "])")?;
         if !self.children.is_empty() {
-            write!(f, " {{\n")?;
+            writeln!(f, " {{")?;
             for child in &self.children {
-                write!(f, "    {}\n", child)?;
+                writeln!(f, "    {}", child)?;
             }
             write!(f, "}}")?;
         }
@@ -74,7 +75,10 @@ impl Parser {
                         nodes.push(node);
                     }
                 }
-                _ => self.current += 1,
+                token => {
+                    trace!(?token, "skipping unexpected token");
+                    self.current += 1;
+                }
             }
         }
 
@@ -94,12 +98,8 @@ impl Parser {
                                 self.current += 1;
                                 break;
                             }
-                            Token::Text(_) => {
-                                if let Some(token) = self.tokens.get(self.current).cloned() {
-                                    if let Token::Text(text) = token {
-                                        children.push(DomNode::Text(text));
-                                    }
-                                }
+                            Token::Text(text) => {
+                                children.push(DomNode::Text(text.clone()));
                                 self.current += 1;
                             }
                             _ => {
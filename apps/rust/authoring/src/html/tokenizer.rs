@@ -1,5 +1,7 @@
 use std::fmt;
 
+use oxc_span::Span;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
     StartTag(String, Vec<Attribute>),
@@ -11,24 +13,131 @@ pub enum Token {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Attribute {
-    name: String,
-    value: String,
+    pub kind: AttributeKind,
+    /// The name as written, e.g. `[class.active]`.
+    pub name: String,
+    /// Empty for a valueless attribute, such as `disabled` or `#ref`.
+    pub value: String,
+    /// Covers `name="value"`.
+    pub span: Span,
+    /// Covers the value between the quotes, empty at the end of the name when there is none.
+    pub value_span: Span,
+}
+
+/// What an attribute binds, told apart by its name.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AttributeKind {
+    /// `name="value"`, set once.
+    Static,
+    /// `[name]="expr"`
+    Property(String),
+    /// `(name)="statement"`
+    Event(String),
+    /// `[(name)]="signal"`, a property with a listener for `nameChange`.
+    TwoWay(String),
+    /// `[attr.name]="expr"`
+    Attribute(String),
+    /// `[class.name]="expr"`
+    Class(String),
+    /// `[style.name]="expr"` or `[style.name.unit]="expr"`
+    Style { name: String, unit: Option<String> },
+    /// `#name`, a template reference to the element.
+    Reference(String),
+    /// `@name` or `[@name]="expr"`, an animation trigger.
+    Animation(String),
+    /// `{...object}`, binding each key of the object to the input of the same name.
+    Spread(String),
+}
+
+impl AttributeKind {
+    pub fn from_name(name: &str) -> Self {
+        if let Some(inner) = name.strip_prefix('{').and_then(|n| n.strip_suffix('}')) {
+            if let Some(object) = inner.trim().strip_prefix("...") {
+                return Self::Spread(object.trim().to_string());
+            }
+        }
+        if let Some(inner) = name.strip_prefix("[(").and_then(|n| n.strip_suffix(")]")) {
+            return Self::TwoWay(inner.to_string());
+        }
+        if let Some(inner) = name.strip_prefix('[').and_then(|n| n.strip_suffix(']')) {
+            if let Some(animation) = inner.strip_prefix('@') {
+                return Self::Animation(animation.to_string());
+            }
+            if let Some(attribute) = inner.strip_prefix("attr.") {
+                return Self::Attribute(attribute.to_string());
+            }
+            if let Some(class) = inner.strip_prefix("class.") {
+                return Self::Class(class.to_string());
+            }
+            if let Some(style) = inner.strip_prefix("style.") {
+                return match style.split_once('.') {
+                    Some((name, unit)) => Self::Style {
+                        name: name.to_string(),
+                        unit: Some(unit.to_string()),
+                    },
+                    None => Self::Style {
+                        name: style.to_string(),
+                        unit: None,
+                    },
+                };
+            }
+            return Self::Property(inner.to_string());
+        }
+        if let Some(event) = name.strip_prefix('(').and_then(|n| n.strip_suffix(')')) {
+            return Self::Event(event.to_string());
+        }
+        if let Some(reference) = name.strip_prefix('#') {
+            return Self::Reference(reference.to_string());
+        }
+        if let Some(animation) = name.strip_prefix('@') {
+            return Self::Animation(animation.to_string());
+        }
+        Self::Static
+    }
 }
 
 impl fmt::Display for Attribute {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}=\"{}\"", self.name, self.value)
+        if self.value.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{}=\"{}\"", self.name, self.value)
+        }
     }
 }
 
+/// Elements that never have children or a closing tag.
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+pub fn is_void_element(tag_name: &str) -> bool {
+    VOID_ELEMENTS.contains(&tag_name.to_ascii_lowercase().as_str())
+}
+
 pub struct HtmlTokenizer<'a> {
     input: &'a str,
     pos: usize,
+    offset: usize, // Position of `input` within the whole source, added to every span
 }
 
 impl<'a> HtmlTokenizer<'a> {
     pub fn new(input: &'a str) -> Self {
-        Self { input, pos: 0 }
+        Self::with_offset(input, 0)
+    }
+
+    /// Tokenizes a fragment that starts at byte `offset` of a larger source.
+    pub fn with_offset(input: &'a str, offset: usize) -> Self {
+        Self {
+            input,
+            pos: 0,
+            offset,
+        }
+    }
+
+    fn span(&self, start: usize, end: usize) -> Span {
+        Span::new((self.offset + start) as u32, (self.offset + end) as u32)
     }
 
     pub fn next_token(&mut self) -> Option<Token> {
@@ -76,9 +185,9 @@ impl<'a> HtmlTokenizer<'a> {
         self.pos += 4;
 
         let start_pos = self.pos;
-        while !self.starts_with("-->") && self.pos < self.input.len() {
-            self.pos += 1;
-        }
+        self.pos = self.input[start_pos..]
+            .find("-->")
+            .map_or(self.input.len(), |end| start_pos + end);
         let comment = self.input[start_pos..self.pos].to_string();
 
         if self.starts_with("-->") {
@@ -92,8 +201,8 @@ impl<'a> HtmlTokenizer<'a> {
     fn consume_end_tag(&mut self) -> Option<Token> {
         self.pos += 2;
         let tag_name = self.consume_while(|c| c != '>');
-        self.pos += 1;
-        Some(Token::EndTag(tag_name))
+        self.pos = (self.pos + 1).min(self.input.len());
+        Some(Token::EndTag(tag_name.trim().to_string()))
     }
 
     fn consume_start_or_self_closing_tag(&mut self) -> Option<Token> {
@@ -104,39 +213,128 @@ impl<'a> HtmlTokenizer<'a> {
             self.pos += 2;
             Some(Token::SelfClosingTag(tag_name, attributes))
         } else {
-            self.pos += 1;
+            self.pos = (self.pos + 1).min(self.input.len());
             Some(Token::StartTag(tag_name, attributes))
         }
     }
 
     fn consume_attributes(&mut self) -> Vec<Attribute> {
-    let mut attributes = Vec::new();
-    while self.pos < self.input.len() && !self.starts_with(">") && !self.starts_with("/>") {
-        self.skip_whitespace();
-        if self.starts_with(">") || self.starts_with("/>") {
-            break;
-        }
-        let name = self.consume_while(|c| c != '=' && !c.is_whitespace());
-        self.skip_whitespace();
-        if self.pos < self.input.len() && self.input[self.pos..].starts_with("=") {
-            self.pos += 1; // Skip '='
+        let mut attributes = Vec::new();
+        while self.pos < self.input.len() && !self.starts_with(">") && !self.starts_with("/>") {
             self.skip_whitespace();
-            if self.pos < self.input.len() {
-                let quote = self.input[self.pos..].chars().next().unwrap();
-                if quote == '"' || quote == '\'' {
-                    self.pos += 1;
-                    let value = self.consume_while(|c| c != quote);
-                    self.pos += 1;
-                    attributes.push(Attribute { name, value });
-                }
+            if self.starts_with(">") || self.starts_with("/>") {
+                break;
+            }
+            let name_start = self.pos;
+            let name = if self.starts_with("{") {
+                // A spread, which may have whitespace inside its braces
+                let length = self.input[self.pos..].find('}').map_or(0, |end| end + 1);
+                self.pos += length;
+                self.input[name_start..self.pos].to_string()
+            } else {
+                self.consume_while(|c| c != '=' && c != '>' && c != '/' && !c.is_whitespace())
+            };
+            if name.is_empty() && !self.starts_with("=") {
+                self.pos = (self.pos + 1).min(self.input.len()); // Skip a stray '/'
+                continue;
             }
+            let name_end = self.pos;
+            self.skip_whitespace();
+            let (value, value_span) = if self.starts_with("=") {
+                self.pos += 1; // Skip '='
+                self.skip_whitespace();
+                match self.input[self.pos..].chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        self.pos += 1;
+                        let value_start = self.pos;
+                        let value = self.consume_while(|c| c != quote);
+                        let value_span = self.span(value_start, self.pos);
+                        self.pos = (self.pos + 1).min(self.input.len());
+                        (value, value_span)
+                    }
+                    // Unquoted, up to the next whitespace
+                    _ => {
+                        let value_start = self.pos;
+                        let value = self.consume_while(|c| c != '>' && !c.is_whitespace());
+                        (value, self.span(value_start, self.pos))
+                    }
+                }
+            } else {
+                // Valueless, the whitespace after the name belongs to whatever comes next
+                self.pos = name_end;
+                (String::new(), self.span(name_end, name_end))
+            };
+            let span = self.span(name_start, self.pos);
+            attributes.push(Attribute {
+                kind: AttributeKind::from_name(&name),
+                name,
+                value,
+                span,
+                value_span,
+            });
         }
+        attributes
     }
-    attributes
-}
 
     fn consume_text(&mut self) -> Option<Token> {
         let text = self.consume_while(|c| c != '<');
         Some(Token::Text(text.trim().to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comment_with_non_ascii_text() {
+        let mut tokenizer = HtmlTokenizer::new("<!-- café --><p>a</p>");
+        assert_eq!(
+            tokenizer.next_token(),
+            Some(Token::Comment(" café ".to_string()))
+        );
+        assert!(matches!(tokenizer.next_token(), Some(Token::StartTag(name, _)) if name == "p"));
+    }
+
+    #[test]
+    fn attribute_kinds() {
+        let cases = [
+            ("title", AttributeKind::Static),
+            ("[value]", AttributeKind::Property("value".to_string())),
+            ("(click)", AttributeKind::Event("click".to_string())),
+            ("[(checked)]", AttributeKind::TwoWay("checked".to_string())),
+            ("[attr.role]", AttributeKind::Attribute("role".to_string())),
+            ("[class.active]", AttributeKind::Class("active".to_string())),
+            (
+                "[style.width.px]",
+                AttributeKind::Style {
+                    name: "width".to_string(),
+                    unit: Some("px".to_string()),
+                },
+            ),
+            (
+                "[style.color]",
+                AttributeKind::Style {
+                    name: "color".to_string(),
+                    unit: None,
+                },
+            ),
+            ("#input", AttributeKind::Reference("input".to_string())),
+            ("@fade", AttributeKind::Animation("fade".to_string())),
+            ("[@fade]", AttributeKind::Animation("fade".to_string())),
+            ("{...props}", AttributeKind::Spread("props".to_string())),
+            ("{ ...props }", AttributeKind::Spread("props".to_string())),
+        ];
+        for (name, kind) in cases {
+            assert_eq!(AttributeKind::from_name(name), kind, "{}", name);
+        }
+    }
+
+    #[test]
+    fn unclosed_tags_at_the_end_of_input() {
+        for input in ["<p", "<p title", "<p /", "</p"] {
+            let mut tokenizer = HtmlTokenizer::new(input);
+            while tokenizer.next_token().is_some() {}
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod angular;
+pub mod compiler;
+pub mod html;
+pub mod logging;
+pub mod treaty;
@@ -0,0 +1,281 @@
+//! What the crate does, reported as [`tracing`] events rather than printed, so that stdout only
+//! ever carries generated code. Hosts with their own subscriber receive the events as they are;
+//! otherwise [`collect`] gathers them while a closure runs, e.g. to return them with a transform
+//! result, and [`init_stderr`] writes them to stderr for the command line.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// Which events to keep: a level for every module and levels for some, written like `RUST_LOG`,
+/// e.g. `warn,rust_authoring::angular=debug`. The level of the most specific module applies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    /// The most verbose level kept, `None` keeping nothing.
+    level: Option<Level>,
+    modules: Vec<(String, Option<Level>)>,
+}
+
+impl Default for LogFilter {
+    /// Warnings and errors.
+    fn default() -> Self {
+        Self::new(Level::WARN)
+    }
+}
+
+impl LogFilter {
+    pub fn new(level: Level) -> Self {
+        Self {
+            level: Some(level),
+            modules: Vec::new(),
+        }
+    }
+
+    /// Keeps nothing.
+    pub fn off() -> Self {
+        Self {
+            level: None,
+            modules: Vec::new(),
+        }
+    }
+
+    /// Keeps events of `module` and its submodules up to `level`, e.g. `rust_authoring::treaty`.
+    pub fn with_module(mut self, module: impl Into<String>, level: Level) -> Self {
+        self.modules.push((module.into(), Some(level)));
+        self
+    }
+
+    /// Reads comma-separated directives, each a level such as `debug` or `off`, or a module with
+    /// its level such as `rust_authoring::html=trace`. Directives that do not parse are ignored.
+    pub fn parse(directives: &str) -> Self {
+        let mut filter = Self::default();
+        for directive in directives.split(',').map(str::trim) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    if let Some(level) = parse_level(level) {
+                        filter.modules.push((module.trim().to_string(), level));
+                    }
+                }
+                None => {
+                    if let Some(level) = parse_level(directive) {
+                        filter.level = level;
+                    }
+                }
+            }
+        }
+        filter
+    }
+
+    /// Whether an event of `target`, the module reporting it, is kept at `level`.
+    pub fn enabled(&self, target: &str, level: &Level) -> bool {
+        let max = self
+            .modules
+            .iter()
+            .filter(|(module, _)| {
+                target == module
+                    || target
+                        .strip_prefix(module.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.level, |(_, level)| *level);
+        max.is_some_and(|max| *level <= max)
+    }
+}
+
+/// `None` for `off`.
+fn parse_level(level: &str) -> Option<Option<Level>> {
+    match level.trim().to_ascii_lowercase().as_str() {
+        "off" => Some(None),
+        "error" => Some(Some(Level::ERROR)),
+        "warn" => Some(Some(Level::WARN)),
+        "info" => Some(Some(Level::INFO)),
+        "debug" => Some(Some(Level::DEBUG)),
+        "trace" => Some(Some(Level::TRACE)),
+        _ => None,
+    }
+}
+
+/// An event, with its fields formatted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEvent {
+    pub level: Level,
+    /// The module that reported it, e.g. `rust_authoring::angular::transformers::dependency`.
+    pub target: String,
+    pub message: String,
+    /// The other fields, e.g. `("class", "AppComponent")`.
+    pub fields: Vec<(String, String)>,
+}
+
+impl fmt::Display for LogEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>5} {}: {}", self.level, self.target, self.message)?;
+        for (name, value) in &self.fields {
+            write!(f, " {}={}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Visit for LogEvent {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields
+                .push((field.name().to_string(), value.to_string()));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields
+                .push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+}
+
+enum Sink {
+    Collect(Mutex<Vec<LogEvent>>),
+    Stderr,
+}
+
+/// A subscriber keeping the events `filter` selects. Spans are not tracked.
+struct Logger {
+    filter: LogFilter,
+    sink: Sink,
+}
+
+impl Subscriber for Logger {
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        // Collectors come and go with each transform, so ask every time.
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.filter.enabled(metadata.target(), metadata.level())
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let metadata = event.metadata();
+        let mut log_event = LogEvent {
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: String::new(),
+            fields: Vec::new(),
+        };
+        event.record(&mut log_event);
+        match &self.sink {
+            Sink::Collect(events) => events.lock().unwrap().push(log_event),
+            Sink::Stderr => eprintln!("{}", log_event),
+        }
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+/// Runs `f`, returning the events `filter` selects from those it reports instead of sending them
+/// to the host's subscriber.
+pub fn collect<R>(filter: &LogFilter, f: impl FnOnce() -> R) -> (R, Vec<LogEvent>) {
+    let logger = Arc::new(Logger {
+        filter: filter.clone(),
+        sink: Sink::Collect(Mutex::new(Vec::new())),
+    });
+    let result = tracing::subscriber::with_default(Arc::clone(&logger), f);
+    let events = match &logger.sink {
+        Sink::Collect(events) => std::mem::take(&mut *events.lock().unwrap()),
+        Sink::Stderr => Vec::new(),
+    };
+    (result, events)
+}
+
+/// Writes the events `filter` selects to stderr, unless a subscriber was installed already.
+pub fn init_stderr(filter: LogFilter) {
+    let _ = tracing::subscriber::set_global_default(Logger {
+        filter,
+        sink: Sink::Stderr,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::Level;
+
+    use super::{collect, LogEvent, LogFilter};
+
+    #[test]
+    fn default_keeps_warnings() {
+        let filter = LogFilter::parse("");
+        assert_eq!(filter, LogFilter::default());
+        assert!(filter.enabled("rust_authoring::angular", &Level::WARN));
+        assert!(!filter.enabled("rust_authoring::angular", &Level::INFO));
+    }
+
+    #[test]
+    fn most_specific_module_applies() {
+        let filter = LogFilter::parse(
+            "error, rust_authoring::angular=debug, rust_authoring::angular::imports=off",
+        );
+        assert!(filter.enabled("rust_authoring::html", &Level::ERROR));
+        assert!(!filter.enabled("rust_authoring::html", &Level::WARN));
+        assert!(filter.enabled("rust_authoring::angular", &Level::DEBUG));
+        assert!(filter.enabled("rust_authoring::angular::transformers", &Level::DEBUG));
+        assert!(!filter.enabled("rust_authoring::angular::transformers", &Level::TRACE));
+        assert!(!filter.enabled("rust_authoring::angular::imports", &Level::ERROR));
+        // Only whole modules match.
+        assert!(!filter.enabled("rust_authoring::angularjs", &Level::DEBUG));
+    }
+
+    #[test]
+    fn invalid_directives_are_ignored() {
+        let filter = LogFilter::parse("verbose, rust_authoring::html=loud, TRACE,");
+        assert_eq!(filter, LogFilter::new(Level::TRACE));
+        assert_eq!(LogFilter::parse("off"), LogFilter::off());
+    }
+
+    #[test]
+    fn collects_the_selected_events() {
+        let filter = LogFilter::parse("info");
+        let (result, events) = collect(&filter, || {
+            tracing::info!(class = "App", count = 2, "compiled");
+            tracing::debug!("skipped");
+            42
+        });
+        assert_eq!(result, 42);
+        assert_eq!(
+            events,
+            [LogEvent {
+                level: Level::INFO,
+                target: "rust_authoring::logging::tests".to_string(),
+                message: "compiled".to_string(),
+                fields: vec![
+                    ("class".to_string(), "App".to_string()),
+                    ("count".to_string(), "2".to_string()),
+                ],
+            }]
+        );
+        assert_eq!(
+            events[0].to_string(),
+            " INFO rust_authoring::logging::tests: compiled class=App count=2"
+        );
+
+        let (_, events) = collect(&LogFilter::off(), || tracing::error!("dropped"));
+        assert!(events.is_empty());
+    }
+}
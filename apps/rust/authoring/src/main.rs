@@ -1,28 +1,39 @@
 use std::env;
 use std::path::PathBuf;
 
-mod treaty;
-use treaty::lexer::Lexer;
-use treaty::parser::Parser;
-use treaty::token::TokenKind;
+use oxc_diagnostics::Severity;
+use rust_authoring::compiler;
+use rust_authoring::logging::{self, LogFilter};
+use rust_authoring::treaty::lexer::Lexer;
+use rust_authoring::treaty::parser::Parser;
+use rust_authoring::treaty::token::TokenKind;
+use tracing::{debug, info};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Only the compiled component goes to stdout; `TREATY_LOG=debug` shows what led to it.
+    logging::init_stderr(
+        env::var("TREATY_LOG")
+            .map(|directives| LogFilter::parse(&directives))
+            .unwrap_or_default(),
+    );
+
     let default_path = "apps/rust/authoring/src/test.treaty";
     let file_path = env::args().nth(1).unwrap_or_else(|| default_path.to_string());
 
     let path = PathBuf::from(&file_path);
-    println!("Reading file: {}", path.display());
+    info!(path = %path.display(), "reading file");
 
     let source_text = std::fs::read_to_string(&path)
         .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
 
-    println!("Lexing source text...");
+    info!("lexing source text");
     let mut lexer = Lexer::new(&source_text);
     let mut tokens = Vec::new();
 
     while let Some(token) = lexer.next_token() {
         tokens.push(token);
     }
+    let mut diagnostics = lexer.take_errors();
 
     let mut javascript_chunks = Vec::new();
     let mut html_chunks = Vec::new();
@@ -37,27 +48,31 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    println!("JavaScript Chunks:");
-    for chunk in &javascript_chunks {
-        println!("{}", chunk);
-    }
+    debug!(chunks = ?javascript_chunks, "JavaScript chunks");
+    debug!(chunks = ?html_chunks, "HTML chunks");
+    debug!(chunks = ?css_chunks, "CSS chunks");
 
-    println!("\nHTML Chunks:");
-    for chunk in &html_chunks {
-        println!("{}", chunk);
-    }
-
-    println!("\nCSS Chunks:");
-    for chunk in &css_chunks {
-        println!("{}", chunk);
-    }
-
-    println!("\nParsing tokens...");
+    info!("parsing tokens");
     let mut parser = Parser::new(tokens);
     let ast = parser.parse();
+    diagnostics.append(&mut parser.take_errors());
+
+    debug!(?ast, "AST");
 
-    println!("AST:");
-    println!("{:#?}", ast);
+    info!("compiling to Ivy");
+    let component = compiler::compile(&source_text, &ast, &file_path);
+    diagnostics.extend(component.diagnostics);
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    for diagnostic in diagnostics {
+        eprintln!("{:?}", diagnostic.with_source_code(source_text.clone()));
+    }
+    println!("{}", component.code);
 
+    if errors > 0 {
+        return Err(format!("{} error(s) in {}", errors, path.display()).into());
+    }
     Ok(())
 }
\ No newline at end of file
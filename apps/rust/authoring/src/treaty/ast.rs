@@ -1,14 +1,172 @@
+use oxc_span::Span;
+
+use crate::html::Attribute;
+
 #[derive(Debug, Clone)]
 pub enum AstNode {
-    JavaScript(String),
-    Style(String),
-    Html(String),
-    TemplateExpression(String),
-    ControlFlow(String),
-    EOF,
+    JavaScript(JavaScript),
+    Style(Style),
+    Element(Element),
+    Text(Text),
+    Comment(Comment),
+    TemplateExpression(TemplateExpression),
+    If(IfBlock),
+    For(ForBlock),
+    Switch(SwitchBlock),
+    Defer(DeferBlock),
+}
+
+impl AstNode {
+    pub fn span(&self) -> Span {
+        match self {
+            AstNode::JavaScript(node) => node.span,
+            AstNode::Style(node) => node.span,
+            AstNode::Element(node) => node.span,
+            AstNode::Text(node) => node.span,
+            AstNode::Comment(node) => node.span,
+            AstNode::TemplateExpression(node) => node.span,
+            AstNode::If(node) => node.span,
+            AstNode::For(node) => node.span,
+            AstNode::Switch(node) => node.span,
+            AstNode::Defer(node) => node.span,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Ast {
     pub nodes: Vec<AstNode>,
-}
\ No newline at end of file
+}
+
+/// An expression taken from the template, with the span of its trimmed source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    pub source: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct JavaScript {
+    pub code: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Style {
+    pub content: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Text {
+    pub value: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Comment {
+    pub value: String,
+    pub span: Span,
+}
+
+/// `{{ expression }}`; `span` covers the braces.
+#[derive(Debug, Clone)]
+pub struct TemplateExpression {
+    pub expression: Expression,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Element {
+    pub tag_name: String,
+    pub attributes: Vec<Attribute>,
+    pub children: Vec<AstNode>,
+    pub span: Span,
+    pub start_tag_span: Span,
+    /// `None` for void and self-closing elements, or when the closing tag is missing.
+    pub end_tag_span: Option<Span>,
+}
+
+/// `@if`, followed by any number of `@else if` branches and an optional `@else`.
+#[derive(Debug, Clone)]
+pub struct IfBlock {
+    pub branches: Vec<IfBranch>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct IfBranch {
+    /// `None` for the trailing `@else` branch.
+    pub condition: Option<Expression>,
+    /// Name bound by `@if (expr; as name)`.
+    pub alias: Option<Expression>,
+    pub children: Vec<AstNode>,
+    pub span: Span,
+}
+
+/// `@for (item of items; track expr; let i = $index) { ... } @empty { ... }`
+#[derive(Debug, Clone)]
+pub struct ForBlock {
+    pub item: Expression,
+    pub expression: Expression,
+    pub track: Option<Expression>,
+    /// `let name = $variable` aliases, as `(name, $variable)` pairs.
+    pub context_aliases: Vec<(Expression, Expression)>,
+    pub children: Vec<AstNode>,
+    pub empty: Option<EmptyBlock>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmptyBlock {
+    pub children: Vec<AstNode>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchBlock {
+    pub expression: Expression,
+    pub cases: Vec<SwitchCase>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchCase {
+    /// `None` for `@default`.
+    pub expression: Option<Expression>,
+    pub children: Vec<AstNode>,
+    pub span: Span,
+}
+
+/// `@defer` together with its `@placeholder`, `@loading` and `@error` sub-blocks.
+#[derive(Debug, Clone)]
+pub struct DeferBlock {
+    /// Triggers in source order, e.g. `on viewport; prefetch on idle`.
+    pub triggers: Vec<DeferTrigger>,
+    pub children: Vec<AstNode>,
+    pub placeholder: Option<DeferSubBlock>,
+    pub loading: Option<DeferSubBlock>,
+    pub error: Option<DeferSubBlock>,
+    pub span: Span,
+}
+
+/// A single `@defer` trigger: `when ready`, `on timer(500ms)`, `prefetch on hover(button)`, ...
+#[derive(Debug, Clone)]
+pub struct DeferTrigger {
+    /// `when`, or the event following `on`, e.g. `viewport`.
+    pub name: Expression,
+    /// The condition of `when`, the delay of `timer` or the reference of an element trigger.
+    pub parameter: Option<Expression>,
+    pub prefetch: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeferSubBlock {
+    /// `minimum 500ms`, for `@placeholder` and `@loading`.
+    pub minimum: Option<Expression>,
+    /// `after 100ms`, for `@loading`.
+    pub after: Option<Expression>,
+    pub children: Vec<AstNode>,
+    pub span: Span,
+}
@@ -1,14 +1,57 @@
+use crate::html::is_void_element;
 use crate::treaty::token::{Token, TokenKind};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_span::Span;
 use std::str::Chars;
 
 use super::token::{ControlFlowKind, DeferKind};
 
+/// Block keywords in the order they are matched; `@else if` must be tried before `@else`.
+const BLOCK_KEYWORDS: [(&str, TokenKind); 12] = [
+    ("@if", TokenKind::ControlFlow(ControlFlowKind::If)),
+    ("@else if", TokenKind::ControlFlow(ControlFlowKind::ElseIf)),
+    ("@else", TokenKind::ControlFlow(ControlFlowKind::Else)),
+    ("@for", TokenKind::ControlFlow(ControlFlowKind::For)),
+    ("@empty", TokenKind::ControlFlow(ControlFlowKind::Empty)),
+    ("@switch", TokenKind::ControlFlow(ControlFlowKind::Switch)),
+    ("@case", TokenKind::ControlFlow(ControlFlowKind::Case)),
+    ("@default", TokenKind::ControlFlow(ControlFlowKind::Default)),
+    ("@defer", TokenKind::Defer(DeferKind::Defer)),
+    ("@placeholder", TokenKind::Defer(DeferKind::Placeholder)),
+    ("@loading", TokenKind::Defer(DeferKind::Loading)),
+    ("@error", TokenKind::Defer(DeferKind::Error)),
+];
+
+/// Elements whose closing tag may be left out, as HTML closes them when their parent closes.
+const IMPLICITLY_CLOSED: [&str; 14] = [
+    "p", "li", "dt", "dd", "option", "optgroup", "tr", "td", "th", "thead", "tbody", "tfoot", "rt",
+    "rp",
+];
+
+/// Keywords followed by an expression, after which a `/` starts a regular expression.
+const EXPRESSION_KEYWORDS: [&str; 14] = [
+    "return",
+    "typeof",
+    "instanceof",
+    "in",
+    "of",
+    "new",
+    "delete",
+    "void",
+    "throw",
+    "case",
+    "do",
+    "else",
+    "yield",
+    "await",
+];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum LexerState {
     Default,
     JavaScript,
-    HTML,
-    CSS,
+    Html,
+    Css,
     TemplateExpression,
     ControlFlow,
 }
@@ -20,6 +63,16 @@ pub struct Lexer<'a> {
     current_char: Option<char>,
     state: LexerState,
     state_stack: Vec<LexerState>, // Stack to keep track of parent states
+    /// Elements opened but not yet closed, with the position of their `<`.
+    tag_stack: Vec<(String, usize)>,
+    /// Blocks opened but not yet closed, with the span of their keyword.
+    block_stack: Vec<Span>,
+    /// The keyword of the block whose parameters are being lexed.
+    block_keyword: Option<Span>,
+    /// Lexing a component template, which has no JavaScript.
+    markup_only: bool,
+    /// Problems found so far, see [`Lexer::take_errors`].
+    errors: Vec<OxcDiagnostic>,
 }
 
 impl<'a> Lexer<'a> {
@@ -33,19 +86,45 @@ impl<'a> Lexer<'a> {
             current_char,
             state: LexerState::Default,
             state_stack: Vec::new(), // Initialize the state stack
+            tag_stack: Vec::new(),
+            block_stack: Vec::new(),
+            block_keyword: None,
+            markup_only: false,
+            errors: Vec::new(),
+        }
+    }
+
+    /// A lexer for a component template, e.g. the `template` of `@Component`, where plain
+    /// characters at the top level are text rather than JavaScript.
+    pub fn template(input: &'a str) -> Self {
+        Lexer {
+            markup_only: true,
+            ..Lexer::new(input)
         }
     }
 
+    /// The problems found so far, such as an unterminated string or an unclosed element.
+    /// Lexing carries on past each of them, so one mistake does not hide those after it.
+    pub fn take_errors(&mut self) -> Vec<OxcDiagnostic> {
+        std::mem::take(&mut self.errors)
+    }
+
     pub fn next_token(&mut self) -> Option<Token> {
+        let whitespace_start = self.pos;
         self.consume_whitespace();
+        if self.current_char.is_none() {
+            self.report_unclosed_elements(0);
+            self.report_unclosed_blocks();
+            return None;
+        }
 
         match self.state {
-            LexerState::Default => self.lex_default_state(),
+            LexerState::Default => self.lex_default_state(whitespace_start),
             LexerState::JavaScript => self.parse_javascript(),
-            LexerState::HTML => self.parse_html(),
-            LexerState::CSS => self.parse_style(),
+            LexerState::Html => self.parse_html(),
+            LexerState::Css => self.parse_style(),
             LexerState::TemplateExpression => self.parse_template_expression(),
-            LexerState::ControlFlow => self.parse_control_flow(),
+            LexerState::ControlFlow => self.parse_block_header(),
         }
     }
 
@@ -77,26 +156,41 @@ impl<'a> Lexer<'a> {
         self.consume_while(|ch| ch.is_whitespace());
     }
 
+    /// Whether the lexer is currently inside an element or a control flow block, where
+    /// plain characters are template text rather than JavaScript.
+    fn in_template(&self) -> bool {
+        self.markup_only || !self.tag_stack.is_empty() || !self.block_stack.is_empty()
+    }
+
     /// Retrieves the next token.
-    fn lex_default_state(&mut self) -> Option<Token> {
+    fn lex_default_state(&mut self, whitespace_start: usize) -> Option<Token> {
         let current_char = self.current_char?;
 
         match current_char {
             '<' if self.starts_with("<style>") => {
                 self.advance_by("<style>".len());
-                self.push_state(LexerState::CSS);
+                self.push_state(LexerState::Css);
                 self.parse_style()
             }
-            '<' => {
-                self.push_state(LexerState::HTML);
+            '<' if self.is_tag_start() => {
+                self.push_state(LexerState::Html);
                 self.parse_html()
             }
             '{' if self.starts_with("{{") => {
-                self.advance_by(2); // Skip '{{'
                 self.push_state(LexerState::TemplateExpression);
                 self.parse_template_expression()
             }
-            '@' => self.parse_control_flow(),
+            '}' if !self.block_stack.is_empty() => {
+                let start_pos = self.pos;
+                self.advance();
+                self.block_stack.pop();
+                Some(Token::new(TokenKind::BlockEnd, start_pos, self.pos))
+            }
+            '@' if self.block_keyword().is_some() => self.parse_control_flow(),
+            '@' if self.unknown_block().is_some_and(|(_, has_body)| has_body) => {
+                self.parse_unknown_block()
+            }
+            _ if self.in_template() => self.parse_text(whitespace_start),
             _ => {
                 self.push_state(LexerState::JavaScript);
                 self.parse_javascript()
@@ -114,12 +208,14 @@ impl<'a> Lexer<'a> {
                     self.consume_string(ch);
                 }
 
-                // Handle comments
+                // Handle comments and regular expressions
                 '/' => {
                     if self.starts_with("//") {
                         self.consume_line_comment();
                     } else if self.starts_with("/*") {
                         self.consume_block_comment();
+                    } else if self.regex_allowed() {
+                        self.consume_regex();
                     } else {
                         self.advance();
                     }
@@ -130,11 +226,9 @@ impl<'a> Lexer<'a> {
                 }
                 '<' if (self.starts_with("<style>") || self.starts_with("</")) => break,
                 '{' if self.starts_with("{{") => break,
-                '@' => {
-                    // Handle the '@' character and transition to control flow state
-                    self.push_state(LexerState::ControlFlow);
-                    break;
-                }
+                // Only blocks end the chunk; decorators such as `@Component` are JavaScript
+                '@' if self.block_keyword().is_some() => break,
+                '@' if self.unknown_block().is_some_and(|(_, has_body)| has_body) => break,
                 // Handle other cases
                 _ => self.advance(),
             }
@@ -146,7 +240,8 @@ impl<'a> Lexer<'a> {
         Some(Token::new(TokenKind::JavaScript(value), start_pos, end_pos))
     }
 
-    /// Parses a style block.
+    /// Parses a style block. Without its `</style>`, the block ends at the next tag, as CSS has
+    /// no `<` of its own.
     fn parse_style(&mut self) -> Option<Token> {
         let start_pos = self.pos;
 
@@ -156,8 +251,8 @@ impl<'a> Lexer<'a> {
             }
             match ch {
                 '/' if self.starts_with("/*") => self.consume_block_comment(),
-                '{' => self.advance(), // Advance over '{', you might want to handle nested blocks
-                '}' => self.advance(), // Advance over '}', matching any opened '{'
+                '\'' | '"' => self.consume_string(ch),
+                '<' if self.is_tag_start() => break,
                 _ => self.advance(),
             }
         }
@@ -167,53 +262,47 @@ impl<'a> Lexer<'a> {
 
         if self.starts_with("</style>") {
             self.advance_by("</style>".len());
+        } else {
+            let open_tag = Span::new((start_pos - "<style>".len()) as u32, start_pos as u32);
+            self.errors.push(
+                OxcDiagnostic::error("Unterminated `<style>` element")
+                    .with_help("Close it with `</style>`")
+                    .with_label(open_tag),
+            );
         }
 
         self.pop_state(); // Return to the previous state
         Some(Token::new(TokenKind::Style(value), start_pos, end_pos))
     }
 
-    /// Parses an HTML segment.
+    /// Parses a single HTML tag or comment, tracking which elements are still open.
     fn parse_html(&mut self) -> Option<Token> {
         let start_pos = self.pos;
-        let mut tag_stack = Vec::new();
 
-        while let Some(ch) = self.current_char {
-            self.consume_whitespace();
-            if ch == '<' {
-                if self.starts_with("<!--") {
-                    self.consume_html_comment();
-                    continue;
-                } else if self.starts_with("</") {
-                    self.advance_by(2); // Skip '</'
-                    let tag_name = self.consume_tag_name();
-                    if let Some(expected_tag) = tag_stack.pop() {
-                        if tag_name != expected_tag {
-                            // Handle mismatched tag (optional)
-                        }
-                    } else {
-                        break;
-                    }
-                    self.consume_until('>'); // Skip until '>'
-                    self.advance(); // Skip '>'
-                    if tag_stack.is_empty() {
-                        break;
-                    }
-                } else if self.starts_with("<") {
-                    self.advance(); // Skip '<'
-                    let tag_name = self.consume_tag_name();
-                    tag_stack.push(tag_name);
-                    self.consume_attributes(); // Handle attributes (optional)
-                } else {
-                    self.advance();
-                }
+        if self.starts_with("<!--") {
+            self.consume_html_comment();
+        } else if self.starts_with("</") {
+            self.advance_by(2); // Skip '</'
+            let tag_name = self.consume_tag_name();
+            self.consume_until('>'); // Skip until '>'
+            self.advance(); // Skip '>'
+            let open = self
+                .tag_stack
+                .iter()
+                .rposition(|(open, _)| *open == tag_name);
+            // Closing tags without an open element are reported by the parser, which knows the
+            // blocks they are in.
+            if let Some(index) = open {
+                // Elements opened since are closed along with it
+                self.report_unclosed_elements(index + 1);
+                self.tag_stack.truncate(index);
             }
-            // lets deal with html as we only have top level support for break down on lexer
-            // else if ch == '{' && self.starts_with("{{") {
-            //     break;
-            // }
-             else {
-                self.advance();
+        } else {
+            self.advance(); // Skip '<'
+            let tag_name = self.consume_tag_name();
+            let self_closing = self.consume_attributes();
+            if !self_closing && !is_void_element(&tag_name) {
+                self.tag_stack.push((tag_name, start_pos));
             }
         }
 
@@ -223,36 +312,76 @@ impl<'a> Lexer<'a> {
         Some(Token::new(TokenKind::HTML(value), start_pos, end_pos))
     }
 
-    /// Parses a template expression.
+    /// Parses template text up to the next tag, interpolation or block boundary.
+    fn parse_text(&mut self, start_pos: usize) -> Option<Token> {
+        while let Some(ch) = self.current_char {
+            match ch {
+                '<' if self.is_tag_start() => break,
+                '{' if self.starts_with("{{") => break,
+                '}' if !self.block_stack.is_empty() => break,
+                '@' if self.block_keyword().is_some() => break,
+                '@' => match self.unknown_block() {
+                    Some((_, true)) => break,
+                    // Left as text, but most likely a misspelt block
+                    Some((len, false)) => {
+                        self.report_unknown_block(len);
+                        self.advance_by(len);
+                    }
+                    None => self.advance(),
+                },
+                _ => self.advance(),
+            }
+        }
+
+        let end_pos = self.pos;
+        let value = self.input[start_pos..end_pos].to_string();
+        Some(Token::new(TokenKind::Text(value), start_pos, end_pos))
+    }
+
+    /// Parses a template expression. Without its `}}`, the expression ends before the first tag
+    /// or interpolation that follows it.
     fn parse_template_expression(&mut self) -> Option<Token> {
         let start_pos = self.pos;
+        self.advance_by(2); // Skip '{{'
+        let expression_start = self.pos;
         let mut brace_count = 0;
+        let mut recovery = None;
 
         while let Some(ch) = self.current_char {
-            if ch == '{' {
-                brace_count += 1;
-            } else if ch == '}' {
-                brace_count -= 1;
-                if brace_count == -2 {
-                    // We've found the closing '}}'
-                    break;
+            match ch {
+                '}' if brace_count == 0 && self.starts_with("}}") => break,
+                '<' if recovery.is_none() && self.is_tag_start() => recovery = Some(self.pos),
+                '{' if recovery.is_none() && self.starts_with("{{") => recovery = Some(self.pos),
+                '{' => brace_count += 1,
+                '}' => brace_count -= 1,
+                '\'' | '"' | '`' => {
+                    self.consume_string(ch);
+                    continue;
                 }
-            } else if ch == '\'' || ch == '"' || ch == '`' {
-                self.consume_string(ch);
-                continue;
+                _ => {}
             }
             self.advance();
         }
 
+        if !self.starts_with("}}") {
+            self.errors.push(
+                OxcDiagnostic::error("Unterminated interpolation")
+                    .with_help("Close it with `}}`")
+                    .with_label(Span::new(start_pos as u32, (start_pos + 2) as u32)),
+            );
+            if let Some(recovery) = recovery {
+                self.reset_to(recovery);
+            }
+        }
+        let value = self.input[expression_start..self.pos].to_string();
         if self.starts_with("}}") {
             self.advance_by(2); // Skip '}}'
         }
 
         let end_pos = self.pos;
-        let value = self.input[start_pos..end_pos].to_string();
         self.pop_state(); // Return to the previous state
         Some(Token::new(
-            TokenKind::TemplateExpression(value.trim().to_string()),
+            TokenKind::TemplateExpression(value),
             start_pos,
             end_pos,
         ))
@@ -262,50 +391,181 @@ impl<'a> Lexer<'a> {
     fn parse_control_flow(&mut self) -> Option<Token> {
         let start_pos = self.pos;
 
-        if self.starts_with("@if") {
-            self.advance_by("@if".len());
-            return Some(Token::new(TokenKind::ControlFlow(ControlFlowKind::If), start_pos, self.pos));
-        } else if self.starts_with("@else if") {
-            self.advance_by("@else if".len());
-            return Some(Token::new(TokenKind::ControlFlow(ControlFlowKind::ElseIf), start_pos, self.pos));
-        } else if self.starts_with("@else") {
-            self.advance_by("@else".len());
-            return Some(Token::new(TokenKind::ControlFlow(ControlFlowKind::Else), start_pos, self.pos));
-        } else if self.starts_with("@for") {
-            self.advance_by("@for".len());
-            return Some(Token::new(TokenKind::ControlFlow(ControlFlowKind::For), start_pos, self.pos));
-        } else if self.starts_with("@empty") {
-            self.advance_by("@empty".len());
-            return Some(Token::new(TokenKind::ControlFlow(ControlFlowKind::Empty), start_pos, self.pos));
-        } else if self.starts_with("@switch") {
-            self.advance_by("@switch".len());
-            return Some(Token::new(TokenKind::ControlFlow(ControlFlowKind::Switch), start_pos, self.pos));
-        } else if self.starts_with("@case") {
-            self.advance_by("@case".len());
-            return Some(Token::new(TokenKind::ControlFlow(ControlFlowKind::Case), start_pos, self.pos));
-        } else if self.starts_with("@default") {
-            self.advance_by("@default".len());
-            return Some(Token::new(TokenKind::ControlFlow(ControlFlowKind::Default), start_pos, self.pos));
-        } else if self.starts_with("@defer") {
-            self.advance_by("@defer".len());
-            return Some(Token::new(TokenKind::Defer(DeferKind::Defer), start_pos, self.pos));
-        } else if self.starts_with("@placeholder") {
-            self.advance_by("@placeholder".len());
-            return Some(Token::new(TokenKind::Defer(DeferKind::Placeholder), start_pos, self.pos));
-        } else if self.starts_with("@loading") {
-            self.advance_by("@loading".len());
-            return Some(Token::new(TokenKind::Defer(DeferKind::Loading), start_pos, self.pos));
-        } else if self.starts_with("@error") {
-            self.advance_by("@error".len());
-            return Some(Token::new(TokenKind::Defer(DeferKind::Error), start_pos, self.pos));
-        } else {
-            // If not a recognized control flow, assume it's JavaScript
-            self.state = LexerState::JavaScript;
-            self.advance(); // Ensure we advance the position to avoid infinite loop
-            self.parse_javascript()
+        if let Some((kind, len)) = self.block_keyword() {
+            self.advance_by(len);
+            self.block_keyword = Some(Span::new(start_pos as u32, self.pos as u32));
+            // The keyword may be followed by parameters and a body
+            self.state = LexerState::ControlFlow;
+            return Some(Token::new(kind, start_pos, self.pos));
+        }
+
+        // If not a recognized control flow, assume it's JavaScript
+        self.push_state(LexerState::JavaScript);
+        self.parse_javascript()
+    }
+
+    /// Reports an unknown block, then lexes its parameters and body as those of a known one, so
+    /// that the template inside it is still checked.
+    fn parse_unknown_block(&mut self) -> Option<Token> {
+        let (len, _) = self.unknown_block()?;
+        self.report_unknown_block(len);
+        self.block_keyword = Some(Span::new(self.pos as u32, (self.pos + len) as u32));
+        self.advance_by(len);
+        self.consume_whitespace();
+        self.state = LexerState::ControlFlow;
+        self.parse_block_header()
+    }
+
+    /// Parses what follows a block keyword: its `(parameters)` and the opening `{`.
+    fn parse_block_header(&mut self) -> Option<Token> {
+        let start_pos = self.pos;
+
+        match self.current_char? {
+            '(' => {
+                let closed = self.consume_parenthesized();
+                let end_pos = if closed { self.pos - 1 } else { self.pos };
+                let value = self.input[start_pos + 1..end_pos].to_string();
+                Some(Token::new(
+                    TokenKind::BlockParameters(value),
+                    start_pos,
+                    self.pos,
+                ))
+            }
+            '{' => {
+                self.advance();
+                let keyword = self.block_keyword.take();
+                self.block_stack
+                    .push(keyword.unwrap_or(Span::new(start_pos as u32, self.pos as u32)));
+                self.state = LexerState::Default;
+                Some(Token::new(TokenKind::BlockStart, start_pos, self.pos))
+            }
+            _ => {
+                // A block without a body, carry on lexing whatever comes next
+                self.block_keyword = None;
+                self.state = LexerState::Default;
+                self.lex_default_state(start_pos)
+            }
+        }
+    }
+
+    /// Matches a block keyword at the current position, returning its token kind and length.
+    fn block_keyword(&self) -> Option<(TokenKind, usize)> {
+        let rest = &self.input[self.pos..];
+        if !rest.starts_with('@') {
+            return None;
+        }
+
+        BLOCK_KEYWORDS.iter().find_map(|(keyword, kind)| {
+            let len = if let Some(condition) = keyword.strip_prefix("@else ") {
+                // `@else if` allows any whitespace between the two words
+                let after_else = rest.strip_prefix("@else")?;
+                let trimmed = after_else.trim_start();
+                if trimmed.len() == after_else.len() || !trimmed.starts_with(condition) {
+                    return None;
+                }
+                rest.len() - trimmed.len() + condition.len()
+            } else if rest.starts_with(keyword) {
+                keyword.len()
+            } else {
+                return None;
+            };
+
+            let is_word_end =
+                !rest[len..].starts_with(|ch: char| ch.is_alphanumeric() || ch == '_' || ch == '$');
+            is_word_end.then(|| (kind.clone(), len))
+        })
+    }
+
+    /// Matches `@name` at the current position when it is not a block keyword, returning its
+    /// length and whether it is followed by a block body, e.g. `@iff (a) {`. An `@` inside a
+    /// word, as in an email address, is not a block.
+    fn unknown_block(&self) -> Option<(usize, bool)> {
+        let rest = &self.input[self.pos..];
+        let name = rest.strip_prefix('@')?;
+        let preceded_by_word = self.input[..self.pos]
+            .chars()
+            .next_back()
+            .is_some_and(|ch| ch.is_alphanumeric() || ch == '_' || ch == '.');
+        if preceded_by_word
+            || !name.starts_with(|ch: char| ch.is_ascii_lowercase())
+            || self.block_keyword().is_some()
+        {
+            return None;
+        }
+        let len = 1 + name
+            .find(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '$'))
+            .unwrap_or(name.len());
+
+        // The parameters are skipped roughly, as only the brace after them matters.
+        let mut after = rest[len..].trim_start();
+        if after.starts_with('(') {
+            let mut depth = 0;
+            let end = after.find(|ch| {
+                match ch {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            });
+            after = end.map_or("", |end| after[end + 1..].trim_start());
+        }
+        Some((len, after.starts_with('{')))
+    }
+
+    fn report_unknown_block(&mut self, len: usize) {
+        let name = &self.input[self.pos..self.pos + len];
+        let known: Vec<_> = BLOCK_KEYWORDS
+            .iter()
+            .map(|(keyword, _)| format!("`{}`", keyword))
+            .collect();
+        self.errors.push(
+            OxcDiagnostic::error(format!("Unknown block `{}`", name))
+                .with_help(format!(
+                    "Blocks are {}; write `&#64;` for an `@` character",
+                    known.join(", ")
+                ))
+                .with_label(Span::new(self.pos as u32, (self.pos + len) as u32)),
+        );
+    }
+
+    /// Reports the elements opened from `index` of the tag stack on as unclosed, except those
+    /// HTML closes implicitly, and forgets them.
+    fn report_unclosed_elements(&mut self, index: usize) {
+        if index >= self.tag_stack.len() {
+            return;
+        }
+        for (tag_name, start) in self.tag_stack.split_off(index) {
+            if IMPLICITLY_CLOSED.contains(&tag_name.as_str()) {
+                continue;
+            }
+            let open_tag = Span::new(start as u32, (start + 1 + tag_name.len()) as u32);
+            self.errors.push(
+                OxcDiagnostic::error(format!("Unclosed element `<{}>`", tag_name))
+                    .with_help(format!("Close it with `</{}>`", tag_name))
+                    .with_label(open_tag),
+            );
+        }
+    }
+
+    /// Reports the blocks still open at the end of the input, and forgets them.
+    fn report_unclosed_blocks(&mut self) {
+        for keyword in std::mem::take(&mut self.block_stack) {
+            let name = keyword.source_text(self.input);
+            self.errors.push(
+                OxcDiagnostic::error(format!("Unclosed block `{}`", name))
+                    .with_help("Close it with `}`")
+                    .with_label(keyword),
+            );
         }
     }
 
+    /// Checks if the current `<` opens a tag, closing tag or comment. Tag names may start with
+    /// any letter, as in `<é>`.
+    fn is_tag_start(&self) -> bool {
+        matches!(self.peek(), Some(ch) if ch.is_alphabetic() || ch == '/' || ch == '!')
+    }
+
     /// Checks if the upcoming characters match the given string.
     fn starts_with(&self, s: &str) -> bool {
         self.input[self.pos..].starts_with(s)
@@ -313,13 +573,24 @@ impl<'a> Lexer<'a> {
 
     /// Advances the lexer by a given number of bytes.
     fn advance_by(&mut self, n: usize) {
-        for _ in 0..n {
+        let target = self.pos + n;
+        while self.pos < target && self.current_char.is_some() {
             self.advance();
         }
     }
 
-    /// Consumes a string literal, handling escaped characters.
+    /// Moves the lexer back or forth to `pos`, a character boundary.
+    fn reset_to(&mut self, pos: usize) {
+        self.pos = pos;
+        self.chars = self.input[pos..].chars();
+        self.current_char = self.chars.next();
+    }
+
+    /// Consumes a string literal, handling escaped characters. As in JavaScript, quotes other
+    /// than backticks end at a line break, except in attribute values.
     fn consume_string(&mut self, delimiter: char) {
+        let start_pos = self.pos;
+        let multiline = delimiter == '`' || self.state == LexerState::Html;
         self.advance(); // Skip the opening quote
         while let Some(ch) = self.current_char {
             match ch {
@@ -329,11 +600,88 @@ impl<'a> Lexer<'a> {
                 }
                 ch if ch == delimiter => {
                     self.advance(); // Skip the closing quote
-                    break;
+                    return;
                 }
+                '\n' | '\r' if !multiline => break,
                 _ => self.advance(),
             }
         }
+        self.errors.push(
+            OxcDiagnostic::error("Unterminated string literal")
+                .with_label(Span::new(start_pos as u32, self.pos as u32)),
+        );
+    }
+
+    /// Consumes a balanced `( ... )` group, skipping over string literals.
+    /// Returns whether the closing parenthesis was found.
+    fn consume_parenthesized(&mut self) -> bool {
+        let mut depth = 0;
+        while let Some(ch) = self.current_char {
+            match ch {
+                '\'' | '"' | '`' => {
+                    self.consume_string(ch);
+                    continue;
+                }
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.advance(); // Skip the closing ')'
+                        return true;
+                    }
+                }
+                _ => {}
+            }
+            self.advance();
+        }
+        false
+    }
+
+    /// Whether a `/` at the current position starts a regular expression rather than a
+    /// division, going by what comes before it, e.g. `= /a/` or `(/a/` but not `a / b`.
+    fn regex_allowed(&self) -> bool {
+        let before = self.input[..self.pos].trim_end();
+        match before.chars().next_back() {
+            None => true,
+            Some(ch) if ch.is_alphanumeric() || ch == '_' || ch == '$' => {
+                let word_start = before
+                    .rfind(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '$'))
+                    .map_or(0, |index| index + 1);
+                EXPRESSION_KEYWORDS.contains(&&before[word_start..])
+            }
+            Some(ch) => !matches!(ch, ')' | ']' | '}' | '"' | '\'' | '`' | '.'),
+        }
+    }
+
+    /// Consumes a regular expression literal and its flags, so that quotes and slashes in it
+    /// are not taken for strings or comments. Like a string, it ends at a line break.
+    fn consume_regex(&mut self) {
+        let start_pos = self.pos;
+        let mut in_class = false;
+        self.advance(); // Skip the opening '/'
+        while let Some(ch) = self.current_char {
+            match ch {
+                '\\' => {
+                    self.advance(); // Skip the backslash
+                    self.advance(); // Skip the escaped character
+                    continue;
+                }
+                '[' => in_class = true,
+                ']' => in_class = false,
+                '/' if !in_class => {
+                    self.advance(); // Skip the closing '/'
+                    self.consume_while(|ch| ch.is_alphanumeric());
+                    return;
+                }
+                '\n' | '\r' => break,
+                _ => {}
+            }
+            self.advance();
+        }
+        self.errors.push(
+            OxcDiagnostic::error("Unterminated regular expression")
+                .with_label(Span::new(start_pos as u32, self.pos as u32)),
+        );
     }
 
     /// Consumes a line comment.
@@ -348,39 +696,47 @@ impl<'a> Lexer<'a> {
 
     /// Consumes a block comment.
     fn consume_block_comment(&mut self) {
+        let start_pos = self.pos;
         self.advance_by(2); // Skip '/*'
-        while let Some(ch) = self.current_char {
+        while self.current_char.is_some() {
             if self.starts_with("*/") {
                 self.advance_by(2); // Skip '*/'
-                break;
+                return;
             }
             self.advance();
         }
+        self.report_unterminated_comment(start_pos, "*/");
     }
 
     /// Consumes an HTML comment.
     fn consume_html_comment(&mut self) {
+        let start_pos = self.pos;
         self.advance_by("<!--".len());
-        while let Some(ch) = self.current_char {
+        while self.current_char.is_some() {
             if self.starts_with("-->") {
                 self.advance_by("-->".len());
-                break;
+                return;
             }
             self.advance();
         }
+        self.report_unterminated_comment(start_pos, "-->");
     }
 
-    fn consume_tag_name(&mut self) -> String {
-        let mut tag_name = String::new();
-        while let Some(ch) = self.current_char {
-            if ch.is_alphanumeric() {
-                tag_name.push(ch);
-                self.advance();
-            } else {
-                break;
-            }
+    /// Reports the comment at `start_pos` and ends it at its first line break instead, so what
+    /// follows is still lexed.
+    fn report_unterminated_comment(&mut self, start_pos: usize, end: &str) {
+        if let Some(line_end) = self.input[start_pos..].find(['\n', '\r']) {
+            self.reset_to(start_pos + line_end);
         }
-        tag_name
+        self.errors.push(
+            OxcDiagnostic::error("Unterminated comment")
+                .with_help(format!("Close it with `{}`", end))
+                .with_label(Span::new(start_pos as u32, self.pos as u32)),
+        );
+    }
+
+    fn consume_tag_name(&mut self) -> String {
+        self.consume_while(|ch| ch.is_alphanumeric() || matches!(ch, '-' | '_' | ':' | '.'))
     }
 
     fn consume_attributes(&mut self) -> bool {
@@ -427,4 +783,101 @@ impl<'a> Lexer<'a> {
             self.state = state;
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The messages of the errors found lexing `input`, in order.
+    fn errors(input: &str) -> Vec<String> {
+        let mut lexer = Lexer::new(input);
+        while lexer.next_token().is_some() {}
+        lexer
+            .take_errors()
+            .iter()
+            .map(|error| error.message.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn well_formed_file() {
+        let input = "const a = 1;\n<div class=\"card\">@if (a) {<p>{{ a }}</p>}</div>\n<style>p { color: red; }</style>\n";
+        assert!(errors(input).is_empty());
+    }
+
+    #[test]
+    fn unterminated_string_ends_at_line_break() {
+        assert_eq!(
+            errors("const a = 'one;\nconst b = \"two\";\n<p>{{ b }}</p>\n"),
+            ["Unterminated string literal"]
+        );
+    }
+
+    #[test]
+    fn quote_in_regular_expression() {
+        assert!(errors("const re = /\"/;\nconst half = a / 2 / b;\n<p>{{ re }}</p>\n").is_empty());
+        assert!(errors("function f(s) { return /['\"]/g.test(s); }\n").is_empty());
+        assert_eq!(
+            errors("const re = /abc\n<p></p>\n"),
+            ["Unterminated regular expression"]
+        );
+    }
+
+    #[test]
+    fn unterminated_comments() {
+        assert_eq!(
+            errors("/* never closed\nconst a = 1;\n"),
+            ["Unterminated comment"]
+        );
+        assert_eq!(
+            errors("<div><!-- never closed\n<p>a</p></div>\n"),
+            ["Unterminated comment"]
+        );
+    }
+
+    #[test]
+    fn unterminated_interpolation_ends_at_next_tag() {
+        assert_eq!(
+            errors("<p>{{ name </p>\n<p>{{ count }}</p>\n"),
+            ["Unterminated interpolation"]
+        );
+    }
+
+    #[test]
+    fn unterminated_style_ends_at_next_tag() {
+        assert_eq!(
+            errors("<style>p { color: red; }\n<footer></footer>\n"),
+            ["Unterminated `<style>` element"]
+        );
+    }
+
+    #[test]
+    fn mismatched_tags() {
+        assert_eq!(errors("<main><b>bold</main>\n"), ["Unclosed element `<b>`"]);
+        // HTML closes these itself.
+        assert!(errors("<ul><li>one<li>two</ul>\n").is_empty());
+    }
+
+    #[test]
+    fn non_ascii_tag_names() {
+        assert!(errors("<é>a</é>\n<x-ü>b</x-ü>\n").is_empty());
+    }
+
+    #[test]
+    fn unknown_blocks() {
+        assert_eq!(
+            errors("<div>@iff (a) {<p>a</p>} me@example.com</div>\n"),
+            ["Unknown block `@iff`"]
+        );
+    }
+
+    #[test]
+    fn unclosed_blocks() {
+        assert_eq!(errors("@if (a) {<p>x</p>\n"), ["Unclosed block `@if`"]);
+        assert_eq!(
+            errors("@for (item of items; track item) {@if (item) {<p>x</p>}\n"),
+            ["Unclosed block `@for`"]
+        );
+    }
+}
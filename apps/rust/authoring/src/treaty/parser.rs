@@ -1,16 +1,41 @@
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_span::Span;
+use tracing::{debug, trace};
+
+use crate::html::{is_void_element, HtmlTokenizer, Token as HtmlToken};
+use crate::treaty::ast::{
+    Ast, AstNode, Comment, DeferBlock, DeferSubBlock, DeferTrigger, Element, EmptyBlock,
+    Expression, ForBlock, IfBlock, IfBranch, JavaScript, Style, SwitchBlock, SwitchCase,
+    TemplateExpression, Text,
+};
 use crate::treaty::token::{Token, TokenKind};
-use crate::treaty::ast::{AstNode, Ast};
 
 use super::token::{ControlFlowKind, DeferKind};
 
 pub struct Parser {
     tokens: Vec<Token>,
     pos: usize,
+    /// The elements being parsed, innermost last, with `None` where a block opens: a closing tag
+    /// inside a block cannot close an element outside of it.
+    open_elements: Vec<Option<String>>,
+    /// Problems found so far, see [`Parser::take_errors`].
+    errors: Vec<OxcDiagnostic>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, pos: 0 }
+        Parser {
+            tokens,
+            pos: 0,
+            open_elements: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// The problems found so far, such as a closing tag without an open element. Those the lexer
+    /// reports, e.g. unclosed elements and blocks, are not reported again.
+    pub fn take_errors(&mut self) -> Vec<OxcDiagnostic> {
+        std::mem::take(&mut self.errors)
     }
 
     /// Parses the tokens and returns an AST.
@@ -18,47 +43,418 @@ impl Parser {
         let mut nodes = Vec::new();
 
         while !self.is_at_end() {
-            let node = self.parse_node();
-            nodes.push(node);
+            if let Some(node) = self.parse_node() {
+                nodes.push(node);
+            }
         }
 
+        debug!(nodes = nodes.len(), "parsed treaty file");
         Ast { nodes }
     }
 
-    fn parse_node(&mut self) -> AstNode {
+    fn parse_node(&mut self) -> Option<AstNode> {
         let token = self.advance();
         let token_kind = token.kind.clone(); // Clone the token kind to avoid multiple mutable borrows
+        let (start, span) = (token.start, token.span());
 
         match token_kind {
-            TokenKind::JavaScript(code) => AstNode::JavaScript(code),
-            TokenKind::Style(style) => AstNode::Style(style),
-            TokenKind::HTML(content) => AstNode::Html(content),
-            TokenKind::TemplateExpression(expr) => AstNode::TemplateExpression(expr),
-            TokenKind::ControlFlow(kind) => self.parse_control_flow_node(&kind),
-            TokenKind::Defer(kind) => self.parse_defer_node(&kind),
-            TokenKind::Eof => AstNode::EOF,
+            TokenKind::JavaScript(code) => Some(AstNode::JavaScript(JavaScript { code, span })),
+            TokenKind::Style(content) => Some(AstNode::Style(Style { content, span })),
+            TokenKind::HTML(content) => self.parse_html_node(&content, start),
+            TokenKind::Text(value) => Some(AstNode::Text(Text { value, span })),
+            TokenKind::TemplateExpression(expr) => {
+                let expression = expression(&expr, start + "{{".len());
+                Some(AstNode::TemplateExpression(TemplateExpression {
+                    expression,
+                    span,
+                }))
+            }
+            TokenKind::ControlFlow(kind) => self.parse_control_flow_node(&kind, start),
+            TokenKind::Defer(kind) => self.parse_defer_node(&kind, start),
+            // Parameters or braces without a keyword in front of them carry no meaning
+            TokenKind::BlockParameters(_) | TokenKind::BlockStart | TokenKind::BlockEnd => {
+                trace!(kind = ?token_kind, start, "skipping stray block token");
+                None
+            }
+            TokenKind::Eof => None,
+        }
+    }
+
+    /// Turns a tag token into an element, consuming its children up to the matching closing tag.
+    fn parse_html_node(&mut self, content: &str, start: usize) -> Option<AstNode> {
+        let start_tag_span = self.previous().span();
+
+        let Some(token) = HtmlTokenizer::with_offset(content, start).next_token() else {
+            self.errors
+                .push(OxcDiagnostic::error("Expected a tag").with_label(start_tag_span));
+            return None;
+        };
+        match token {
+            HtmlToken::StartTag(tag_name, attributes) => {
+                let (children, end_tag_span) = if is_void_element(&tag_name) {
+                    (Vec::new(), None)
+                } else {
+                    self.parse_children(Some(&tag_name))
+                };
+                let span = span(start, self.previous().end);
+                Some(AstNode::Element(Element {
+                    tag_name,
+                    attributes,
+                    children,
+                    span,
+                    start_tag_span,
+                    end_tag_span,
+                }))
+            }
+            HtmlToken::SelfClosingTag(tag_name, attributes) => Some(AstNode::Element(Element {
+                tag_name,
+                attributes,
+                children: Vec::new(),
+                span: start_tag_span,
+                start_tag_span,
+                end_tag_span: None,
+            })),
+            HtmlToken::Comment(value) => Some(AstNode::Comment(Comment {
+                value,
+                span: start_tag_span,
+            })),
+            // A closing tag without an open element
+            HtmlToken::EndTag(tag_name) => {
+                self.errors.push(
+                    OxcDiagnostic::error(format!("Unexpected closing tag `</{}>`", tag_name))
+                        .with_help("No element of that name is open here")
+                        .with_label(start_tag_span),
+                );
+                None
+            }
+            HtmlToken::Text(_) => {
+                self.errors
+                    .push(OxcDiagnostic::error("Expected a tag").with_label(start_tag_span));
+                None
+            }
+        }
+    }
+
+    /// Parses child nodes until the closing tag of `tag_name`, or until the end of the current
+    /// block when `tag_name` is `None`. Returns the span of the consumed terminator, if any.
+    fn parse_children(&mut self, tag_name: Option<&str>) -> (Vec<AstNode>, Option<Span>) {
+        self.open_elements.push(tag_name.map(str::to_string));
+        let children = self.parse_children_of(tag_name);
+        self.open_elements.pop();
+        children
+    }
+
+    fn parse_children_of(&mut self, tag_name: Option<&str>) -> (Vec<AstNode>, Option<Span>) {
+        let mut children = Vec::new();
+
+        while !self.is_at_end() {
+            match (&self.peek().kind, tag_name) {
+                (TokenKind::BlockEnd, None) => {
+                    return (children, Some(self.advance().span()));
+                }
+                // The enclosing block ends before the element is closed
+                (TokenKind::BlockEnd, Some(_)) => break,
+                (TokenKind::HTML(content), Some(tag_name))
+                    if Self::closing_tag_name(content).as_deref() == Some(tag_name) =>
+                {
+                    return (children, Some(self.advance().span()));
+                }
+                // Closing an enclosing element closes this one too, which the lexer reports
+                (TokenKind::HTML(content), Some(_))
+                    if Self::closing_tag_name(content)
+                        .is_some_and(|closing| self.closes_enclosing_element(&closing)) =>
+                {
+                    break
+                }
+                _ => {
+                    if let Some(node) = self.parse_node() {
+                        children.push(node);
+                    }
+                }
+            }
+        }
+
+        (children, None)
+    }
+
+    /// Whether `tag_name` names an element enclosing the current one within the same block.
+    fn closes_enclosing_element(&self, tag_name: &str) -> bool {
+        self.open_elements
+            .iter()
+            .rev()
+            .skip(1)
+            .map_while(Option::as_deref)
+            .any(|open| open == tag_name)
+    }
+
+    fn closing_tag_name(content: &str) -> Option<String> {
+        match HtmlTokenizer::new(content).next_token()? {
+            HtmlToken::EndTag(tag_name) => Some(tag_name),
+            _ => None,
         }
     }
 
-    fn parse_control_flow_node(&mut self, kind: &ControlFlowKind) -> AstNode {
+    /// Parses the optional `(parameters)` and `{ body }` following a block keyword.
+    fn parse_block(&mut self) -> (Option<Expression>, Vec<AstNode>) {
+        let parameters = self.parse_block_parameters();
+
+        let children = if self.peek().kind == TokenKind::BlockStart {
+            self.advance();
+            self.parse_children(None).0
+        } else {
+            Vec::new()
+        };
+
+        (parameters, children)
+    }
+
+    fn parse_block_parameters(&mut self) -> Option<Expression> {
+        match &self.peek().kind {
+            TokenKind::BlockParameters(parameters) => {
+                let parameters = expression(parameters, self.peek().start + "(".len());
+                self.advance();
+                Some(parameters)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_control_flow_node(&mut self, kind: &ControlFlowKind, start: usize) -> Option<AstNode> {
         match kind {
-            ControlFlowKind::If => AstNode::ControlFlow("@if".to_string()),
-            ControlFlowKind::ElseIf => AstNode::ControlFlow("@else if".to_string()),
-            ControlFlowKind::Else => AstNode::ControlFlow("@else".to_string()),
-            ControlFlowKind::For => AstNode::ControlFlow("@for".to_string()),
-            ControlFlowKind::Empty => AstNode::ControlFlow("@empty".to_string()),
-            ControlFlowKind::Switch => AstNode::ControlFlow("@switch".to_string()),
-            ControlFlowKind::Case => AstNode::ControlFlow("@case".to_string()),
-            ControlFlowKind::Default => AstNode::ControlFlow("@default".to_string()),
+            ControlFlowKind::If => Some(self.parse_if_block(start)),
+            ControlFlowKind::For => Some(self.parse_for_block(start)),
+            ControlFlowKind::Switch => Some(self.parse_switch_block(start)),
+            // Branches only make sense after their parent block; skip over them otherwise
+            ControlFlowKind::ElseIf
+            | ControlFlowKind::Else
+            | ControlFlowKind::Empty
+            | ControlFlowKind::Case
+            | ControlFlowKind::Default => {
+                self.parse_block();
+                None
+            }
+        }
+    }
+
+    fn parse_if_block(&mut self, start: usize) -> AstNode {
+        let (parameters, children) = self.parse_block();
+        let mut branches = vec![self.if_branch(parameters, children, start)];
+
+        loop {
+            let branch_start = self.peek().start;
+            match self.peek().kind {
+                TokenKind::ControlFlow(ControlFlowKind::ElseIf) => {
+                    self.advance();
+                    let (parameters, children) = self.parse_block();
+                    branches.push(self.if_branch(parameters, children, branch_start));
+                }
+                TokenKind::ControlFlow(ControlFlowKind::Else) => {
+                    self.advance();
+                    let (_, children) = self.parse_block();
+                    let span = span(branch_start, self.previous().end);
+                    branches.push(IfBranch {
+                        condition: None,
+                        alias: None,
+                        children,
+                        span,
+                    });
+                    break;
+                }
+                _ => break,
+            }
         }
+
+        AstNode::If(IfBlock {
+            branches,
+            span: span(start, self.previous().end),
+        })
     }
 
-    fn parse_defer_node(&mut self, kind: &DeferKind) -> AstNode {
+    fn if_branch(
+        &self,
+        parameters: Option<Expression>,
+        children: Vec<AstNode>,
+        start: usize,
+    ) -> IfBranch {
+        let mut parts = parameters
+            .map(|parameters| split_parameters(&parameters))
+            .unwrap_or_default()
+            .into_iter();
+        let condition = parts.next();
+        let alias = parts.find_map(|part| {
+            part.source
+                .starts_with("as ")
+                .then(|| slice(&part, "as ".len()))
+        });
+
+        IfBranch {
+            condition,
+            alias,
+            children,
+            span: span(start, self.previous().end),
+        }
+    }
+
+    fn parse_for_block(&mut self, start: usize) -> AstNode {
+        let (parameters, children) = self.parse_block();
+        let parts = parameters
+            .map(|parameters| split_parameters(&parameters))
+            .unwrap_or_default();
+        let mut parts = parts.into_iter();
+
+        let empty_expression = Expression {
+            source: String::new(),
+            span: span(start, start),
+        };
+        let (item, iterable) = parts
+            .next()
+            .and_then(|part| {
+                let index = part.source.find(" of ")?;
+                let item = expression(&part.source[..index], part.span.start as usize);
+                Some((item, slice(&part, index + " of ".len())))
+            })
+            .unwrap_or_else(|| (empty_expression.clone(), empty_expression));
+
+        let mut track = None;
+        let mut context_aliases = Vec::new();
+        for part in parts {
+            if part.source.starts_with("track ") {
+                track = Some(slice(&part, "track ".len()));
+            } else if part.source.starts_with("let ") {
+                let aliases = slice(&part, "let ".len());
+                context_aliases.extend(split_expression(&aliases, ',').iter().filter_map(
+                    |alias| {
+                        let index = alias.source.find('=')?;
+                        let name = expression(&alias.source[..index], alias.span.start as usize);
+                        Some((name, slice(alias, index + 1)))
+                    },
+                ));
+            }
+        }
+
+        let empty = if self.peek().kind == TokenKind::ControlFlow(ControlFlowKind::Empty) {
+            let empty_start = self.advance().start;
+            let children = self.parse_block().1;
+            Some(EmptyBlock {
+                children,
+                span: span(empty_start, self.previous().end),
+            })
+        } else {
+            None
+        };
+
+        AstNode::For(ForBlock {
+            item,
+            expression: iterable,
+            track,
+            context_aliases,
+            children,
+            empty,
+            span: span(start, self.previous().end),
+        })
+    }
+
+    fn parse_switch_block(&mut self, start: usize) -> AstNode {
+        let expression = self.parse_block_parameters().unwrap_or_else(|| Expression {
+            source: String::new(),
+            span: span(start, start),
+        });
+
+        let mut cases = Vec::new();
+        if self.peek().kind == TokenKind::BlockStart {
+            self.advance();
+            while !self.is_at_end() {
+                let case_start = self.peek().start;
+                match self.peek().kind {
+                    TokenKind::BlockEnd => {
+                        self.advance();
+                        break;
+                    }
+                    TokenKind::ControlFlow(ControlFlowKind::Case) => {
+                        self.advance();
+                        let (expression, children) = self.parse_block();
+                        let span = span(case_start, self.previous().end);
+                        cases.push(SwitchCase {
+                            expression,
+                            children,
+                            span,
+                        });
+                    }
+                    TokenKind::ControlFlow(ControlFlowKind::Default) => {
+                        self.advance();
+                        let (_, children) = self.parse_block();
+                        let span = span(case_start, self.previous().end);
+                        cases.push(SwitchCase {
+                            expression: None,
+                            children,
+                            span,
+                        });
+                    }
+                    // Only cases are allowed directly inside a switch
+                    _ => {
+                        self.parse_node();
+                    }
+                }
+            }
+        }
+
+        AstNode::Switch(SwitchBlock {
+            expression,
+            cases,
+            span: span(start, self.previous().end),
+        })
+    }
+
+    fn parse_defer_node(&mut self, kind: &DeferKind, start: usize) -> Option<AstNode> {
         match kind {
-            DeferKind::Defer => AstNode::ControlFlow("@defer".to_string()),
-            DeferKind::Placeholder => AstNode::ControlFlow("@placeholder".to_string()),
-            DeferKind::Loading => AstNode::ControlFlow("@loading".to_string()),
-            DeferKind::Error => AstNode::ControlFlow("@error".to_string()),
+            DeferKind::Defer => {
+                let (parameters, children) = self.parse_block();
+                let mut defer = DeferBlock {
+                    triggers: parameters.map(|p| defer_triggers(&p)).unwrap_or_default(),
+                    children,
+                    placeholder: None,
+                    loading: None,
+                    error: None,
+                    span: span(start, self.previous().end),
+                };
+
+                while let TokenKind::Defer(kind) = &self.peek().kind {
+                    let slot = match kind {
+                        DeferKind::Placeholder => &mut defer.placeholder,
+                        DeferKind::Loading => &mut defer.loading,
+                        DeferKind::Error => &mut defer.error,
+                        DeferKind::Defer => break,
+                    };
+                    let sub_block_start = self.advance().start;
+                    let (parameters, children) = self.parse_block();
+                    let span = span(sub_block_start, self.previous().end);
+                    let mut sub_block = DeferSubBlock {
+                        minimum: None,
+                        after: None,
+                        children,
+                        span,
+                    };
+                    let parts = parameters
+                        .map(|parameters| split_parameters(&parameters))
+                        .unwrap_or_default();
+                    for part in parts {
+                        if part.source.starts_with("minimum ") {
+                            sub_block.minimum = Some(slice(&part, "minimum ".len()));
+                        } else if part.source.starts_with("after ") {
+                            sub_block.after = Some(slice(&part, "after ".len()));
+                        }
+                    }
+                    *slot = Some(sub_block);
+                    defer.span = defer.span.merge(&span);
+                }
+
+                Some(AstNode::Defer(defer))
+            }
+            // Sub-blocks without a preceding `@defer`
+            DeferKind::Placeholder | DeferKind::Loading | DeferKind::Error => {
+                self.parse_block();
+                None
+            }
         }
     }
 
@@ -84,4 +480,251 @@ impl Parser {
     fn previous(&self) -> &Token {
         self.tokens.get(self.pos - 1).unwrap()
     }
-}
\ No newline at end of file
+}
+
+fn span(start: usize, end: usize) -> Span {
+    Span::new(start as u32, end as u32)
+}
+
+/// Builds an expression from raw source found at byte `offset`, trimming surrounding whitespace.
+fn expression(raw: &str, offset: usize) -> Expression {
+    let leading = raw.len() - raw.trim_start().len();
+    let source = raw.trim();
+    let start = offset + leading;
+    Expression {
+        source: source.to_string(),
+        span: span(start, start + source.len()),
+    }
+}
+
+/// The part of `expression` from byte `from` onwards.
+fn slice(expression: &Expression, from: usize) -> Expression {
+    self::expression(
+        &expression.source[from..],
+        expression.span.start as usize + from,
+    )
+}
+
+/// Splits on every `separator`, with spans for each trimmed piece.
+fn split_expression(expression: &Expression, separator: char) -> Vec<Expression> {
+    let offset = expression.span.start as usize;
+    let mut start = 0;
+    let mut parts = Vec::new();
+    for (index, _) in expression.source.match_indices(separator) {
+        parts.push(self::expression(
+            &expression.source[start..index],
+            offset + start,
+        ));
+        start = index + separator.len_utf8();
+    }
+    parts.push(self::expression(
+        &expression.source[start..],
+        offset + start,
+    ));
+    parts
+}
+
+/// Splits block parameters on top-level `;`, leaving separators inside strings and brackets alone.
+fn split_parameters(parameters: &Expression) -> Vec<Expression> {
+    let source = &parameters.source;
+    let offset = parameters.span.start as usize;
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+
+    for (index, ch) in source.char_indices() {
+        match (quote, ch) {
+            (Some(delimiter), ch) if ch == delimiter => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(ch),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth -= 1,
+            (None, ';') if depth == 0 => {
+                parts.push(expression(&source[start..index], offset + start));
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(expression(&source[start..], offset + start));
+
+    parts
+        .into_iter()
+        .filter(|part| !part.source.is_empty())
+        .collect()
+}
+
+/// Parses `@defer` parameters, e.g. `on viewport, timer(1s); prefetch when ready`.
+fn defer_triggers(parameters: &Expression) -> Vec<DeferTrigger> {
+    let mut triggers = Vec::new();
+    for part in split_parameters(parameters) {
+        let (prefetch, part) = match part.source.strip_prefix("prefetch ") {
+            Some(_) => (true, slice(&part, "prefetch ".len())),
+            None => (false, part),
+        };
+        if part.source.starts_with("when ") {
+            triggers.push(DeferTrigger {
+                name: expression("when", part.span.start as usize),
+                parameter: Some(slice(&part, "when ".len())),
+                prefetch,
+                span: part.span,
+            });
+        } else if part.source.starts_with("on ") {
+            let events = slice(&part, "on ".len());
+            triggers.extend(split_expression(&events, ',').into_iter().map(|event| {
+                let offset = event.span.start as usize;
+                let (name, parameter) = match event.source.find('(') {
+                    Some(index) => {
+                        let end = event.source.rfind(')').filter(|&end| end > index);
+                        let end = end.unwrap_or(event.source.len());
+                        let parameter =
+                            expression(&event.source[index + 1..end], offset + index + 1);
+                        let parameter = (!parameter.source.is_empty()).then_some(parameter);
+                        (expression(&event.source[..index], offset), parameter)
+                    }
+                    None => (event.clone(), None),
+                };
+                DeferTrigger {
+                    name,
+                    parameter,
+                    prefetch,
+                    span: event.span,
+                }
+            }));
+        } else {
+            // Not a trigger, reported by the compiler
+            triggers.push(DeferTrigger {
+                name: part.clone(),
+                parameter: None,
+                prefetch,
+                span: part.span,
+            });
+        }
+    }
+    triggers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::treaty::lexer::Lexer;
+
+    /// Parses markup, returning its nodes and the messages of the errors the parser found.
+    fn parse(template: &str) -> (Vec<AstNode>, Vec<String>) {
+        let mut lexer = Lexer::template(template);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token() {
+            tokens.push(token);
+        }
+        let mut parser = Parser::new(tokens);
+        let ast = parser.parse();
+        let errors = parser.take_errors();
+        let errors = errors.iter().map(|error| error.message.to_string());
+        (ast.nodes, errors.collect())
+    }
+
+    /// The nodes with their spans, e.g. `div@0..12[text@5..6]`, leaving out whitespace.
+    fn outline(nodes: &[AstNode]) -> String {
+        let outlines = nodes.iter().filter_map(|node| {
+            let (name, children) = match node {
+                AstNode::Text(text) if text.value.trim().is_empty() => return None,
+                AstNode::Element(element) => (element.tag_name.clone(), outline(&element.children)),
+                AstNode::Text(_) => ("text".to_string(), String::new()),
+                AstNode::TemplateExpression(interpolation) => (
+                    format!("{{{{{}}}}}", interpolation.expression.source),
+                    String::new(),
+                ),
+                AstNode::If(block) => {
+                    let branches = block.branches.iter().map(|branch| {
+                        let condition = branch.condition.as_ref();
+                        let condition = condition.map_or("else", |condition| &condition.source);
+                        let span = branch.span;
+                        let children = outline(&branch.children);
+                        format!("{}@{}..{}[{}]", condition, span.start, span.end, children)
+                    });
+                    ("if".to_string(), branches.collect::<Vec<_>>().join(" "))
+                }
+                AstNode::For(block) => (
+                    format!("for {}", block.item.source),
+                    outline(&block.children),
+                ),
+                node => (format!("{:?}", node), String::new()),
+            };
+            let span = node.span();
+            Some(match children.is_empty() {
+                true => format!("{}@{}..{}", name, span.start, span.end),
+                false => format!("{}@{}..{}[{}]", name, span.start, span.end, children),
+            })
+        });
+        outlines.collect::<Vec<_>>().join(" ")
+    }
+
+    #[test]
+    fn nested_elements_and_blocks() {
+        let template = "<ul>@for (item of items; track item) {<li>{{ item }}</li>}</ul>\n@if (a) {<p>a</p>} @else {b}";
+        let (nodes, errors) = parse(template);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(
+            outline(&nodes),
+            "ul@0..63[for item@4..58[li@38..57[{{item}}@42..52]]] \
+             if@64..92[a@64..82[p@73..81[text@76..77]] else@83..92[text@90..91]]"
+        );
+
+        let AstNode::Element(ul) = &nodes[0] else {
+            panic!("expected an element, got {:?}", nodes[0]);
+        };
+        assert_eq!(ul.start_tag_span, Span::new(0, 4));
+        assert_eq!(ul.end_tag_span, Some(Span::new(58, 63)));
+        let AstNode::For(block) = &ul.children[0] else {
+            panic!("expected a for block, got {:?}", ul.children[0]);
+        };
+        assert_eq!(block.expression.source, "items");
+        assert_eq!(block.expression.span, Span::new(18, 23));
+        assert_eq!(
+            block.track.as_ref().map(|track| track.span),
+            Some(Span::new(31, 35))
+        );
+    }
+
+    #[test]
+    fn void_and_self_closing_elements_have_no_end_tag() {
+        let (nodes, errors) = parse("<p><br><img src=\"a.png\" />b</p>");
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(outline(&nodes), "p@0..31[br@3..7 img@7..26 text@26..27]");
+        let AstNode::Element(p) = &nodes[0] else {
+            panic!("expected an element, got {:?}", nodes[0]);
+        };
+        for child in &p.children[..2] {
+            let AstNode::Element(element) = child else {
+                panic!("expected an element, got {:?}", child);
+            };
+            assert_eq!(element.start_tag_span, element.span);
+            assert_eq!(element.end_tag_span, None);
+        }
+    }
+
+    #[test]
+    fn stray_closing_tags_are_reported() {
+        let (nodes, errors) = parse("<div></section></div>");
+        assert_eq!(errors, ["Unexpected closing tag `</section>`"]);
+        assert_eq!(outline(&nodes), "div@0..21");
+
+        // A block cannot close an element opened outside of it, nor the other way round
+        let (nodes, errors) = parse("@if (a) {<div>x}</div>");
+        assert_eq!(errors, ["Unexpected closing tag `</div>`"]);
+        assert_eq!(outline(&nodes), "if@0..16[a@0..16[div@9..15[text@14..15]]]");
+        let (_, errors) = parse("<div>@if (a) {</div>}");
+        assert_eq!(errors, ["Unexpected closing tag `</div>`"]);
+    }
+
+    #[test]
+    fn closing_an_ancestor_closes_the_element() {
+        let (nodes, errors) = parse("<main><b>bold</main><p>after</p>");
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(
+            outline(&nodes),
+            "main@0..20[b@6..13[text@9..13]] p@20..32[text@23..28]"
+        );
+    }
+}
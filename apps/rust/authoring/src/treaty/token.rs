@@ -1,3 +1,5 @@
+use oxc_span::Span;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub kind: TokenKind,
@@ -9,6 +11,10 @@ impl Token {
     pub fn new(kind: TokenKind, start: usize, end: usize) -> Self {
         Token { kind, start, end }
     }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.start as u32, self.end as u32)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -36,8 +42,15 @@ pub enum TokenKind {
     JavaScript(String),
     HTML(String),
     Style(String),
+    Text(String),
+    /// Raw text between `{{` and `}}`; the token spans the braces as well.
     TemplateExpression(String),
     Defer(DeferKind),
     ControlFlow(ControlFlowKind),
+    /// Raw text between the parentheses of a block, e.g. `item of items; track item.id`.
+    /// The token spans the parentheses as well.
+    BlockParameters(String),
+    BlockStart,
+    BlockEnd,
     Eof,
-}
\ No newline at end of file
+}